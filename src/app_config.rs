@@ -1,13 +1,13 @@
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use console::style;
 use serde::Deserialize;
 use std::{
     collections::HashMap,
-    fs,
+    env, fs,
     path::{Path, PathBuf},
 };
 
-use crate::info;
+use crate::{emoji, info, Vcs};
 
 pub const CONFIG_FILE_NAME: &str = "cargo-generate.toml";
 
@@ -16,12 +16,65 @@ pub struct AppConfig {
     pub defaults: Option<DefaultsConfig>,
     pub favorites: Option<HashMap<String, FavoriteConfig>>,
     pub values: Option<HashMap<String, toml::Value>>,
+    pub profiles: Option<HashMap<String, ProfileConfig>>,
 }
 
 impl AppConfig {
     pub fn get_favorite_cfg(&self, favorite_name: &str) -> Option<&FavoriteConfig> {
         self.favorites.as_ref().and_then(|f| f.get(favorite_name))
     }
+
+    /// Merges the profile `name` into the `[defaults]` and `[values]`, the ones of the profile take precedence.
+    pub fn apply_profile(&mut self, name: &str) -> Result<()> {
+        let profile = match self.profiles.as_mut().and_then(|p| p.remove(name)) {
+            Some(profile) => profile,
+            None => {
+                let mut known = self
+                    .profiles
+                    .iter()
+                    .flat_map(|profiles| profiles.keys())
+                    .map(String::as_str)
+                    .collect::<Vec<_>>();
+                known.sort_unstable();
+                bail!(
+                    "{} {} {}",
+                    emoji::ERROR,
+                    style(format!(
+                        "Profile `{}` is not defined in the app config.",
+                        name
+                    ))
+                    .bold()
+                    .red(),
+                    style(format!("Known profiles: {}", known.join(", "))).yellow(),
+                );
+            }
+        };
+
+        let defaults = self.defaults.get_or_insert_with(Default::default);
+        defaults.ssh_identity = profile
+            .defaults
+            .ssh_identity
+            .or(defaults.ssh_identity.take());
+        defaults.cache_templates = profile
+            .defaults
+            .cache_templates
+            .or(defaults.cache_templates);
+        defaults.vcs = profile.defaults.vcs.or(defaults.vcs);
+        if let Some(values) = profile.values {
+            self.values
+                .get_or_insert_with(Default::default)
+                .extend(values);
+        }
+        Ok(())
+    }
+}
+
+/// A named `[profiles.<name>]`, selected by `--profile <name>`
+#[derive(Deserialize, Default)]
+pub struct ProfileConfig {
+    #[serde(flatten)]
+    pub defaults: DefaultsConfig,
+    pub values: Option<HashMap<String, toml::Value>>,
 }
 
 #[derive(Deserialize, Default)]
@@ -34,10 +87,68 @@ pub struct FavoriteConfig {
     pub values: Option<HashMap<String, toml::Value>>,
 }
 
+impl FavoriteConfig {
+    /// Expands `${ENV_VAR}` in `git`, `branch` and `path`, e.g. to share favorites across machines
+    /// where the host differs
+    pub fn expand_env_vars(&self, favorite_name: &str) -> Result<Self> {
+        let expand = |value: &str| expand_env_vars(value, favorite_name);
+        Ok(Self {
+            description: self.description.clone(),
+            git: self.git.as_deref().map(expand).transpose()?,
+            branch: self.branch.as_deref().map(expand).transpose()?,
+            subfolder: self.subfolder.clone(),
+            path: match self.path.as_deref().map(Path::to_str) {
+                Some(Some(path)) => Some(expand(path)?.into()),
+                _ => self.path.clone(),
+            },
+            values: self.values.clone(),
+        })
+    }
+}
+
+fn expand_env_vars(value: &str, favorite_name: &str) -> Result<String> {
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        expanded.push_str(&rest[..start]);
+        let name_and_rest = &rest[start + 2..];
+        let end = match name_and_rest.find('}') {
+            Some(end) => end,
+            None => bail!(
+                "{} {} {}",
+                emoji::ERROR,
+                style(format!("Favorite `{}`:", favorite_name)).bold().red(),
+                style(format!("unclosed `${{` in `{}`", value)).yellow(),
+            ),
+        };
+        let var_name = &name_and_rest[..end];
+        match env::var(var_name) {
+            Ok(var_value) => expanded.push_str(&var_value),
+            Err(_) => bail!(
+                "{} {} {}",
+                emoji::ERROR,
+                style(format!("Favorite `{}`:", favorite_name)).bold().red(),
+                style(format!(
+                    "the environment variable `{}` is not set",
+                    var_name
+                ))
+                .yellow(),
+            ),
+        }
+        rest = &name_and_rest[end + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
 #[derive(Deserialize, Default)]
 pub struct DefaultsConfig {
     /// relates to `crate::Args::ssh_identity`
     pub ssh_identity: Option<PathBuf>,
+    /// keep cloned git templates in a cache, to only fetch updates on the next run
+    pub cache_templates: Option<bool>,
+    /// relates to `crate::Args::vcs`
+    pub vcs: Option<Vcs>,
 }
 
 impl TryFrom<&Path> for AppConfig {
@@ -85,3 +196,69 @@ Use --config option, or place {} in $CARGO_HOME."#,
             )
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_vars_are_expanded() {
+        env::set_var("CARGO_GENERATE_TEST_FAV_HOST", "git.example.com");
+        assert_eq!(
+            expand_env_vars(
+                "https://${CARGO_GENERATE_TEST_FAV_HOST}/org/template.git",
+                "demo"
+            )
+            .unwrap(),
+            "https://git.example.com/org/template.git"
+        );
+        assert_eq!(
+            expand_env_vars("no-vars-at-all", "demo").unwrap(),
+            "no-vars-at-all"
+        );
+    }
+
+    #[test]
+    fn a_profile_takes_precedence_over_the_defaults_and_values() {
+        let mut config: AppConfig = toml::from_str(
+            r#"
+            [defaults]
+            ssh_identity = "~/.ssh/id_personal"
+            cache_templates = true
+
+            [values]
+            gh_username = "me"
+            license = "MIT"
+
+            [profiles.work]
+            ssh_identity = "~/.ssh/id_work"
+            vcs = "none"
+
+            [profiles.work.values]
+            gh_username = "me-at-work"
+            "#,
+        )
+        .unwrap();
+
+        assert!(config.apply_profile("home").is_err());
+        config.apply_profile("work").unwrap();
+
+        let defaults = config.defaults.unwrap();
+        assert_eq!(defaults.ssh_identity, Some(PathBuf::from("~/.ssh/id_work")));
+        assert_eq!(defaults.cache_templates, Some(true));
+        assert!(matches!(defaults.vcs, Some(Vcs::None)));
+        let values = config.values.unwrap();
+        assert_eq!(values["gh_username"].as_str(), Some("me-at-work"));
+        assert_eq!(values["license"].as_str(), Some("MIT"));
+    }
+
+    #[test]
+    fn unset_env_vars_are_named_in_the_error() {
+        env::remove_var("CARGO_GENERATE_TEST_FAV_UNSET");
+        let error = expand_env_vars("${CARGO_GENERATE_TEST_FAV_UNSET}/template", "demo")
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("CARGO_GENERATE_TEST_FAV_UNSET"));
+        assert!(expand_env_vars("${UNCLOSED", "demo").is_err());
+    }
+}
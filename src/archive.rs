@@ -0,0 +1,374 @@
+//! Handle `--archive` and template locations pointing to `.tar.gz`, `.tar` or `.zip` files,
+//! and writing the generated project as a tar for `--to-stdout-tar`
+
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use console::style;
+use flate2::read::GzDecoder;
+use tempfile::{NamedTempFile, TempDir};
+use walkdir::WalkDir;
+
+use crate::{emoji, info, link_stays_within, Symlinks};
+
+const ARCHIVE_EXTENSIONS: [&str; 4] = [".tar.gz", ".tgz", ".tar", ".zip"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ArchiveFormat {
+    TarGz,
+    Tar,
+    Zip,
+}
+
+/// checks if the given location (path or url) looks like a supported archive
+pub fn is_archive(location: &str) -> bool {
+    let location = location.to_lowercase();
+    // urls might carry a query string or a fragment, like release assets often do
+    let location = location.split(['?', '#']).next().unwrap_or_default();
+    ARCHIVE_EXTENSIONS
+        .iter()
+        .any(|extension| location.ends_with(extension))
+}
+
+fn is_remote(location: &str) -> bool {
+    location.starts_with("https://") || location.starts_with("http://")
+}
+
+/// extracts a local or remote archive into a fresh temp dir, remote ones are refused `offline`
+///
+/// If the archive contains nothing but a single folder, like release tarballs mostly do,
+/// the content of that folder becomes the root of the template.
+pub fn extract_archive_into_temp(location: &str, offline: bool) -> Result<TempDir> {
+    if offline && is_remote(location) {
+        return Err(crate::refuse_to_fetch_offline(location));
+    }
+
+    // keep the downloaded file alive until the archive is unpacked
+    let downloaded: NamedTempFile;
+    let archive_path = if is_remote(location) {
+        downloaded = download(location)?;
+        downloaded.path().to_path_buf()
+    } else {
+        PathBuf::from(location)
+    };
+
+    extract_archive_file_into_temp(&archive_path, location)
+}
+
+/// extracts the archive at `archive_path` into a fresh temp dir, like [`extract_archive_into_temp`],
+/// `location` is where it has been taken from, for errors
+pub fn extract_archive_file_into_temp(archive_path: &Path, location: &str) -> Result<TempDir> {
+    let archive_dir = tempfile::tempdir()?;
+    let format = sniff_format(archive_path)?;
+    unpack(archive_path, format, archive_dir.path()).with_context(|| {
+        format!(
+            "{} {} `{}`",
+            emoji::ERROR,
+            style("Unable to extract archive").bold().red(),
+            style(location).bold()
+        )
+    })?;
+    flatten_single_root_dir(archive_dir.path())?;
+
+    Ok(archive_dir)
+}
+
+fn download(url: &str) -> Result<NamedTempFile> {
+    info!(
+        "{} `{}`",
+        style("Downloading archive").bold(),
+        style(url).bold().yellow()
+    );
+    let response = ureq::get(url)
+        .call()
+        .with_context(|| format!("Failed to download archive from {}", url))?;
+
+    let mut file = NamedTempFile::new()?;
+    io::copy(&mut response.into_reader(), &mut file)?;
+    Ok(file)
+}
+
+/// detects the archive format from its magic bytes, the extension alone is not trusted
+fn sniff_format(archive_path: &Path) -> Result<ArchiveFormat> {
+    let file = File::open(archive_path)
+        .with_context(|| format!("Unable to open archive {}", archive_path.display()))?;
+    let mut header = Vec::with_capacity(262);
+    file.take(262).read_to_end(&mut header)?;
+
+    if header.starts_with(&[0x1f, 0x8b]) {
+        Ok(ArchiveFormat::TarGz)
+    } else if header.starts_with(b"PK\x03\x04") {
+        Ok(ArchiveFormat::Zip)
+    } else if header.len() == 262 && &header[257..262] == b"ustar" {
+        Ok(ArchiveFormat::Tar)
+    } else {
+        bail!(
+            "{} {} {}",
+            emoji::ERROR,
+            style("Archive Error:").bold().red(),
+            style("Unsupported archive format. Only `.tar.gz`, `.tar` and `.zip` are supported.")
+                .bold()
+                .red(),
+        )
+    }
+}
+
+fn unpack(archive_path: &Path, format: ArchiveFormat, dest: &Path) -> Result<()> {
+    let file = File::open(archive_path)?;
+    match format {
+        ArchiveFormat::TarGz => tar::Archive::new(GzDecoder::new(file)).unpack(dest)?,
+        ArchiveFormat::Tar => tar::Archive::new(file).unpack(dest)?,
+        ArchiveFormat::Zip => zip::ZipArchive::new(file)?.extract(dest)?,
+    }
+    Ok(())
+}
+
+fn flatten_single_root_dir(dir: &Path) -> Result<()> {
+    let entries = fs::read_dir(dir)?.collect::<io::Result<Vec<_>>>()?;
+    if entries.len() != 1 || !entries[0].file_type()?.is_dir() {
+        return Ok(());
+    }
+
+    // move the root folder aside first, it could contain an entry with the same name
+    let staging_dir = tempfile::tempdir_in(dir)?;
+    let root = staging_dir.path().join("root");
+    fs::rename(entries[0].path(), &root)?;
+    for entry in fs::read_dir(&root)? {
+        let entry = entry?;
+        fs::rename(entry.path(), dir.join(entry.file_name()))?;
+    }
+
+    Ok(())
+}
+
+/// Writes the expanded templates as a tar archive, instead of copying them into their output dirs.
+///
+/// Each template folder ends up at its output dir, relative to `root`, the render extensions are
+/// stripped like on a regular copy. Returns the paths of all files, as if they had been copied.
+pub fn write_tar(
+    outputs: &[(PathBuf, PathBuf, String)],
+    root: &Path,
+    symlinks: Symlinks,
+    writer: impl Write,
+) -> Result<Vec<PathBuf>> {
+    let mut builder = tar::Builder::new(writer);
+    // timestamps and owners of the temp dir are meaningless, keep the archive reproducible
+    builder.mode(tar::HeaderMode::Deterministic);
+
+    let mut files = vec![];
+    for (template_folder, output_dir, render_extension) in outputs {
+        let entries = WalkDir::new(template_folder)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|entry| entry.file_name() != ".git");
+        for entry in entries {
+            let entry = entry?;
+            let relative_path = entry.path().strip_prefix(template_folder)?;
+            let dst_path = match relative_path.file_name() {
+                Some(filename) if entry.file_type().is_file() => {
+                    let filename = filename.to_string_lossy();
+                    let filename = filename
+                        .strip_suffix(render_extension.as_str())
+                        .unwrap_or(&filename);
+                    output_dir.join(relative_path.with_file_name(filename))
+                }
+                _ => output_dir.join(relative_path),
+            };
+            let name = dst_path.strip_prefix(root)?;
+
+            if entry.file_type().is_dir() {
+                builder.append_dir(name, entry.path())?;
+            } else if entry.file_type().is_file() {
+                builder.append_path_with_name(entry.path(), name)?;
+                files.push(dst_path);
+            } else if entry.file_type().is_symlink() && symlinks != Symlinks::Unsupported {
+                let target = fs::read_link(entry.path())?;
+                let link_dir = relative_path.parent().unwrap_or(relative_path);
+                if symlinks == Symlinks::Recreate && !link_stays_within(link_dir, &target) {
+                    bail!(
+                        "{} {} `{}` -> `{}`",
+                        emoji::ERROR,
+                        style("Symbolic link points outside of the project:")
+                            .bold()
+                            .red(),
+                        style(dst_path.display()).bold(),
+                        style(target.display()).bold(),
+                    );
+                }
+                let mut header = tar::Header::new_gnu();
+                header.set_entry_type(tar::EntryType::Symlink);
+                header.set_size(0);
+                header.set_mode(0o777);
+                builder.append_link(&mut header, name, &target)?;
+                files.push(dst_path);
+            } else {
+                bail!(
+                    "{} {}",
+                    emoji::WARN,
+                    style("Symbolic links not supported, see `--allow-symlinks`")
+                        .bold()
+                        .red(),
+                )
+            }
+        }
+    }
+    builder.into_inner()?.flush()?;
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{write::GzEncoder, Compression};
+
+    fn create_tar_gz(path: &Path, files: &[(&str, &str)]) {
+        let encoder = GzEncoder::new(File::create(path).unwrap(), Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        for (name, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, name, content.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    #[test]
+    fn should_detect_archives_by_extension() {
+        assert!(is_archive("template.tar.gz"));
+        assert!(is_archive("template.tgz"));
+        assert!(is_archive("template.TAR"));
+        assert!(is_archive("https://example.com/template.zip?raw=true"));
+        assert!(!is_archive("https://github.com/foo/bar.git"));
+        assert!(!is_archive("foo/bar"));
+    }
+
+    #[test]
+    fn should_extract_and_flatten_single_root_dir() {
+        let tmp = tempfile::tempdir().unwrap();
+        let archive = tmp.path().join("template.tar.gz");
+        create_tar_gz(
+            &archive,
+            &[
+                ("template-1.0/Cargo.toml", "[package]"),
+                ("template-1.0/template-1.0/nested.txt", "nested"),
+            ],
+        );
+
+        let extracted = extract_archive_into_temp(archive.to_str().unwrap(), false).unwrap();
+
+        assert!(extracted.path().join("Cargo.toml").is_file());
+        assert!(extracted.path().join("template-1.0/nested.txt").is_file());
+    }
+
+    #[test]
+    fn should_reject_unsupported_formats() {
+        let tmp = tempfile::tempdir().unwrap();
+        let archive = tmp.path().join("template.zip");
+        fs::write(&archive, "definitely not a zip file").unwrap();
+
+        assert!(extract_archive_into_temp(archive.to_str().unwrap(), false).is_err());
+    }
+
+    #[test]
+    fn should_write_the_outputs_relative_to_the_root() {
+        let tmp = tempfile::tempdir().unwrap();
+        let template = tmp.path().join("template");
+        fs::create_dir_all(template.join("src")).unwrap();
+        fs::create_dir_all(template.join(".git")).unwrap();
+        fs::write(template.join("Cargo.toml.liquid"), "[package]").unwrap();
+        fs::write(template.join("src/main.rs"), "fn main() {}").unwrap();
+        fs::write(template.join(".git/HEAD"), "ref: refs/heads/main").unwrap();
+        let root = tmp.path().join("out");
+        let output_dir = root.join("my-project");
+
+        let mut archive = vec![];
+        let files = write_tar(
+            &[(template, output_dir.clone(), ".liquid".to_string())],
+            &root,
+            Symlinks::Unsupported,
+            &mut archive,
+        )
+        .unwrap();
+
+        assert_eq!(
+            files,
+            vec![
+                output_dir.join("Cargo.toml"),
+                output_dir.join("src/main.rs")
+            ]
+        );
+        let names: Vec<_> = tar::Archive::new(archive.as_slice())
+            .entries()
+            .unwrap()
+            .map(|entry| entry.unwrap().path().unwrap().to_path_buf())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                PathBuf::from("my-project"),
+                PathBuf::from("my-project/Cargo.toml"),
+                PathBuf::from("my-project/src"),
+                PathBuf::from("my-project/src/main.rs"),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_refuse_to_download_offline() {
+        let error =
+            extract_archive_into_temp("https://example.com/template.tar.gz", true).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("refusing to fetch https://example.com/template.tar.gz"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn should_write_symlinks_only_when_allowed() {
+        let tmp = tempfile::tempdir().unwrap();
+        let template = tmp.path().join("template");
+        fs::create_dir_all(&template).unwrap();
+        fs::write(template.join("README.md"), "readme").unwrap();
+        std::os::unix::fs::symlink("README.md", template.join("link")).unwrap();
+        let root = tmp.path().join("out");
+        let outputs = [(template, root.join("my-project"), ".liquid".to_string())];
+
+        let error = write_tar(&outputs, &root, Symlinks::Unsupported, vec![]).unwrap_err();
+        assert!(error.to_string().contains("see `--allow-symlinks`"));
+
+        let mut archive = vec![];
+        write_tar(&outputs, &root, Symlinks::Recreate, &mut archive).unwrap();
+        let mut archive = tar::Archive::new(archive.as_slice());
+        let link = archive
+            .entries()
+            .unwrap()
+            .map(Result::unwrap)
+            .find(|entry| entry.header().entry_type() == tar::EntryType::Symlink)
+            .unwrap();
+        assert_eq!(link.path().unwrap(), Path::new("my-project/link"));
+        assert_eq!(link.link_name().unwrap().unwrap(), Path::new("README.md"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn should_refuse_symlinks_pointing_outside_of_the_project() {
+        let tmp = tempfile::tempdir().unwrap();
+        let template = tmp.path().join("template");
+        fs::create_dir_all(&template).unwrap();
+        std::os::unix::fs::symlink("../secret", template.join("link")).unwrap();
+        let root = tmp.path().join("out");
+        let outputs = [(template, root.join("my-project"), ".liquid".to_string())];
+
+        let error = write_tar(&outputs, &root, Symlinks::Recreate, vec![]).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("Symbolic link points outside of the project"));
+    }
+}
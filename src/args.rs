@@ -1,12 +1,15 @@
 use std::{
+    num::NonZeroUsize,
     path::{Path, PathBuf},
     str::FromStr,
 };
 
 use anyhow::{anyhow, Result};
-use clap::{Args, Parser};
+use clap::{Args, Command, CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use serde::Deserialize;
 
-use crate::git;
+use crate::{git, hg};
 
 #[derive(Parser)]
 #[clap(name = "cargo generate")]
@@ -21,6 +24,7 @@ pub enum Cli {
 
 #[derive(Clone, Debug, Args)]
 #[clap(arg_required_else_help(true))]
+#[clap(args_conflicts_with_subcommands(true))]
 #[clap(version)]
 #[clap(about)]
 pub struct GenerateArgs {
@@ -32,7 +36,7 @@ pub struct GenerateArgs {
         long,
         action,
         conflicts_with_all(&[
-            "git", "path", "subfolder", "branch",
+            "git", "hg", "path", "archive", "subfolder", "subfolders", "branch", "tag", "rev",
             "name",
             "force",
             "silent",
@@ -40,6 +44,7 @@ pub struct GenerateArgs {
             "lib",
             "bin",
             "define",
+            "define-from-json",
             "init",
             "template-values-file",
             "ssh-identity"
@@ -47,38 +52,126 @@ pub struct GenerateArgs {
     )]
     pub list_favorites: bool,
 
+    /// List all placeholders the template declares, including conditional ones,
+    /// without prompting or generating anything
+    #[clap(
+        long,
+        action,
+        conflicts_with_all(&[
+            "list-favorites",
+            "name",
+            "directory-name",
+            "force",
+            "silent",
+            "vcs",
+            "init",
+            "destination",
+            "dry-run"
+        ])
+    )]
+    pub list_variables: bool,
+
+    /// Print the folder the template resolves to, and the `cargo-generate.toml` found for it,
+    /// without prompting or generating anything. Useful when a template generates nothing,
+    /// as the wrong folder has been chosen.
+    #[clap(
+        long,
+        action,
+        conflicts_with_all(&[
+            "list-favorites",
+            "list-variables",
+            "name",
+            "directory-name",
+            "force",
+            "silent",
+            "vcs",
+            "init",
+            "destination",
+            "dry-run"
+        ])
+    )]
+    pub print_template_dir: bool,
+
+    /// The template to expand if the template repository contains several `cargo-generate.toml`,
+    /// given as the path of its dir, like in the prompt. Fails if no template is at this path.
+    #[clap(
+        long,
+        value_name = "PATH",
+        alias = "template-subdir-prompt-default",
+        value_parser
+    )]
+    pub select_template: Option<String>,
+
     /// Directory to create / project name; if the name isn't in kebab-case, it will be converted
     /// to kebab-case unless `--force` is given.
+    /// If not given, the name is taken from `--destination`, or from the current dir with `--init`.
     #[clap(long, short, value_parser)]
     pub name: Option<String>,
 
+    /// Name of the directory to create, instead of the one derived from the project name.
+    /// The project name is still used as is for `{{project-name}}`.
+    #[clap(long, value_parser, conflicts_with = "init")]
+    pub directory_name: Option<String>,
+
     /// Don't convert the project name to kebab-case before creating the directory.
     /// Note that cargo generate won't overwrite an existing directory, even if `--force` is given.
     #[clap(long, short, action)]
     pub force: bool,
 
+    /// Name the directory exactly like the project name, instead of converting it to kebab-case.
+    /// Unlike `--force`, `{{project-name}}` is still converted.
+    #[clap(long, action)]
+    pub no_rename: bool,
+
     /// Enables more verbose output.
     #[clap(long, short, action)]
     pub verbose: bool,
 
-    /// Pass template values through a file
-    /// Values should be in the format `key=value`, one per line
-    #[clap(long, value_parser)]
+    /// Only print warnings and errors, while still prompting for the placeholders.
+    /// Unlike `--silent`, values don't have to be given up front.
+    #[clap(long, short, action, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// How warnings and progress are written: `human` (styled, with emojis), `plain` (`<level>: <message>` lines),
+    /// `json` (one object per line) or `log` (events of the `log` crate, for an application with its own logger).
+    #[clap(
+        long,
+        env = "CARGO_GENERATE_LOG_FORMAT",
+        default_value = "human",
+        value_parser
+    )]
+    pub log_format: LogFormat,
+
+    /// Pass template values through a TOML file with a `[values]` table, like `[values]\nkey = "value"`.
+    /// Takes precedence over the file of `CARGO_GENERATE_TEMPLATE_VALUES_FILE`.
+    #[clap(long, alias = "values-file", value_parser)]
     pub template_values_file: Option<String>,
 
     /// If silent mode is set all variables will be
     /// extracted from the template_values_file.
-    /// If a value is missing the project generation will fail
-    #[clap(long, short, requires("name"), action)]
+    /// If a value is missing the project generation will fail.
+    /// Progress output is reduced to the final result.
+    /// Without `--name` the project name has to be inferable, e.g. from `--destination`.
+    #[clap(long, short, action)]
     pub silent: bool,
 
     /// Use specific configuration file. Defaults to $CARGO_HOME/cargo-generate or $HOME/.cargo/cargo-generate
     #[clap(short, long, value_parser)]
     pub config: Option<PathBuf>,
 
-    /// Specify the VCS used to initialize the generated template.
-    #[clap(long, default_value = "git", value_parser)]
-    pub vcs: Vcs,
+    /// Specify the VCS used to initialize the generated template. Defaults to the `vcs` of the app config, or `git`.
+    #[clap(long, value_parser)]
+    pub vcs: Option<Vcs>,
+
+    /// Use the defaults and values of the named `[profiles.<name>]` in the app config,
+    /// on top of its `[defaults]` and `[values]`.
+    #[clap(long, alias = "config-profile", value_parser)]
+    pub profile: Option<String>,
+
+    /// Specify the output format. With `json` a single JSON summary of the generated project
+    /// is printed to stdout at the end, while all other output goes to stderr.
+    #[clap(long, default_value = "human", value_parser)]
+    pub output_format: OutputFormat,
 
     /// Populates a template variable `crate_type` with value `"lib"`
     #[clap(long, conflicts_with = "bin", action)]
@@ -92,10 +185,16 @@ pub struct GenerateArgs {
     #[clap(short = 'i', long = "identity", value_parser)]
     pub ssh_identity: Option<PathBuf>,
 
-    /// Define a value for use during template expansion
-    #[clap(long, short, number_of_values = 1, value_parser)]
+    /// Define a value for use during template expansion, like `--define use_serde=true`.
+    /// Values are parsed as TOML scalars (bool, integer, float) and fall back to a string.
+    #[clap(long, short, short_alias = 'D', number_of_values = 1, value_parser)]
     pub define: Vec<String>,
 
+    /// Define values from a JSON file with a flat object, like `{"use_serde": true}`,
+    /// with the same precedence as `--define`, which still overrides single values.
+    #[clap(long, value_parser)]
+    pub define_from_json: Option<PathBuf>,
+
     /// Generate the template directly into the current dir. No subfolder will be created and no vcs is initialized.
     #[clap(long, conflicts_with = "destination", action)]
     pub init: bool,
@@ -108,41 +207,231 @@ pub struct GenerateArgs {
     #[clap(long, action)]
     pub force_git_init: bool,
 
-    /// Allows running system commands without being prompted.
+    /// Keep the git history of the template, so the generated project starts with its commits
+    /// instead of a fresh repository. Only for git or local templates generated from their root.
+    #[clap(long, action, conflicts_with_all(&["init", "update", "to-stdout-tar"]))]
+    pub keep_history: bool,
+
+    /// Name of the first branch of the generated git repository, like `trunk`.
+    /// Defaults to the branch of the template.
+    #[clap(long, value_parser)]
+    pub initial_branch: Option<String>,
+
+    /// Refuse any network access, only templates from local paths, local git repositories
+    /// or local archives can be used.
+    #[clap(long, action)]
+    pub offline: bool,
+
+    /// Retry cloning a git template this many times on network errors, waiting exponentially longer
+    /// before each retry. Errors like a failed authentication or a missing repository are not retried.
+    #[clap(long, default_value_t = 0, value_parser)]
+    pub clone_retries: u32,
+
+    /// Also fetch the submodules nested in the submodules of a git template, recursively.
+    /// The submodules of the template itself are always fetched.
+    #[clap(long, alias = "init-submodules", action)]
+    pub recurse_submodules: bool,
+
+    /// Leave out the files a local template ignores by its `.gitignore` files, like a `target` dir.
+    /// On by default, `--respect-gitignore=false` copies a local template with all its files.
+    #[clap(
+        long,
+        value_name = "BOOL",
+        action = clap::ArgAction::Set,
+        value_parser = clap::value_parser!(bool),
+        default_value = "true",
+        default_missing_value = "true",
+        min_values = 0,
+        require_equals = true
+    )]
+    pub respect_gitignore: bool,
+
+    /// Don't use the template cache for this run, even if `cache_templates` is enabled in the config
+    #[clap(long, action)]
+    pub no_cache: bool,
+
+    /// Don't show the progress of processing the template files.
+    /// Without a terminal, e.g. in CI, the progress is logged as plain lines instead of spinners.
+    #[clap(long, action)]
+    pub no_progress: bool,
+
+    /// Number of threads rendering the template files, defaults to the number of CPUs.
+    #[clap(long, value_name = "N", value_parser)]
+    pub jobs: Option<NonZeroUsize>,
+
+    /// What to do with files of the template that already exist in the destination,
+    /// one of `never` (fail), `always` (overwrite silently) or `prompt` (ask for each file).
+    /// Useful together with `--init` to update an existing project from a template.
+    #[clap(long, default_value = "never", value_parser)]
+    pub overwrite: Overwrite,
+
+    /// Update an existing project from the template: files identical to the ones of the project are skipped,
+    /// and a summary of the added, modified and unchanged files is printed. Modified files are handled by `--overwrite`.
+    #[clap(long, action, conflicts_with = "to-stdout-tar")]
+    pub update: bool,
+
+    /// Report which files would be generated, without writing anything to the destination
+    /// or initializing a repository. Fails if any file would collide with an existing one.
+    /// Hooks are listed, but not run.
+    #[clap(long, action)]
+    pub dry_run: bool,
+
+    /// Write the generated project as a tar archive to stdout, instead of into a directory.
+    /// All other output goes to stderr, and no repository is initialized.
+    #[clap(
+        long,
+        action,
+        conflicts_with_all(&["dry-run", "init", "destination", "list-variables"])
+    )]
+    pub to_stdout_tar: bool,
+
+    /// Keep the partially generated project if generation fails, to inspect a broken template.
+    /// By default a freshly created project dir is removed on failure.
+    /// With `--init` the current dir is never removed, regardless of this flag.
+    #[clap(long, action)]
+    pub keep_project_on_failure: bool,
+
+    /// Recreate symbolic links of the template in the generated project, instead of failing on them.
+    /// Links must be relative and stay within the project dir.
+    #[clap(long, action)]
+    pub allow_symlinks: bool,
+
+    /// Run `cargo fmt` in the generated project, if it has a `Cargo.toml` and cargo is installed.
+    /// A failing `cargo fmt` is reported as a warning, the project is kept.
+    #[clap(long, action, conflicts_with_all(&["dry-run", "to-stdout-tar"]))]
+    pub fmt: bool,
+
+    /// Allows hooks to run system commands.
+    /// Without it hooks are sandboxed and can't run any system command.
     /// Warning: Setting this flag will enable the template to run arbitrary system commands without user confirmation.
     /// Use at your own risk and be sure to review the template code beforehand.
     #[clap(short, long, action)]
     pub allow_commands: bool,
+
+    /// Don't run any pre or post hooks of the template, the skipped hooks are listed instead.
+    #[clap(long, conflicts_with = "allow-commands", action)]
+    pub deny_hooks: bool,
+
+    /// Don't run the named pre or post hook, while the others still run. A hook is named by its
+    /// path as listed in `cargo-generate.toml` or by its file name without extension.
+    #[clap(long = "skip-hook", number_of_values = 1, value_parser)]
+    pub skip_hooks: Vec<String>,
+
+    /// Generate even if commands the template requires in `required_commands` are not on `PATH`
+    #[clap(long, action)]
+    pub ignore_required_commands: bool,
+
+    /// Generate even if the installed `rustc` is older than the `min_rust_version` of the template
+    #[clap(long, action)]
+    pub ignore_rust_version: bool,
+
+    /// Verify the hooks of the template against a SHA-256 checksum manifest before running any of them,
+    /// in the format of `sha256sum` with paths relative to the template.
+    #[clap(long, value_parser)]
+    pub verify_checksums: Option<PathBuf>,
+
+    #[clap(subcommand)]
+    pub command: Option<GenerateCommand>,
+}
+
+/// Commands of `cargo generate` that don't generate a project
+#[derive(Debug, Clone, Subcommand)]
+pub enum GenerateCommand {
+    /// Print the completion script of `cargo generate` for a shell to stdout
+    Completions {
+        #[clap(value_parser)]
+        shell: Shell,
+    },
+    /// Validate a template without prompting or generating anything: its `cargo-generate.toml` files,
+    /// their placeholders and the rhai scripts of their hooks. Fails if any problem is found.
+    Check(Box<CheckArgs>),
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct CheckArgs {
+    #[clap(flatten)]
+    pub template_path: TemplatePath,
+}
+
+/// The clap `Command` of `cargo generate`, with all its args, e.g. to generate completions
+pub fn command() -> Command<'static> {
+    Cli::command()
 }
 
 #[derive(Debug, Clone, Args)]
 pub struct TemplatePath {
     /// Auto attempt to use as either `--git` or `--favorite`.
     /// If either is specified explicitly, use as subfolder.
-    #[clap(required_unless_present_any(&["SpecificPath"]))]
+    /// Without any template, a favorite can be chosen interactively.
+    #[clap()]
     pub auto_path: Option<String>,
 
     /// Specifies a subfolder within the template repository to be used as the actual template.
+    /// Without `--name`, it is taken as the project name if the template has no such subfolder.
     #[clap()]
     pub subfolder: Option<String>,
 
+    /// Subfolder within the template repository to be used as the actual template, like the positional subfolder.
+    /// Can be given multiple times, to generate each subfolder into its own dir within the project dir,
+    /// all sharing the same placeholder values.
+    #[clap(long = "subfolder", value_name = "SUBFOLDER", number_of_values = 1)]
+    pub subfolders: Vec<String>,
+
     /// Git repository to clone template from. Can be a URL (like
     /// `https://github.com/rust-cli/cli-template`), a path (relative or absolute), or an
     /// `owner/repo` abbreviated GitHub URL (like `rust-cli/cli-template`).
     ///
     /// Note that cargo generate will first attempt to interpret the `owner/repo` form as a
     /// relative path and only try a GitHub URL if the local path doesn't exist.
-    #[clap(short, long, group("SpecificPath"))]
-    pub git: Option<String>,
+    ///
+    /// Can be given multiple times, to overlay the templates in order, later ones win.
+    /// `--branch`, `--tag` and `--rev` only apply to the first one.
+    #[clap(short, long, group("SpecificPath"), number_of_values = 1)]
+    pub git: Vec<String>,
+
+    /// Mercurial repository to clone template from, using the `hg` executable.
+    /// Urls prefixed with `hg+`, like `hg+https://hg.example.com/template`, are cloned with
+    /// Mercurial as well.
+    #[clap(long, group("SpecificPath"))]
+    pub hg: Option<String>,
 
-    /// Branch to use when installing from git
+    /// Branch to use when installing from git or Mercurial
     #[clap(short, long)]
     pub branch: Option<String>,
 
+    /// Git tag to checkout after cloning, like a release tag.
+    /// Can not be specified together with --branch or --rev.
+    #[clap(long, conflicts_with_all(&["branch", "rev"]))]
+    pub tag: Option<String>,
+
+    /// Git revision (commit SHA) to checkout after cloning, for reproducible generation.
+    /// Can not be specified together with --branch or --tag.
+    #[clap(long, conflicts_with_all(&["branch", "tag"]))]
+    pub rev: Option<String>,
+
     /// Local path to copy the template from. Can not be specified together with --git.
     #[clap(short, long, group("SpecificPath"))]
     pub path: Option<String>,
 
+    /// Archive (`.tar.gz`, `.tar` or `.zip`) to extract the template from. Can be a local path
+    /// or an `http(s)://` URL, like a release asset.
+    #[clap(long, group("SpecificPath"))]
+    pub archive: Option<String>,
+
+    /// GitHub release to download the template from, as `owner/repo@tag`, together with `--asset`.
+    /// `GITHUB_TOKEN` is used if set, for releases of private repositories.
+    #[clap(
+        long,
+        value_name = "OWNER/REPO@TAG",
+        group("SpecificPath"),
+        requires = "asset"
+    )]
+    pub github_release: Option<String>,
+
+    /// Name of the asset of `--github-release` to use as template, a `.tar.gz`, `.tar` or `.zip` file
+    #[clap(long, requires = "github-release")]
+    pub asset: Option<String>,
+
     /// Generate a favorite template as defined in the config. In case the favorite is undefined,
     /// use in place of the `--git` option, otherwise specifies the subfolder
     #[clap(long, group("SpecificPath"))]
@@ -150,30 +439,74 @@ pub struct TemplatePath {
 }
 
 impl TemplatePath {
+    /// `true` if no template has been given at all
+    pub const fn is_empty(&self) -> bool {
+        self.git.is_empty()
+            && self.hg.is_none()
+            && self.path.is_none()
+            && self.archive.is_none()
+            && self.github_release.is_none()
+            && self.favorite.is_none()
+            && self.auto_path.is_none()
+    }
+
     /// # Panics
     /// Will panic if no path to a template has been set at all,
-    /// which is never if Clap is initialized properly.
+    /// which is never once a favorite has been chosen for an empty `TemplatePath`.
     pub fn any_path(&self) -> &str {
         self.git
-            .as_ref()
+            .first()
+            .or(self.hg.as_ref())
             .or(self.path.as_ref())
+            .or(self.archive.as_ref())
+            .or(self.github_release.as_ref())
             .or(self.favorite.as_ref())
             .or(self.auto_path.as_ref())
             .unwrap()
     }
 
-    pub const fn git(&self) -> Option<&(impl AsRef<str> + '_)> {
-        self.git.as_ref()
+    /// The first `--git`, the base of all others
+    pub fn git(&self) -> Option<&(impl AsRef<str> + '_)> {
+        self.git.first()
+    }
+
+    /// All `--git` but the first, to be overlaid on it in order
+    pub fn git_overlays(&self) -> &[String] {
+        self.git.get(1..).unwrap_or_default()
+    }
+
+    pub const fn hg(&self) -> Option<&(impl AsRef<str> + '_)> {
+        self.hg.as_ref()
     }
 
     pub const fn branch(&self) -> Option<&(impl AsRef<str> + '_)> {
         self.branch.as_ref()
     }
 
+    pub const fn tag(&self) -> Option<&(impl AsRef<str> + '_)> {
+        self.tag.as_ref()
+    }
+
+    pub const fn rev(&self) -> Option<&(impl AsRef<str> + '_)> {
+        self.rev.as_ref()
+    }
+
     pub const fn path(&self) -> Option<&(impl AsRef<str> + '_)> {
         self.path.as_ref()
     }
 
+    pub const fn archive(&self) -> Option<&(impl AsRef<str> + '_)> {
+        self.archive.as_ref()
+    }
+
+    pub const fn github_release(&self) -> Option<&(impl AsRef<str> + '_)> {
+        self.github_release.as_ref()
+    }
+
+    pub const fn asset(&self) -> Option<&(impl AsRef<str> + '_)> {
+        self.asset.as_ref()
+    }
+
     pub const fn favorite(&self) -> Option<&(impl AsRef<str> + '_)> {
         self.favorite.as_ref()
     }
@@ -182,19 +515,54 @@ impl TemplatePath {
         self.auto_path.as_ref()
     }
 
-    pub const fn subfolder(&self) -> Option<&(impl AsRef<str> + '_)> {
-        if self.git.is_some() || self.path.is_some() || self.favorite.is_some() {
+    pub fn subfolder(&self) -> Option<&(impl AsRef<str> + '_)> {
+        self.positional_subfolder()
+            .or_else(|| self.subfolders.first())
+    }
+
+    /// All subfolders to generate, the positional one first
+    pub fn subfolders(&self) -> Vec<&str> {
+        self.positional_subfolder()
+            .into_iter()
+            .chain(&self.subfolders)
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// The subfolder given as positional argument, the first one if the template is given by option, the second one otherwise
+    pub const fn positional_subfolder(&self) -> Option<&String> {
+        if self.has_specific_path() {
             self.auto_path.as_ref()
         } else {
             self.subfolder.as_ref()
         }
     }
+
+    /// Removes the positional subfolder, when it is taken as the project name instead
+    pub const fn take_positional_subfolder(&mut self) -> Option<String> {
+        if self.has_specific_path() {
+            self.auto_path.take()
+        } else {
+            self.subfolder.take()
+        }
+    }
+
+    const fn has_specific_path(&self) -> bool {
+        !self.git.is_empty()
+            || self.hg.is_some()
+            || self.path.is_some()
+            || self.archive.is_some()
+            || self.github_release.is_some()
+            || self.favorite.is_some()
+    }
 }
 
-#[derive(Debug, Parser, Clone, Copy)]
+#[derive(Debug, Parser, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Vcs {
     None,
     Git,
+    Hg,
 }
 
 impl FromStr for Vcs {
@@ -204,7 +572,8 @@ impl FromStr for Vcs {
         match s.to_uppercase().as_str() {
             "NONE" => Ok(Self::None),
             "GIT" => Ok(Self::Git),
-            _ => Err(anyhow!("Must be one of 'git' or 'none'")),
+            "HG" => Ok(Self::Hg),
+            _ => Err(anyhow!("Must be one of 'git', 'hg' or 'none'")),
         }
     }
 }
@@ -216,6 +585,17 @@ impl Vcs {
             Self::Git => git::init(project_dir, &branch, force)
                 .map(|_| ())
                 .map_err(anyhow::Error::from),
+            // Mercurial has no equivalent of the initial git branch, commits go to `default`
+            Self::Hg => hg::init(project_dir, force),
+        }
+    }
+
+    /// Human readable name of the vcs
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Git => "Git",
+            Self::Hg => "Mercurial",
         }
     }
 
@@ -223,3 +603,69 @@ impl Vcs {
         matches!(self, Self::None)
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overwrite {
+    Never,
+    Always,
+    Prompt,
+}
+
+impl FromStr for Overwrite {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "NEVER" => Ok(Self::Never),
+            "ALWAYS" => Ok(Self::Always),
+            "PROMPT" => Ok(Self::Prompt),
+            _ => Err(anyhow!("Must be one of 'never', 'always' or 'prompt'")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Human,
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "HUMAN" => Ok(Self::Human),
+            "JSON" => Ok(Self::Json),
+            _ => Err(anyhow!("Must be one of 'human' or 'json'")),
+        }
+    }
+}
+
+impl OutputFormat {
+    pub const fn is_human(&self) -> bool {
+        matches!(self, Self::Human)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Human,
+    Plain,
+    Json,
+    Log,
+}
+
+impl FromStr for LogFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "HUMAN" => Ok(Self::Human),
+            "PLAIN" => Ok(Self::Plain),
+            "JSON" => Ok(Self::Json),
+            "LOG" => Ok(Self::Log),
+            _ => Err(anyhow!("Must be one of 'human', 'plain', 'json' or 'log'")),
+        }
+    }
+}
@@ -0,0 +1,147 @@
+//! Module dealing with `cargo generate check`, to validate a template without generating anything
+
+use crate::{
+    config::{locate_template_configs, Config, CONFIG_FILE_NAME},
+    emoji, hooks,
+    project_variables::try_into_template_slots,
+};
+use anyhow::{bail, Result};
+use console::style;
+use std::path::Path;
+
+/// Validates all `cargo-generate.toml` within `template_dir`: that they can be parsed, that their placeholders,
+/// also the conditional ones, are valid, and that the rhai scripts of their hooks compile.
+/// Every problem is printed, the check fails if there is any.
+pub fn check_template(template_dir: &Path) -> Result<()> {
+    let mut config_dirs = locate_template_configs(template_dir)?;
+    config_dirs.sort();
+    if config_dirs.is_empty() {
+        println!(
+            "{} {}",
+            emoji::WARN,
+            style(format!("No {} found, nothing to check", CONFIG_FILE_NAME)).bold()
+        );
+    }
+
+    let mut problem_count = 0;
+    for config_dir in &config_dirs {
+        let config_path = Path::new(config_dir).join(CONFIG_FILE_NAME);
+        let problems = check_config(&template_dir.join(config_dir));
+        if problems.is_empty() {
+            println!(
+                "{} {}",
+                emoji::WRENCH,
+                style(config_path.display()).bold().green()
+            );
+            continue;
+        }
+        println!(
+            "{} {}",
+            emoji::ERROR,
+            style(config_path.display()).bold().red()
+        );
+        for problem in &problems {
+            println!("    {} {}", emoji::DIAMOND, problem);
+        }
+        problem_count += problems.len();
+    }
+
+    if problem_count > 0 {
+        bail!(
+            "{} {} {}",
+            emoji::ERROR,
+            style("Template check failed:").bold().red(),
+            style(format!("{} problem(s) found", problem_count)).yellow(),
+        );
+    }
+    println!("{} {}", emoji::SPARKLE, style("Done").bold().green());
+    Ok(())
+}
+
+/// The problems of the `cargo-generate.toml` in `dir`, a config that can't be parsed is a single problem
+fn check_config(dir: &Path) -> Vec<String> {
+    let config = match Config::from_path(&Some(dir.join(CONFIG_FILE_NAME))) {
+        Ok(config) => config.unwrap_or_default(),
+        Err(e) => return vec![format!("{:#}", e)],
+    };
+
+    let mut problems = Vec::new();
+    if let Some(ref placeholders) = config.placeholders {
+        if let Err(e) = try_into_template_slots(placeholders, None) {
+            problems.push(e.to_string());
+        }
+    }
+    let mut conditionals = config
+        .conditional
+        .iter()
+        .flatten()
+        .filter_map(|(condition, cfg)| cfg.placeholders.as_ref().map(|p| (condition, p)))
+        .collect::<Vec<_>>();
+    conditionals.sort_by_key(|(condition, _)| *condition);
+    for (condition, placeholders) in conditionals {
+        if let Err(e) = try_into_template_slots(placeholders, None) {
+            problems.push(format!("{} (in conditional `{}`)", e, condition));
+        }
+    }
+    problems.extend(hooks::check_scripts(dir, &config));
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::create_file;
+    use tempfile::tempdir;
+
+    #[test]
+    fn it_reports_invalid_placeholders_and_hooks() -> anyhow::Result<()> {
+        let tmp = tempdir()?;
+        create_file(
+            &tmp,
+            CONFIG_FILE_NAME,
+            r#"
+[placeholders]
+license = { type = "string", prompt = "License?", choices = ["MIT"], default = "GPL" }
+
+[conditional.'crate_type == "lib"'.placeholders]
+edition = { type = "string", prompt = "Edition?", regex = "[" }
+
+[hooks]
+pre = ["broken.rhai", "fine.rhai"]
+post = [{ type = "exec", command = ["sh", "missing.sh"], file = "missing.sh" }]
+"#,
+        )?;
+        create_file(&tmp, "broken.rhai", "let x = ;")?;
+        create_file(&tmp, "fine.rhai", "let x = 1;")?;
+
+        let problems = check_config(tmp.path());
+
+        assert_eq!(problems.len(), 4, "{:?}", problems);
+        assert!(problems[0].contains("`license`"));
+        assert!(problems[1].contains("`edition`"));
+        assert!(problems[1].contains("in conditional"));
+        assert!(problems[2].contains("`broken.rhai`"));
+        assert!(problems[3].contains("`missing.sh`"));
+        Ok(())
+    }
+
+    #[test]
+    fn it_accepts_a_valid_template() -> anyhow::Result<()> {
+        let tmp = tempdir()?;
+        create_file(
+            &tmp,
+            "inner/cargo-generate.toml",
+            r#"
+[placeholders]
+license = { type = "string", prompt = "License?", choices = ["MIT", "GPL"], default = "MIT" }
+
+[hooks]
+pre = ["pre.rhai"]
+"#,
+        )?;
+        create_file(&tmp, "inner/pre.rhai", r#"variable::set("x", "y");"#)?;
+
+        assert!(check_config(&tmp.path().join("inner")).is_empty());
+        check_template(tmp.path())
+    }
+}
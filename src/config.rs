@@ -1,12 +1,17 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
+use console::style;
 use semver::VersionReq;
 use serde::Deserialize;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::{collections::HashMap, fs};
 use std::{convert::TryFrom, io::ErrorKind};
 use walkdir::WalkDir;
 
+use crate::emoji;
+
 pub const CONFIG_FILE_NAME: &str = "cargo-generate.toml";
+/// The suffix stripped from the names of the files of a template, if it doesn't set `render_extension`
+pub const DEFAULT_RENDER_EXTENSION: &str = ".liquid";
 
 #[derive(Deserialize, Debug, PartialEq, Default, Clone)]
 pub struct Config {
@@ -18,16 +23,153 @@ pub struct Config {
 
 #[derive(Deserialize, Debug, PartialEq, Default, Clone)]
 pub struct HooksConfig {
-    pub pre: Option<Vec<String>>,
-    pub post: Option<Vec<String>>,
+    pub pre: Option<Vec<Hook>>,
+    pub post: Option<Vec<Hook>>,
+}
+
+/// A hook is the path of a rhai script, or a table with its `type`
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[serde(untagged)]
+pub enum Hook {
+    Rhai(String),
+    Typed(TypedHook),
+}
+
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum TypedHook {
+    Rhai {
+        file: String,
+    },
+    /// Runs `command` with the project dir as cwd, `file` is a script of the template the command runs
+    Exec {
+        command: Vec<String>,
+        file: Option<String>,
+    },
+}
+
+impl Hook {
+    /// The file of the template the hook consists of, which is not expanded and removed after the hooks ran
+    pub fn file(&self) -> Option<&str> {
+        match self {
+            Self::Rhai(file) | Self::Typed(TypedHook::Rhai { file }) => Some(file),
+            Self::Typed(TypedHook::Exec { file, .. }) => file.as_deref(),
+        }
+    }
+
+    /// The file of the hook, or the command of an `exec` hook without file
+    pub fn name(&self) -> String {
+        match self {
+            Self::Typed(TypedHook::Exec {
+                command,
+                file: None,
+            }) => command.join(" "),
+            _ => self.file().unwrap_or_default().to_string(),
+        }
+    }
 }
 
 #[derive(Deserialize, Debug, PartialEq, Default, Clone)]
 pub struct TemplateConfig {
+    /// A human readable name of the template, printed before generating
+    pub name: Option<String>,
+    /// What the template generates, printed before generating
+    pub description: Option<String>,
     pub cargo_generate_version: Option<VersionReq>,
+    /// The minimum Rust version the generated project needs, like `1.70`, checked against the installed `rustc`
+    pub min_rust_version: Option<String>,
     pub include: Option<Vec<String>>,
     pub exclude: Option<Vec<String>>,
     pub ignore: Option<Vec<String>>,
+    pub normalize_line_endings: Option<LineEnding>,
+    /// A base template, whose files and config this template is laid over
+    pub extends: Option<String>,
+    /// Executables that have to be on `PATH`, e.g. for the hooks, checked before generating anything
+    pub required_commands: Option<Vec<String>>,
+    /// Files with a `[placeholders]` table shared across templates, relative to the template root
+    pub placeholders_include: Option<Vec<String>>,
+    /// Match `include`/`exclude` against the rendered output paths instead of the template paths
+    pub match_rendered_paths: Option<bool>,
+    /// Output paths to rename after rendering, like `gitignore` to `.gitignore`, relative to the template root
+    pub rename: Option<HashMap<String, String>>,
+    /// Only files with this suffix, like `.tmpl`, are rendered, all others are copied verbatim
+    pub render_extension: Option<String>,
+    /// Files copied verbatim without rendering, even if `include` or `exclude` would render them
+    pub raw: Option<Vec<String>>,
+}
+
+impl TemplateConfig {
+    /// The suffix stripped from the names of the files, `.liquid` unless `render_extension` is set
+    pub fn render_extension(&self) -> &str {
+        self.render_extension
+            .as_deref()
+            .unwrap_or(DEFAULT_RENDER_EXTENSION)
+    }
+
+    /// Values set in `self` win over the ones of `base`
+    fn merge_base(self, base: Self) -> Self {
+        Self {
+            name: self.name.or(base.name),
+            description: self.description.or(base.description),
+            cargo_generate_version: self.cargo_generate_version.or(base.cargo_generate_version),
+            min_rust_version: self.min_rust_version.or(base.min_rust_version),
+            include: self.include.or(base.include),
+            exclude: self.exclude.or(base.exclude),
+            ignore: self.ignore.or(base.ignore),
+            normalize_line_endings: self.normalize_line_endings.or(base.normalize_line_endings),
+            match_rendered_paths: self.match_rendered_paths.or(base.match_rendered_paths),
+            render_extension: self.render_extension.or(base.render_extension),
+            raw: self.raw.or(base.raw),
+            extends: self.extends,
+            // the hooks of the base still run, so it still needs its commands
+            required_commands: union(self.required_commands, base.required_commands),
+            placeholders_include: union(self.placeholders_include, base.placeholders_include),
+            rename: match (base.rename, self.rename) {
+                (Some(mut base), Some(renames)) => {
+                    base.extend(renames);
+                    Some(base)
+                }
+                (base, renames) => renames.or(base),
+            },
+        }
+    }
+}
+
+fn union(values: Option<Vec<String>>, base_values: Option<Vec<String>>) -> Option<Vec<String>> {
+    match (values, base_values) {
+        (Some(mut values), Some(base_values)) => {
+            for value in base_values {
+                if !values.contains(&value) {
+                    values.push(value);
+                }
+            }
+            Some(values)
+        }
+        (values, base_values) => values.or(base_values),
+    }
+}
+
+/// Line endings the rendered text files are normalized to
+#[derive(Deserialize, Debug, PartialEq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+    /// `crlf` on windows, `lf` everywhere else
+    Native,
+}
+
+impl LineEnding {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Lf => "\n",
+            Self::Crlf => "\r\n",
+            #[cfg(windows)]
+            Self::Native => "\r\n",
+            #[cfg(not(windows))]
+            Self::Native => "\n",
+        }
+    }
 }
 
 #[derive(Deserialize, Debug, PartialEq, Clone)]
@@ -38,8 +180,15 @@ pub struct ConditionalConfig {
     pub placeholders: Option<TemplateSlotsTable>,
 }
 
+/// The placeholders in the order they are declared in
 #[derive(Deserialize, Debug, PartialEq, Clone)]
-pub struct TemplateSlotsTable(pub HashMap<String, toml::Value>);
+pub struct TemplateSlotsTable(pub toml::value::Table);
+
+/// A file of `placeholders_include`
+#[derive(Deserialize)]
+struct PlaceholdersInclude {
+    placeholders: Option<TemplateSlotsTable>,
+}
 
 impl TryFrom<String> for Config {
     type Error = toml::de::Error;
@@ -57,9 +206,11 @@ impl Config {
     {
         match path {
             Some(path) => match fs::read_to_string(path) {
-                Ok(contents) => Self::try_from(contents)
-                    .map(Option::from)
-                    .map_err(|e| e.into()),
+                Ok(contents) => {
+                    let config = Self::try_from(contents)?;
+                    let template_root = path.as_ref().parent().unwrap_or_else(|| Path::new("."));
+                    config.with_included_placeholders(template_root).map(Some)
+                }
                 Err(e) => match e.kind() {
                     ErrorKind::NotFound => Ok(None),
                     _ => anyhow::bail!(e),
@@ -69,14 +220,129 @@ impl Config {
         }
     }
 
-    pub fn get_pre_hooks(&self) -> Vec<String> {
+    /// The files of `placeholders_include`, relative to `template_root`
+    pub fn placeholders_include_paths(&self, template_root: &Path) -> Vec<PathBuf> {
+        self.template
+            .as_ref()
+            .and_then(|template| template.placeholders_include.as_ref())
+            .map(|includes| {
+                includes
+                    .iter()
+                    .map(|include| template_root.join(include))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Merges the placeholders of the `placeholders_include` files, later files win over earlier ones,
+    /// and the own `[placeholders]` win over all of them
+    fn with_included_placeholders(mut self, template_root: &Path) -> Result<Self> {
+        let includes = match self
+            .template
+            .as_ref()
+            .and_then(|template| template.placeholders_include.clone())
+        {
+            Some(includes) => includes,
+            None => return Ok(self),
+        };
+
+        let mut placeholders = toml::value::Table::new();
+        for include in includes {
+            let contents = match fs::read_to_string(template_root.join(&include)) {
+                Ok(contents) => contents,
+                Err(e) => bail!(
+                    "{} {} {}",
+                    emoji::ERROR,
+                    style(format!("Placeholders include `{}` can't be read:", include))
+                        .bold()
+                        .red(),
+                    style(e).yellow(),
+                ),
+            };
+            let included: PlaceholdersInclude = match toml::from_str(&contents) {
+                Ok(included) => included,
+                Err(e) => bail!(
+                    "{} {} {}",
+                    emoji::ERROR,
+                    style(format!("Placeholders include `{}` is invalid:", include))
+                        .bold()
+                        .red(),
+                    style(e).yellow(),
+                ),
+            };
+            if let Some(included) = included.placeholders {
+                placeholders.extend(included.0);
+            }
+        }
+
+        if let Some(own) = self.placeholders.take() {
+            placeholders.extend(own.0);
+        }
+        self.placeholders = Some(TemplateSlotsTable(placeholders));
+        Ok(self)
+    }
+
+    /// Merges the config of the base template this config `extends`.
+    ///
+    /// Values of `self` win: `[template]` values are taken from `self` where set, placeholders and
+    /// conditionals of the same name are replaced, and the hooks of the base run before the ones of `self`.
+    pub fn merge_base(self, base: Self) -> Self {
+        fn merge_maps<V>(
+            base: Option<HashMap<String, V>>,
+            child: Option<HashMap<String, V>>,
+        ) -> Option<HashMap<String, V>> {
+            match (base, child) {
+                (Some(mut base), Some(child)) => {
+                    base.extend(child);
+                    Some(base)
+                }
+                (base, child) => child.or(base),
+            }
+        }
+        fn chain_hooks(base: Option<Vec<Hook>>, child: Option<Vec<Hook>>) -> Option<Vec<Hook>> {
+            match (base, child) {
+                (Some(mut base), Some(child)) => {
+                    base.extend(child);
+                    Some(base)
+                }
+                (base, child) => child.or(base),
+            }
+        }
+
+        let template = match (self.template, base.template) {
+            (Some(child), Some(base)) => Some(child.merge_base(base)),
+            (child, base) => child.or(base),
+        };
+        let hooks = match (self.hooks, base.hooks) {
+            (Some(child), Some(base)) => Some(HooksConfig {
+                pre: chain_hooks(base.pre, child.pre),
+                post: chain_hooks(base.post, child.post),
+            }),
+            (child, base) => child.or(base),
+        };
+
+        Self {
+            template,
+            placeholders: match (base.placeholders, self.placeholders) {
+                (Some(mut base), Some(child)) => {
+                    base.0.extend(child.0);
+                    Some(base)
+                }
+                (base, child) => child.or(base),
+            },
+            hooks,
+            conditional: merge_maps(base.conditional, self.conditional),
+        }
+    }
+
+    pub fn get_pre_hooks(&self) -> Vec<Hook> {
         self.hooks
             .as_ref()
             .map(|h| h.pre.as_ref().map(Clone::clone).unwrap_or_default())
             .unwrap_or_default()
     }
 
-    pub fn get_post_hooks(&self) -> Vec<String> {
+    pub fn get_post_hooks(&self) -> Vec<Hook> {
         self.hooks
             .as_ref()
             .map(|h| h.post.as_ref().map(Clone::clone).unwrap_or_default())
@@ -84,12 +350,84 @@ impl Config {
     }
 
     pub fn get_hook_files(&self) -> Vec<String> {
-        let mut pre = self.get_pre_hooks();
-        pre.append(&mut self.get_post_hooks());
-        pre
+        self.get_pre_hooks()
+            .iter()
+            .chain(self.get_post_hooks().iter())
+            .filter_map(|hook| hook.file().map(String::from))
+            .collect()
+    }
+
+    /// The names of the placeholders declared in the `[conditional]` sections
+    pub fn conditional_placeholder_names(&self) -> Vec<&str> {
+        self.conditional_placeholders()
+            .flat_map(|placeholders| placeholders.0.keys())
+            .map(String::as_str)
+            .collect()
+    }
+
+    fn conditional_placeholders(&self) -> impl Iterator<Item = &TemplateSlotsTable> {
+        self.conditional
+            .iter()
+            .flat_map(|conditionals| conditionals.values())
+            .filter_map(|conditional| conditional.placeholders.as_ref())
+    }
+
+    /// Whether any placeholder, also a conditional one, runs a `choices_command` of the config
+    pub fn has_choices_commands(&self) -> bool {
+        self.placeholders
+            .iter()
+            .chain(self.conditional_placeholders())
+            .flat_map(|placeholders| placeholders.0.values())
+            .any(|placeholder| placeholder.get("choices_command").is_some())
+    }
+
+    /// Whether the config itself holds commands, which are run by `exec` hooks or `choices_command`
+    pub fn has_commands(&self) -> bool {
+        self.has_exec_hooks() || self.has_choices_commands()
+    }
+
+    /// Whether any hook is an `exec` hook, whose `command` is part of the config instead of a file
+    pub fn has_exec_hooks(&self) -> bool {
+        self.get_pre_hooks()
+            .iter()
+            .chain(self.get_post_hooks().iter())
+            .any(|hook| matches!(hook, Hook::Typed(TypedHook::Exec { .. })))
     }
 }
 
+/// Merges the `cargo-generate.toml` of a template overlaid on another one, see a repeated `--git`.
+///
+/// Like [`Config::merge_base`], the overlay wins: its `[template]` values, placeholders and conditionals
+/// replace the ones of the same name, and its hooks run after the ones of the base.
+pub fn merge_overlay_config(base: &str, overlay: &str) -> Result<String> {
+    let mut base: toml::value::Table = toml::from_str(base)?;
+    let overlay: toml::value::Table = toml::from_str(overlay)?;
+
+    for (key, value) in overlay {
+        let value = match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base_section)), toml::Value::Table(section)) => {
+                for (name, value) in section {
+                    match (base_section.get_mut(&name), value) {
+                        (Some(toml::Value::Array(base_hooks)), toml::Value::Array(hooks))
+                            if key == "hooks" =>
+                        {
+                            base_hooks.extend(hooks);
+                        }
+                        (_, value) => {
+                            base_section.insert(name, value);
+                        }
+                    }
+                }
+                continue;
+            }
+            (_, value) => value,
+        };
+        base.insert(key, value);
+    }
+
+    Ok(toml::to_string(&toml::Value::Table(base))?)
+}
+
 pub fn locate_template_configs(dir: &Path) -> Result<Vec<String>> {
     let mut result = vec![];
 
@@ -180,14 +518,107 @@ mod tests {
             config.template,
             Some(TemplateConfig {
                 cargo_generate_version: Some(VersionReq::from_str(">=0.8.0").unwrap()),
+                min_rust_version: None,
                 include: Some(vec!["Cargo.toml".into()]),
                 exclude: None,
                 ignore: None,
+                normalize_line_endings: None,
+                extends: None,
+                required_commands: None,
+                placeholders_include: None,
+                match_rendered_paths: None,
+                rename: None,
+                render_extension: None,
+                raw: None,
+                name: None,
+                description: None,
             })
         );
         assert!(config.placeholders.is_some());
     }
 
+    #[test]
+    fn merge_base_lets_the_child_win() {
+        let base = Config::try_from(
+            r#"
+            [template]
+            include = ["Cargo.toml"]
+            ignore = ["target"]
+            [placeholders]
+            license = { type = "string", prompt = "License?", default = "MIT" }
+            ci = { type = "bool", prompt = "CI?" }
+            [hooks]
+            pre = ["base-pre.rhai"]
+            post = ["base-post.rhai"]
+            "#
+            .to_string(),
+        )
+        .unwrap();
+        let child = Config::try_from(
+            r#"
+            [template]
+            extends = "../base"
+            include = ["src/*.rs"]
+            [placeholders]
+            license = { type = "string", prompt = "License?", default = "Apache-2.0" }
+            [hooks]
+            pre = ["child-pre.rhai"]
+            "#
+            .to_string(),
+        )
+        .unwrap();
+
+        let merged = child.merge_base(base);
+
+        let template = merged.template.as_ref().unwrap();
+        assert_eq!(template.include, Some(vec!["src/*.rs".into()]));
+        assert_eq!(template.ignore, Some(vec!["target".into()]));
+        assert_eq!(template.extends, Some("../base".into()));
+
+        let placeholders = &merged.placeholders.as_ref().unwrap().0;
+        assert_eq!(placeholders.len(), 2);
+        assert_eq!(
+            placeholders["license"].get("default"),
+            Some(&Value::from("Apache-2.0"))
+        );
+        assert_eq!(
+            merged.get_pre_hooks(),
+            vec![
+                Hook::Rhai("base-pre.rhai".into()),
+                Hook::Rhai("child-pre.rhai".into())
+            ]
+        );
+        assert_eq!(
+            merged.get_post_hooks(),
+            vec![Hook::Rhai("base-post.rhai".into())]
+        );
+    }
+
+    #[test]
+    fn config_try_from_handles_line_endings() {
+        let result = Config::try_from(
+            r#"
+            [template]
+            normalize_line_endings = "crlf"
+            "#
+            .to_string(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            result.template.unwrap().normalize_line_endings,
+            Some(LineEnding::Crlf)
+        );
+        assert!(Config::try_from(
+            r#"
+            [template]
+            normalize_line_endings = "cr"
+            "#
+            .to_string(),
+        )
+        .is_err());
+    }
+
     #[test]
     fn config_try_from_handles_empty() {
         let result = Config::try_from("".to_string());
@@ -204,6 +635,95 @@ mod tests {
         )
     }
 
+    #[test]
+    fn config_try_from_handles_typed_hooks() {
+        let config = Config::try_from(
+            r#"
+            [hooks]
+            pre = [
+                "pre.rhai",
+                { type = "rhai", file = "typed.rhai" },
+                { type = "exec", command = ["python3", "setup.py"], file = "setup.py" },
+            ]
+            post = [{ type = "exec", command = ["cargo", "fmt"] }]
+            "#
+            .to_string(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.get_pre_hooks(),
+            vec![
+                Hook::Rhai("pre.rhai".into()),
+                Hook::Typed(TypedHook::Rhai {
+                    file: "typed.rhai".into()
+                }),
+                Hook::Typed(TypedHook::Exec {
+                    command: vec!["python3".into(), "setup.py".into()],
+                    file: Some("setup.py".into())
+                }),
+            ]
+        );
+        assert_eq!(config.get_post_hooks()[0].name(), "cargo fmt");
+        assert_eq!(
+            config.get_hook_files(),
+            vec!["pre.rhai", "typed.rhai", "setup.py"]
+        );
+    }
+
+    #[test]
+    fn from_path_merges_included_placeholders() -> anyhow::Result<()> {
+        let tmp = tempdir().unwrap();
+        create_file(
+            &tmp,
+            "common.toml",
+            r#"[placeholders]
+license = { type = "string", prompt = "License?", default = "MIT" }
+edition = { type = "string", prompt = "Edition?", default = "2018" }
+"#,
+        )?;
+        create_file(
+            &tmp,
+            "newer.toml",
+            r#"[placeholders]
+edition = { type = "string", prompt = "Edition?", default = "2021" }
+"#,
+        )?;
+        create_file(
+            &tmp,
+            CONFIG_FILE_NAME,
+            r#"[template]
+placeholders_include = ["common.toml", "newer.toml"]
+
+[placeholders]
+license = { type = "string", prompt = "License?", default = "Apache-2.0" }
+"#,
+        )?;
+
+        let config = Config::from_path(&Some(tmp.path().join(CONFIG_FILE_NAME)))?.unwrap();
+        let placeholders = config.placeholders.unwrap().0;
+        let default_of = |name: &str| placeholders[name].get("default").cloned();
+        assert_eq!(default_of("license"), Some(Value::from("Apache-2.0")));
+        assert_eq!(default_of("edition"), Some(Value::from("2021")));
+        Ok(())
+    }
+
+    #[test]
+    fn from_path_names_a_missing_include() -> anyhow::Result<()> {
+        let tmp = tempdir().unwrap();
+        create_file(
+            &tmp,
+            CONFIG_FILE_NAME,
+            r#"[template]
+placeholders_include = ["missing.toml"]
+"#,
+        )?;
+
+        let error = Config::from_path(&Some(tmp.path().join(CONFIG_FILE_NAME))).unwrap_err();
+        assert!(error.to_string().contains("missing.toml"));
+        Ok(())
+    }
+
     #[test]
     fn config_try_from_errors_on_invalid_keys() {
         let result = Config::try_from(
@@ -276,4 +796,48 @@ mod tests {
         assert_eq!(b_type, Some(&Value::String("string".to_string())));
         assert_eq!(b_prompt, Some(&Value::String("bar".to_string())));
     }
+
+    #[test]
+    fn overlay_config_unions_placeholders_and_chains_hooks() -> anyhow::Result<()> {
+        let merged = merge_overlay_config(
+            r#"
+            [template]
+            exclude = ["base"]
+            [placeholders]
+            a = { type = "string", prompt = "a?" }
+            b = { type = "string", prompt = "b?" }
+            [hooks]
+            pre = ["base.rhai"]
+            "#,
+            r#"
+            [template]
+            exclude = ["overlay"]
+            [placeholders]
+            b = { type = "bool", prompt = "b!" }
+            c = { type = "string", prompt = "c?" }
+            [hooks]
+            pre = ["overlay.rhai"]
+            "#,
+        )?;
+        let config = Config::try_from(merged)?;
+
+        let placeholders = config.placeholders.unwrap().0;
+        assert_eq!(
+            placeholders.keys().map(String::as_str).collect::<Vec<_>>(),
+            vec!["a", "b", "c"]
+        );
+        assert_eq!(placeholders["b"]["type"].as_str(), Some("bool"));
+        assert_eq!(
+            config.template.unwrap().exclude,
+            Some(vec!["overlay".to_string()])
+        );
+        assert_eq!(
+            config.hooks.unwrap().pre.unwrap(),
+            vec![
+                Hook::Rhai("base.rhai".to_string()),
+                Hook::Rhai("overlay.rhai".to_string())
+            ]
+        );
+        Ok(())
+    }
 }
@@ -7,3 +7,6 @@ pub static WRENCH: Emoji<'_, '_> = Emoji("🔧  ", "");
 pub static SHRUG: Emoji<'_, '_> = Emoji("🤷  ", "");
 pub static INFO: Emoji<'_, '_> = Emoji("💡  ", "");
 pub static DIAMOND: Emoji<'_, '_> = Emoji("🔸  ", "");
+
+/// All of the above, to strip them from output that is not meant for humans
+pub static ALL: [&Emoji<'_, '_>; 7] = [&ERROR, &SPARKLE, &WARN, &WRENCH, &SHRUG, &INFO, &DIAMOND];
@@ -0,0 +1,104 @@
+use crate::project_variables::ConversionError;
+use std::fmt;
+
+/// The kinds of failures `cargo-generate` exits with a dedicated code for, see [`ErrorKind::exit_code`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// The template could not be fetched, or the given subfolder is not within it
+    TemplateNotFound,
+    /// A placeholder is invalid, or a value given for it is rejected
+    InvalidPlaceholder,
+    /// The project dir, or a file within it, already exists
+    Collision,
+    /// A hook failed, was aborted or refused to run
+    HookFailed,
+}
+
+impl ErrorKind {
+    /// The exit code of the `cargo-generate` binary, any other error exits with `1`.
+    pub const fn exit_code(self) -> i32 {
+        match self {
+            Self::TemplateNotFound => 2,
+            Self::InvalidPlaceholder => 3,
+            Self::Collision => 4,
+            Self::HookFailed => 5,
+        }
+    }
+
+    /// Finds the kind of the first categorized error in the chain of `error`.
+    pub fn of(error: &anyhow::Error) -> Option<Self> {
+        error.chain().find_map(|cause| {
+            cause.downcast_ref::<GenerateError>().map_or_else(
+                || {
+                    cause
+                        .is::<ConversionError>()
+                        .then_some(Self::InvalidPlaceholder)
+                },
+                |error| Some(error.kind),
+            )
+        })
+    }
+}
+
+/// An error of a known [`ErrorKind`], displayed just like the error it wraps.
+#[derive(Debug)]
+pub struct GenerateError {
+    kind: ErrorKind,
+    error: anyhow::Error,
+}
+
+impl GenerateError {
+    pub const fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+}
+
+impl fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+impl std::error::Error for GenerateError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error.source()
+    }
+}
+
+pub trait Categorize<T> {
+    /// Marks the error as being of `kind`.
+    fn categorize(self, kind: ErrorKind) -> anyhow::Result<T>;
+}
+
+impl<T> Categorize<T> for anyhow::Result<T> {
+    fn categorize(self, kind: ErrorKind) -> Self {
+        self.map_err(|error| GenerateError { kind, error }.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{anyhow, Context};
+
+    #[test]
+    fn the_kind_is_found_below_a_context() {
+        let error = Err::<(), _>(anyhow!("exists"))
+            .categorize(ErrorKind::Collision)
+            .context("while generating")
+            .unwrap_err();
+
+        assert_eq!(ErrorKind::of(&error), Some(ErrorKind::Collision));
+        assert_eq!(format!("{:#}", error), "while generating: exists");
+    }
+
+    #[test]
+    fn conversion_errors_are_invalid_placeholders() {
+        let error = anyhow::Error::new(ConversionError::MissingPrompt {
+            var_name: "foo".into(),
+        });
+
+        assert_eq!(ErrorKind::of(&error), Some(ErrorKind::InvalidPlaceholder));
+        assert_eq!(ErrorKind::of(&anyhow!("other")), None);
+    }
+}
@@ -0,0 +1,210 @@
+//! Template inheritance, a template can be laid over a base template with `[template] extends = "<git-or-path>"`
+
+use anyhow::{bail, Result};
+use console::style;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    config::{Config, TemplateConfig, CONFIG_FILE_NAME, DEFAULT_RENDER_EXTENSION},
+    emoji, get_source_template_into_temp, locate_template_file, resolve_template_dir,
+    user_parsed_input::TemplateLocation,
+};
+
+/// How base templates are fetched, like the template itself
+pub struct FetchOptions<'a> {
+    pub offline: bool,
+    pub clone_retries: u32,
+    pub cache_dir: Option<&'a Path>,
+    pub identity: Option<&'a Path>,
+    pub respect_gitignore: bool,
+    pub recurse_submodules: bool,
+}
+
+/// Fetches the base template `config` extends, recursively, and copies its files into `template_folder`.
+/// Files of the template win over the ones of its base.
+///
+/// Returns `config` merged with the config of the base, see [`Config::merge_base`].
+pub fn extend_template(
+    source: &TemplateLocation,
+    template_base_dir: &Path,
+    template_folder: &Path,
+    template_config_path: Option<&Path>,
+    config: Config,
+    fetch: &FetchOptions,
+) -> Result<Config> {
+    if extends_of(&config).is_none() {
+        return Ok(config);
+    }
+
+    let config_dir = template_config_path
+        .and_then(Path::parent)
+        .unwrap_or(template_folder);
+    let origin_dir = resolve_origin_dir(source, template_base_dir, config_dir);
+    let mut chain = vec![location_key(source), path_key(&origin_dir)];
+
+    extend(template_folder, config, &origin_dir, fetch, &mut chain)
+}
+
+fn extend(
+    template_folder: &Path,
+    config: Config,
+    origin_dir: &Path,
+    fetch: &FetchOptions,
+    chain: &mut Vec<String>,
+) -> Result<Config> {
+    let extends = match extends_of(&config) {
+        Some(extends) => extends.to_owned(),
+        None => return Ok(config),
+    };
+
+    let location =
+        TemplateLocation::from_extends(&extends, origin_dir, fetch.identity.map(Path::to_path_buf));
+    let key = location_key(&location);
+    if chain.contains(&key) {
+        bail!(
+            "{} {} {}",
+            emoji::ERROR,
+            style("Cyclic `extends` of templates:").bold().red(),
+            style(format!("`{}` extends a template of its own chain", extends)).yellow(),
+        );
+    }
+    chain.push(key);
+
+    let (base_dir, _, _) = get_source_template_into_temp(
+        &location,
+        fetch.offline,
+        fetch.clone_retries,
+        fetch.cache_dir,
+        fetch.respect_gitignore,
+        fetch.recurse_submodules,
+        false,
+    )?;
+    let base_folder = resolve_template_dir(&base_dir, None, None)?;
+    let base_config_path =
+        locate_template_file(CONFIG_FILE_NAME, base_dir.path(), &base_folder).ok();
+    let base_config = Config::from_path(&base_config_path)?.unwrap_or_default();
+
+    let base_config_dir = base_config_path
+        .as_deref()
+        .and_then(Path::parent)
+        .unwrap_or(&base_folder);
+    let base_origin_dir = resolve_origin_dir(&location, base_dir.path(), base_config_dir);
+    let base_config = extend(&base_folder, base_config, &base_origin_dir, fetch, chain)?;
+
+    let config = config.merge_base(base_config);
+    let render_extension = config
+        .template
+        .as_ref()
+        .map_or(DEFAULT_RENDER_EXTENSION, TemplateConfig::render_extension);
+    copy_missing_files(&base_folder, template_folder, render_extension, true)?;
+    Ok(config)
+}
+
+/// The config of a base template is merged in memory and never part of the template dir, so no checksum
+/// manifest covers it. The commands of an extended config, of `exec` hooks or `choices_command`, are refused then.
+pub fn refuse_unverifiable_commands(extended: &Config) -> Result<()> {
+    if extended.has_commands() {
+        bail!(
+            "{} {} {}",
+            emoji::ERROR,
+            style("Can't verify the commands of a template that extends a base template:")
+                .bold()
+                .red(),
+            style("its base `cargo-generate.toml` is not covered by `--verify-checksums`").yellow(),
+        );
+    }
+    Ok(())
+}
+
+pub fn extends_of(config: &Config) -> Option<&str> {
+    config
+        .template
+        .as_ref()
+        .and_then(|template| template.extends.as_deref())
+}
+
+/// The dir a relative `extends` is resolved against, for local templates that's the original dir
+/// of their `cargo-generate.toml`, as only the template itself has been copied into a temp dir
+fn resolve_origin_dir(location: &TemplateLocation, temp_dir: &Path, config_dir: &Path) -> PathBuf {
+    match (location, config_dir.strip_prefix(temp_dir)) {
+        (TemplateLocation::Path(path), Ok(relative)) => path.join(relative),
+        _ => config_dir.to_path_buf(),
+    }
+}
+
+fn location_key(location: &TemplateLocation) -> String {
+    match location {
+        TemplateLocation::Git(git) => git.url().to_owned(),
+        TemplateLocation::Mercurial(hg) => hg.url().to_owned(),
+        TemplateLocation::Path(path) => path_key(path),
+        TemplateLocation::Archive(archive) => archive.clone(),
+        TemplateLocation::GitHubRelease(release) => release.to_string(),
+    }
+}
+
+fn path_key(path: &Path) -> String {
+    fs::canonicalize(path)
+        .unwrap_or_else(|_| path.to_path_buf())
+        .display()
+        .to_string()
+}
+
+/// Copies the files of `src` that don't exist in `dst`, also not with or without the `render_extension`.
+/// The `cargo-generate.toml` of the base is merged instead of copied.
+fn copy_missing_files(src: &Path, dst: &Path, render_extension: &str, is_root: bool) -> Result<()> {
+    fs::create_dir_all(dst)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        if file_name == ".git" || (is_root && file_name == CONFIG_FILE_NAME) {
+            continue;
+        }
+
+        let dst_path = dst.join(&file_name);
+        if entry.file_type()?.is_dir() {
+            copy_missing_files(&entry.path(), &dst_path, render_extension, false)?;
+            continue;
+        }
+
+        let name = file_name.to_string_lossy();
+        let twin = name.strip_suffix(render_extension).map_or_else(
+            || dst.join(format!("{}{}", name, render_extension)),
+            |stripped| dst.join(stripped),
+        );
+        if !dst_path.exists() && !twin.exists() {
+            fs::copy(entry.path(), &dst_path)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::create_file;
+
+    #[test]
+    fn copy_missing_files_keeps_the_files_of_the_child() -> Result<()> {
+        let base = tempfile::tempdir()?;
+        create_file(&base, "README.md", "base readme")?;
+        create_file(&base, "src/main.rs.liquid", "base main")?;
+        create_file(&base, "src/lib.rs", "base lib")?;
+        create_file(&base, CONFIG_FILE_NAME, "[placeholders]")?;
+        let child = tempfile::tempdir()?;
+        create_file(&child, "README.md", "child readme")?;
+        create_file(&child, "src/main.rs", "child main")?;
+
+        copy_missing_files(base.path(), child.path(), ".liquid", true)?;
+
+        let read = |path: &str| fs::read_to_string(child.path().join(path)).unwrap();
+        assert_eq!(read("README.md"), "child readme");
+        assert_eq!(read("src/main.rs"), "child main");
+        assert_eq!(read("src/lib.rs"), "base lib");
+        assert!(!child.path().join("src/main.rs.liquid").exists());
+        assert!(!child.path().join(CONFIG_FILE_NAME).exists());
+        Ok(())
+    }
+}
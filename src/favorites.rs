@@ -2,29 +2,72 @@
 
 use crate::{
     app_config::{AppConfig, FavoriteConfig},
-    emoji, GenerateArgs,
+    emoji, interactive, GenerateArgs,
 };
-use anyhow::Result;
+use anyhow::{bail, Result};
 use console::style;
 
+/// All favorites sorted by name, only those starting with `prefix` if given
+fn sorted_favorites<'a>(
+    app_config: &'a AppConfig,
+    prefix: Option<&str>,
+) -> Vec<(&'a String, &'a FavoriteConfig)> {
+    let mut favorites = app_config
+        .favorites
+        .as_ref()
+        .map(|h| {
+            h.iter()
+                .filter(|(key, _)| prefix.map_or(true, |f| key.starts_with(f)))
+                .collect::<Vec<(&String, &FavoriteConfig)>>()
+        })
+        .unwrap_or_default();
+    favorites.sort_by_key(|(key, _)| (*key).to_string());
+    favorites
+}
+
+/// Lets the user pick one of the favorites, when no template has been given at all.
+///
+/// In `--silent` mode there is no one to ask, so that's an error.
+pub fn choose_favorite(app_config: &AppConfig, args: &GenerateArgs) -> Result<String> {
+    if args.silent {
+        bail!(
+            "{} {}",
+            emoji::ERROR,
+            style("No template or favorite given, which is required in silent mode")
+                .bold()
+                .red()
+        );
+    }
+
+    let favorites = sorted_favorites(app_config, None);
+    if favorites.is_empty() {
+        bail!(
+            "{} {}",
+            emoji::ERROR,
+            style("No template given, and no favorites defined to choose from")
+                .bold()
+                .red()
+        );
+    }
+
+    let items = favorites
+        .iter()
+        .map(|(key, conf)| {
+            conf.description.as_ref().map_or_else(
+                || key.to_string(),
+                |description| format!("{}: {}", key, description),
+            )
+        })
+        .collect::<Vec<_>>();
+    let chosen = interactive::choose_favorite(&items)?;
+    Ok(favorites[chosen].0.clone())
+}
+
 pub fn list_favorites(app_config: &AppConfig, args: &GenerateArgs) -> Result<()> {
-    let data = {
-        let mut d = app_config
-            .favorites
-            .as_ref()
-            .map(|h| {
-                h.iter()
-                    .filter(|(key, _)| {
-                        args.template_path
-                            .auto_path()
-                            .map_or(true, |f| key.starts_with(f.as_ref()))
-                    })
-                    .collect::<Vec<(&String, &FavoriteConfig)>>()
-            })
-            .unwrap_or_default();
-        d.sort_by_key(|(key, _)| (*key).to_string());
-        d
-    };
+    let data = sorted_favorites(
+        app_config,
+        args.template_path.auto_path().map(|f| f.as_ref()),
+    );
 
     if data.is_empty() {
         println!(
@@ -1,16 +1,37 @@
-use crate::Result;
+use crate::{emoji, Result};
 
 use crate::template::render_string_gracefully;
+use anyhow::bail;
+use console::style;
 use liquid::{Object, Parser};
+use std::collections::HashMap;
+use std::fs;
 use std::path::{Component, Path, PathBuf};
 
-pub fn substitute_filename(filepath: &Path, parser: &Parser, context: &Object) -> Result<PathBuf> {
+/// Renders every segment of `filepath` with liquid, like `{{module_name}}.rs` to `my_module.rs`
+///
+/// Fails with the offending template path if a segment renders to an empty or invalid name,
+/// a name that is nothing but the `render_extension` would be empty once it is stripped.
+pub fn substitute_filename(
+    filepath: &Path,
+    parser: &Parser,
+    context: &Object,
+    render_extension: &str,
+) -> Result<PathBuf> {
     let mut path = PathBuf::new();
     for elem in filepath.components() {
         match elem {
             Component::Normal(e) => {
                 let parsed = render_string_gracefully(context, parser, e.to_str().unwrap())?;
                 let parsed = sanitize_filename(parsed.as_str());
+                if parsed.trim().is_empty() || parsed.trim() == render_extension {
+                    bail!(
+                        "{} {} `{}`",
+                        emoji::ERROR,
+                        style("Filename renders to an empty name:").bold().red(),
+                        style(filepath.display()).bold(),
+                    );
+                }
                 path.push(parsed);
             }
             other => path.push(other),
@@ -19,6 +40,87 @@ pub fn substitute_filename(filepath: &Path, parser: &Parser, context: &Object) -
     Ok(path)
 }
 
+/// Renames the rendered files in `dir` as declared in `[template.rename]`, like `gitignore` to `.gitignore`.
+///
+/// A file to rename may still carry its `render_extension`, files that don't exist, e.g. as they have been ignored,
+/// are skipped. Fails if a path leaves `dir`, or if a file would replace an existing one or another renamed one.
+pub fn apply_renames(
+    dir: &Path,
+    renames: &HashMap<String, String>,
+    render_extension: &str,
+) -> Result<()> {
+    let mut renames: Vec<_> = renames.iter().collect();
+    renames.sort();
+
+    let mut moves: Vec<(PathBuf, PathBuf)> = vec![];
+    for (from, to) in renames {
+        for path in [from, to] {
+            if !is_relative_within(Path::new(path)) {
+                bail!(
+                    "{} {} `{}`",
+                    emoji::ERROR,
+                    style("Rename must stay within the template:").bold().red(),
+                    style(path).bold(),
+                );
+            }
+        }
+        let source = [
+            dir.join(from),
+            dir.join(format!("{}{}", from, render_extension)),
+        ]
+        .into_iter()
+        .find(|source| source.exists());
+        let source = match source {
+            Some(source) => source,
+            None => continue,
+        };
+        let target = dir.join(to);
+        if moves.iter().any(|(_, other)| *other == target) {
+            bail!(
+                "{} {} `{}`",
+                emoji::ERROR,
+                style("Multiple files would be renamed to:").bold().red(),
+                style(to).bold(),
+            );
+        }
+        moves.push((source, target));
+    }
+
+    for (_, target) in &moves {
+        let existing = [
+            target.clone(),
+            PathBuf::from(format!("{}{}", target.display(), render_extension)),
+        ];
+        let collides = existing.iter().any(|existing| {
+            existing.exists() && !moves.iter().any(|(source, _)| source == existing)
+        });
+        if collides {
+            bail!(
+                "{} {} `{}`",
+                emoji::ERROR,
+                style("Rename would replace an existing file:").bold().red(),
+                style(target.strip_prefix(dir).unwrap_or(target).display()).bold(),
+            );
+        }
+    }
+
+    for (source, target) in moves {
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(source, target)?;
+    }
+    Ok(())
+}
+
+/// `true` for a relative path without any `..`, that can't point outside of the dir it is joined to
+fn is_relative_within(path: &Path) -> bool {
+    !path.as_os_str().is_empty()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
 fn sanitize_filename(filename: &str) -> String {
     use sanitize_filename::sanitize_with_options;
 
@@ -152,7 +254,74 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_fail_for_empty_filenames() {
+        assert!(substitute_filename("{{author}}.rs", prepare_context("sassman")).is_ok());
+        assert!(substitute_filename("{{author}}", prepare_context("")).is_err());
+        assert!(substitute_filename("{{author}}.liquid", prepare_context("")).is_err());
+        assert!(substitute_filename("{{author}}/main.rs", prepare_context("  ")).is_err());
+    }
+
+    #[test]
+    fn it_refuses_a_name_of_only_the_render_extension_in_use() {
+        let parser = Parser::default();
+        let substitute = |render_extension| {
+            super::substitute_filename(
+                "{{author}}.tera".as_ref(),
+                &parser,
+                &prepare_context(""),
+                render_extension,
+            )
+        };
+
+        assert!(substitute(".liquid").is_ok());
+        assert!(substitute(".tera").is_err());
+    }
+
     //region wrapper helpers
+    #[test]
+    fn should_rename_files_after_rendering() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join("gitignore"), "target")?;
+        fs::write(dir.path().join("npmrc.liquid"), "{{project-name}}")?;
+        let renames = HashMap::from([
+            ("gitignore".to_string(), ".gitignore".to_string()),
+            ("npmrc".to_string(), "config/.npmrc".to_string()),
+            ("missing".to_string(), ".missing".to_string()),
+        ]);
+
+        apply_renames(dir.path(), &renames, ".liquid")?;
+
+        assert!(!dir.path().join("gitignore").exists());
+        assert_eq!(fs::read_to_string(dir.path().join(".gitignore"))?, "target");
+        assert!(dir.path().join("config/.npmrc").exists());
+        assert!(!dir.path().join(".missing").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn should_refuse_colliding_or_escaping_renames() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join("gitignore"), "")?;
+        fs::write(dir.path().join(".gitignore"), "")?;
+        fs::write(dir.path().join("ignore"), "")?;
+
+        let existing = HashMap::from([("gitignore".to_string(), ".gitignore".to_string())]);
+        assert!(apply_renames(dir.path(), &existing, ".liquid").is_err());
+
+        let twice = HashMap::from([
+            ("gitignore".to_string(), "ignored".to_string()),
+            ("ignore".to_string(), "ignored".to_string()),
+        ]);
+        assert!(apply_renames(dir.path(), &twice, ".liquid").is_err());
+
+        let escaping = HashMap::from([("gitignore".to_string(), "../.gitignore".to_string())]);
+        assert!(apply_renames(dir.path(), &escaping, ".liquid").is_err());
+
+        assert!(dir.path().join("gitignore").exists());
+        Ok(())
+    }
+
     fn prepare_context(value: &str) -> Object {
         let mut ctx = Object::default();
         ctx.entry("author")
@@ -164,7 +333,7 @@ mod tests {
     fn substitute_filename(f: &str, ctx: Object) -> Result<String> {
         let parser = Parser::default();
 
-        super::substitute_filename(f.as_ref(), &parser, &ctx)
+        super::substitute_filename(f.as_ref(), &parser, &ctx, ".liquid")
             .map(|p| p.to_str().unwrap().to_string())
     }
     //endregion
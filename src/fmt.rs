@@ -0,0 +1,46 @@
+//! Module dealing with the `--fmt` arg, to run `cargo fmt` on the generated project
+
+use std::path::Path;
+use std::process::Command;
+
+use console::style;
+
+use crate::{info, requirements::is_on_path, warn};
+
+/// Runs `cargo fmt` in `project_dir` for `--fmt`, projects without a `Cargo.toml` are skipped.
+///
+/// The project has been generated already, so any failure is only a warning.
+pub fn format_project(project_dir: &Path) {
+    if !project_dir.join("Cargo.toml").is_file() {
+        return;
+    }
+    if !is_on_path("cargo") {
+        warn!(
+            "{}",
+            style("Not formatting the project, `cargo` is not on PATH").bold()
+        );
+        return;
+    }
+
+    info!(
+        "{}",
+        style("Formatting the project with `cargo fmt`").bold()
+    );
+    match Command::new("cargo")
+        .arg("fmt")
+        .current_dir(project_dir)
+        .output()
+    {
+        Ok(output) if output.status.success() => {}
+        Ok(output) => warn!(
+            "{} {}",
+            style("`cargo fmt` failed:").bold(),
+            style(String::from_utf8_lossy(&output.stderr).trim_end()).yellow()
+        ),
+        Err(e) => warn!(
+            "{} {}",
+            style("`cargo fmt` failed to run:").bold(),
+            style(e).yellow()
+        ),
+    }
+}
@@ -0,0 +1,186 @@
+//! Cache of cloned git templates, to not clone large templates again on every run
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use console::style;
+use git2::Repository;
+use remove_dir_all::remove_dir_all;
+use sha2::{Digest, Sha256};
+use walkdir::WalkDir;
+
+use super::{with_clone_retries, RepoCloneBuilder};
+use crate::{info, warn};
+
+/// Env var to relocate the template cache
+pub const CACHE_DIR_ENV: &str = "CARGO_GENERATE_CACHE_DIR";
+
+/// The directory cached templates are kept in, `$CARGO_HOME/cargo-generate-cache` by default.
+///
+/// `$CARGO_HOME` is the dir of the app config `cargo-generate.toml`, so the cache is kept next to it.
+pub fn template_cache_dir() -> Result<PathBuf> {
+    std::env::var_os(CACHE_DIR_ENV).map_or_else(
+        || {
+            home::cargo_home()
+                .map(|home| home.join("cargo-generate-cache"))
+                .context("Unable to locate the template cache, please set CARGO_GENERATE_CACHE_DIR")
+        },
+        |dir| Ok(PathBuf::from(dir)),
+    )
+}
+
+/// The cache entry of a template, keyed by a hash of its url and the requested branch.
+///
+/// The name of the repository is only a prefix to find an entry by hand, urls like `a/b_c` and `a_b/c`
+/// end up with the same sanitized name.
+pub fn entry_path(cache_dir: &Path, url: &str, branch: Option<&str>) -> PathBuf {
+    let options = sanitize_filename::Options {
+        replacement: "_",
+        ..Default::default()
+    };
+    let key = format!("{}@{}", url, branch.unwrap_or("HEAD"));
+    let name = url
+        .trim_end_matches('/')
+        .rsplit(['/', ':'])
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches(".git");
+    let name = sanitize_filename::sanitize_with_options(name, options);
+    cache_dir.join(format!("{}-{:x}", name, Sha256::digest(key)))
+}
+
+pub fn is_cached(entry: &Path) -> bool {
+    entry.join(".git").is_dir()
+}
+
+/// Brings the cache entry up to date, by fetching an existing one or cloning it afresh.
+///
+/// With `offline` an existing entry is used as is, so it only has nested submodules
+/// if it has been cached with `recurse_submodules`.
+pub fn update(
+    entry: &Path,
+    url: &str,
+    branch: Option<&str>,
+    identity: Option<&Path>,
+    offline: bool,
+    clone_retries: u32,
+    recurse_submodules: bool,
+) -> Result<()> {
+    if is_cached(entry) {
+        if offline {
+            return Ok(());
+        }
+        info!(
+            "{} `{}`",
+            style("Updating cached template").bold(),
+            style(url).bold().yellow()
+        );
+        match fetch(entry, url, branch, identity, recurse_submodules) {
+            Ok(()) => return Ok(()),
+            Err(e) => {
+                warn!(
+                    "{} {}",
+                    style("Updating the cached template failed, cloning it again:").bold(),
+                    e
+                );
+                remove_dir_all(entry)?;
+            }
+        }
+    } else if entry.exists() {
+        // leftovers of an interrupted clone
+        remove_dir_all(entry)?;
+    }
+
+    with_clone_retries(clone_retries, || {
+        fs::create_dir_all(entry)?;
+        let mut builder = RepoCloneBuilder::new_with(url, branch, identity)?;
+        builder.set_recurse_submodules(recurse_submodules);
+        builder.set_offline(offline);
+        builder.clone_with_submodules(entry).inspect_err(|_| {
+            remove_dir_all(entry).ok();
+        })
+    })
+    .context("Please check if the Git user / repository exists.")?;
+    Ok(())
+}
+
+fn fetch(
+    entry: &Path,
+    url: &str,
+    branch: Option<&str>,
+    identity: Option<&Path>,
+    recurse_submodules: bool,
+) -> Result<()> {
+    let repo = Repository::open(entry)?;
+    let branch = match branch {
+        Some(branch) => branch.to_string(),
+        None => repo
+            .head()?
+            .shorthand()
+            .context("The cached template is not on a branch")?
+            .to_string(),
+    };
+    let mut builder = RepoCloneBuilder::new_with(url, None, identity)?;
+    builder.set_recurse_submodules(recurse_submodules);
+    builder.fetch_with_submodules(&repo, &branch)
+}
+
+/// Copies the cache entry including its history, so a tag or revision can be checked out
+pub fn copy_entry(entry: &Path, dest: &Path) -> Result<()> {
+    for src in WalkDir::new(entry).min_depth(1) {
+        let src = src?;
+        let dest = dest.join(src.path().strip_prefix(entry)?);
+        if src.file_type().is_dir() {
+            fs::create_dir_all(&dest)?;
+        } else if src.file_type().is_file() {
+            fs::copy(src.path(), &dest)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_are_keyed_by_url_and_branch() {
+        let cache_dir = Path::new("/cache");
+        let url = "https://github.com/foo/bar.git";
+
+        let main = entry_path(cache_dir, url, Some("main"));
+        let head = entry_path(cache_dir, url, None);
+        assert_ne!(main, head);
+        assert_eq!(main.parent(), Some(cache_dir));
+        assert!(main
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with("bar-")));
+    }
+
+    #[test]
+    fn urls_with_the_same_sanitized_name_get_their_own_entry() {
+        let cache_dir = Path::new("/cache");
+
+        assert_ne!(
+            entry_path(cache_dir, "https://github.com/a/b_c", None),
+            entry_path(cache_dir, "https://github.com/a_b/c", None)
+        );
+    }
+
+    #[test]
+    fn copies_the_entry_including_history() {
+        let entry = tempfile::tempdir().unwrap();
+        fs::create_dir_all(entry.path().join(".git/refs")).unwrap();
+        fs::write(entry.path().join(".git/HEAD"), "ref: refs/heads/main").unwrap();
+        fs::write(entry.path().join("Cargo.toml"), "[package]").unwrap();
+
+        let dest = tempfile::tempdir().unwrap();
+        copy_entry(entry.path(), dest.path()).unwrap();
+
+        assert!(dest.path().join(".git/HEAD").is_file());
+        assert!(dest.path().join(".git/refs").is_dir());
+        assert!(dest.path().join("Cargo.toml").is_file());
+    }
+}
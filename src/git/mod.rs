@@ -1,16 +1,24 @@
 //! Handle `--git` and related flags
 
 use std::path::{Path, PathBuf};
-use std::{io, ops::Sub, thread::sleep, time::Duration};
+use std::{fs, io, ops::Sub, thread::sleep, time::Duration};
 
-use anyhow::Result;
-use git2::{build::RepoBuilder, FetchOptions, ProxyOptions, Repository, RepositoryInitOptions};
+use anyhow::{anyhow, Result};
+use console::style;
+use git2::{
+    build::{CheckoutBuilder, RepoBuilder},
+    AutotagOption, Commit, Direction, ErrorClass, ErrorCode, FetchOptions, ProxyOptions, Remote,
+    RemoteCallbacks, Repository, RepositoryInitOptions, ResetType, Submodule,
+};
 use remove_dir_all::remove_dir_all;
+use walkdir::WalkDir;
 
+pub use cache::template_cache_dir;
 pub use utils::clone_git_template_into_temp;
 
-use crate::warn;
+use crate::{emoji, info, warn};
 
+mod cache;
 mod creds;
 mod gitconfig;
 mod identity_path;
@@ -30,6 +38,8 @@ mod utils;
 
 // basically we want to call:
 // git clone --recurse-submodules --depth 1 --branch <branch> <url> <tmp_dir>
+// followed by a checkout of `--tag <tag>` or `--rev <sha>`, if given
+// only the submodules of the template itself are fetched, unless `--recurse-submodules` is given
 
 /// Default branch to use if not specified but required
 pub const DEFAULT_BRANCH: &str = "main";
@@ -41,6 +51,8 @@ struct RepoCloneBuilder<'cb> {
     fetch_options: FetchOptions<'cb>,
     identity: Option<PathBuf>,
     url: String,
+    recurse_submodules: bool,
+    offline: bool,
 }
 
 impl<'cb> RepoCloneBuilder<'cb> {
@@ -49,6 +61,8 @@ impl<'cb> RepoCloneBuilder<'cb> {
         po.auto();
         let mut fo = FetchOptions::new();
         fo.proxy_options(po);
+        // tags are not always reachable from the cloned branch, but `--tag` must find them
+        fo.download_tags(AutotagOption::All);
 
         let url = gitconfig::find_gitconfig()?.map_or_else(
             || url.to_owned(),
@@ -64,6 +78,8 @@ impl<'cb> RepoCloneBuilder<'cb> {
             fetch_options: fo,
             identity: None,
             url,
+            recurse_submodules: false,
+            offline: false,
         })
     }
 
@@ -88,26 +104,33 @@ impl<'cb> RepoCloneBuilder<'cb> {
         self.builder.branch(branch);
     }
 
-    fn clone(mut self, dest_path: &Path) -> Result<Repository> {
+    pub const fn set_recurse_submodules(&mut self, recurse_submodules: bool) {
+        self.recurse_submodules = recurse_submodules;
+    }
+
+    /// With `offline` only a local repository is cloned, its submodules must not need a fetch either
+    pub const fn set_offline(&mut self, offline: bool) {
+        self.offline = offline;
+    }
+
+    fn set_credentials(&mut self) -> Result<()> {
+        let callbacks = self.remote_callbacks()?;
+        self.fetch_options.remote_callbacks(callbacks);
+        Ok(())
+    }
+
+    fn remote_callbacks(&mut self) -> Result<RemoteCallbacks<'cb>> {
         #[cfg(not(windows))]
         {
             if self.identity.is_some() {
-                if let Some(callbacks) = creds::git_ssh_credentials_callback(self.identity)? {
-                    self.fetch_options.remote_callbacks(callbacks);
-                } else {
-                    self.fetch_options
-                        .remote_callbacks(creds::git_ssh_agent_callback());
+                if let Some(callbacks) = creds::git_ssh_credentials_callback(self.identity.take())?
+                {
+                    return Ok(callbacks);
                 }
-            } else {
-                self.fetch_options
-                    .remote_callbacks(creds::git_ssh_agent_callback());
             }
         }
         #[cfg(windows)]
         {
-            use crate::info;
-            use console::style;
-
             if self.identity.is_some() {
                 info!(
                     "{} {}",
@@ -115,26 +138,143 @@ impl<'cb> RepoCloneBuilder<'cb> {
                     style("The `--identity` argument is not supported on windows, trying to use ssh-agent instead.").bold().yellow(),
                 );
             }
-            self.fetch_options
-                .remote_callbacks(creds::git_ssh_agent_callback());
         }
+        Ok(creds::git_ssh_agent_callback())
+    }
+
+    /// lists the branches of the remote without cloning it, like `git ls-remote --heads`
+    fn list_branches(mut self) -> Result<RemoteBranches> {
+        let callbacks = self.remote_callbacks()?;
+        let mut proxy_options = ProxyOptions::new();
+        proxy_options.auto();
+
+        let mut remote = Remote::create_detached(self.url.as_str())?;
+        let connection =
+            remote.connect_auth(Direction::Fetch, Some(callbacks), Some(proxy_options))?;
+        let default = connection
+            .default_branch()
+            .ok()
+            .and_then(|head| head.as_str().map(String::from))
+            .and_then(|head| head.strip_prefix("refs/heads/").map(String::from));
+        let names = connection
+            .list()?
+            .iter()
+            .filter_map(|head| head.name().strip_prefix("refs/heads/"))
+            .map(String::from)
+            .collect();
+
+        Ok(RemoteBranches { names, default })
+    }
+
+    fn clone(mut self, dest_path: &Path) -> Result<Repository> {
+        self.set_credentials()?;
         self.builder.fetch_options(self.fetch_options);
         self.builder
             .clone(&self.url, dest_path)
             .map_err(anyhow::Error::from)
     }
 
+    /// fetches `branch` of the `origin` remote into an existing clone and resets its working copy to it
+    pub fn fetch_with_submodules(mut self, repo: &Repository, branch: &str) -> Result<()> {
+        self.set_credentials()?;
+        let mut remote = repo.find_remote("origin")?;
+        remote.fetch(
+            &[format!("+refs/heads/{0}:refs/remotes/origin/{0}", branch)],
+            Some(&mut self.fetch_options),
+            None,
+        )?;
+        let target = repo.revparse_single(&format!("refs/remotes/origin/{}", branch))?;
+        repo.reset(&target, ResetType::Hard, None)?;
+        update_submodules(repo, self.recurse_submodules, self.offline)
+    }
+
     pub fn clone_with_submodules(self, dest_path: &Path) -> Result<Repository> {
+        let recurse_submodules = self.recurse_submodules;
+        let offline = self.offline;
         self.clone(dest_path).and_then(|repo| {
-            for mut sub in repo.submodules()? {
-                sub.update(true, None)?;
-            }
-
+            update_submodules(&repo, recurse_submodules, offline)?;
             Ok(repo)
         })
     }
 }
 
+/// The branches of a remote repository
+pub struct RemoteBranches {
+    pub names: Vec<String>,
+    /// The branch the `HEAD` of the remote points to, which a clone checks out
+    pub default: Option<String>,
+}
+
+/// Lists the branches of the repository at `url`, without cloning it
+pub fn list_remote_branches(
+    url: &str,
+    identity: Option<&Path>,
+    clone_retries: u32,
+) -> Result<RemoteBranches> {
+    with_clone_retries(clone_retries, || {
+        RepoCloneBuilder::new_with(url, None, identity)?.list_branches()
+    })
+}
+
+/// Runs `clone` and retries it up to `retries` times on transient errors, waiting 1s, 2s, 4s, .. before each retry
+fn with_clone_retries<T>(retries: u32, mut clone: impl FnMut() -> Result<T>) -> Result<T> {
+    let mut attempt = 0;
+    loop {
+        match clone() {
+            Err(e) if attempt < retries && is_transient(&e) => {
+                attempt += 1;
+                let wait_for = Duration::from_secs(2_u64.pow(attempt.sub(1).min(6)));
+                warn!(
+                    "{} {} [retrying ({}/{}) in {:?}]",
+                    style("Cloning the template failed:").bold(),
+                    e,
+                    attempt,
+                    retries,
+                    wait_for
+                );
+                sleep(wait_for);
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Network errors are worth a retry, a failed authentication or a missing repository is not
+fn is_transient(error: &anyhow::Error) -> bool {
+    error
+        .chain()
+        .filter_map(|cause| cause.downcast_ref::<git2::Error>())
+        .any(|e| {
+            let message = e.message().to_lowercase();
+            let permanent = ["not found", "authentication", "401", "403", "404"]
+                .iter()
+                .any(|hint| message.contains(hint));
+            matches!(
+                e.class(),
+                ErrorClass::Net
+                    | ErrorClass::Os
+                    | ErrorClass::Ssl
+                    | ErrorClass::Ssh
+                    | ErrorClass::Http
+            ) && !matches!(
+                e.code(),
+                ErrorCode::Auth | ErrorCode::Certificate | ErrorCode::NotFound
+            ) && !permanent
+        })
+}
+
+/// The git config of the repository in the current dir, or the global one, e.g. for `user.name`
+pub fn user_config() -> Option<git2::Config> {
+    std::env::current_dir()
+        .ok()
+        .and_then(|cwd| {
+            Repository::discover(cwd)
+                .and_then(|repo| repo.config())
+                .ok()
+        })
+        .or_else(|| git2::Config::open_default().ok())
+}
+
 /// Init project_dir with fresh repository on branch
 ///
 /// Arguments:
@@ -159,6 +299,118 @@ pub fn init(project_dir: &Path, branch: &str, force: bool) -> Git2Result<Reposit
     }
 }
 
+/// checkout the given revision (commit SHA) as detached head, including its submodules
+pub fn checkout_revision(
+    repo: &Repository,
+    revision: &str,
+    recurse_submodules: bool,
+    offline: bool,
+) -> Result<()> {
+    let object = repo.revparse_single(revision).map_err(|_| {
+        anyhow!(
+            "{} {} {}",
+            emoji::ERROR,
+            style("Git Error:").bold().red(),
+            style(format!(
+                "Revision `{}` does not exist in the fetched history.",
+                revision
+            ))
+            .bold()
+            .red(),
+        )
+    })?;
+
+    checkout_commit(repo, &object.peel_to_commit()?, recurse_submodules, offline)
+}
+
+/// checkout the commit the given tag points to as detached head, including its submodules
+pub fn checkout_tag(
+    repo: &Repository,
+    tag: &str,
+    recurse_submodules: bool,
+    offline: bool,
+) -> Result<()> {
+    let object = repo
+        .revparse_single(&format!("refs/tags/{}", tag))
+        .map_err(|_| {
+            anyhow!(
+                "{} {} {}",
+                emoji::ERROR,
+                style("Git Error:").bold().red(),
+                style(format!("Tag `{}` does not exist in the repository.", tag))
+                    .bold()
+                    .red(),
+            )
+        })?;
+    info!(
+        "{} `{}`",
+        style("Using tag").bold(),
+        style(tag).bold().yellow()
+    );
+
+    checkout_commit(repo, &object.peel_to_commit()?, recurse_submodules, offline)
+}
+
+fn checkout_commit(
+    repo: &Repository,
+    commit: &Commit,
+    recurse_submodules: bool,
+    offline: bool,
+) -> Result<()> {
+    repo.checkout_tree(commit.as_object(), Some(CheckoutBuilder::new().force()))?;
+    repo.set_head_detached(commit.id())?;
+    update_submodules(repo, recurse_submodules, offline)
+}
+
+/// initializes and checks out the submodules of `repo`, with `recursive` also the ones nested in them.
+///
+/// Their files end up as plain files in the working copy, only their `.git` file refers to the history,
+/// which is left out like the `.git` dir of the template itself.
+///
+/// With `offline` a submodule that would have to be fetched from a remote url is refused.
+fn update_submodules(repo: &Repository, recursive: bool, offline: bool) -> Result<()> {
+    for mut sub in repo.submodules()? {
+        if offline && !is_available_offline(&sub) {
+            return Err(crate::refuse_to_fetch_offline(
+                sub.url()
+                    .unwrap_or_else(|| sub.path().to_str().unwrap_or_default()),
+            ));
+        }
+        sub.update(true, None)?;
+        if recursive {
+            update_submodules(&sub.open()?, true, offline)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// A submodule can be updated without network access if its url is local, or if it has been checked out
+/// before and already has the commit the template wants
+fn is_available_offline(sub: &Submodule) -> bool {
+    let has_commit = match (sub.open(), sub.index_id()) {
+        (Ok(repo), Some(commit)) => repo.find_commit(commit).is_ok(),
+        _ => false,
+    };
+    has_commit || sub.url().map_or(false, utils::is_local)
+}
+
+/// Copies the `.git` dir of `template_dir` into `project_dir`, so the project keeps the history of the template
+pub fn copy_history(template_dir: &Path, project_dir: &Path) -> io::Result<()> {
+    let git_dir = template_dir.join(".git");
+    for entry in WalkDir::new(&git_dir) {
+        let entry = entry?;
+        let relative_path = entry.path().strip_prefix(&git_dir).unwrap_or(entry.path());
+        let target = project_dir.join(".git").join(relative_path);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
 /// remove context of repository by removing `.git` from filesystem
 pub fn remove_history(project_dir: &Path) -> io::Result<()> {
     let git_dir = project_dir.join(".git");
@@ -189,3 +441,55 @@ pub fn remove_history(project_dir: &Path) -> io::Result<()> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_network_errors_are_retried() {
+        let transient = git2::Error::new(
+            ErrorCode::GenericError,
+            ErrorClass::Net,
+            "failed to resolve address for github.com",
+        );
+        assert!(is_transient(&anyhow::Error::from(transient)));
+
+        let missing = git2::Error::new(
+            ErrorCode::GenericError,
+            ErrorClass::Http,
+            "unexpected http status code: 404",
+        );
+        assert!(!is_transient(&anyhow::Error::from(missing)));
+
+        let auth = git2::Error::new(ErrorCode::Auth, ErrorClass::Ssh, "authentication failed");
+        assert!(!is_transient(&anyhow::Error::from(auth)));
+    }
+
+    #[test]
+    fn permanent_errors_fail_without_retrying() {
+        let mut attempts = 0;
+        let result: Result<()> = with_clone_retries(3, || {
+            attempts += 1;
+            Err(git2::Error::new(ErrorCode::NotFound, ErrorClass::Repository, "not found").into())
+        });
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+    }
+
+    #[test]
+    fn lists_the_branches_of_a_remote_and_its_default() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let repo = init(dir.path(), "trunk", false)?;
+        let signature = git2::Signature::now("cargo-generate", "cargo-generate@example.com")?;
+        let tree = repo.find_tree(repo.index()?.write_tree()?)?;
+        let commit = repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])?;
+        repo.branch("variant", &repo.find_commit(commit)?, false)?;
+
+        let branches = list_remote_branches(dir.path().to_str().unwrap(), None)?;
+
+        assert_eq!(branches.names, vec!["trunk", "variant"]);
+        assert_eq!(branches.default.as_deref(), Some("trunk"));
+        Ok(())
+    }
+}
@@ -5,7 +5,9 @@ use std::path::{Path, PathBuf};
 use git2::Repository;
 use tempfile::TempDir;
 
-use super::RepoCloneBuilder;
+use super::{
+    cache, checkout_revision, checkout_tag, remove_history, with_clone_retries, RepoCloneBuilder,
+};
 
 /// deals with `~/` and `$HOME/` prefixes
 pub fn canonicalize_path(p: impl AsRef<Path>) -> Result<PathBuf> {
@@ -28,21 +30,75 @@ pub fn home() -> Result<PathBuf> {
 }
 
 // clone git reposiotry into temp using libgit2
+//
+// with a `cache_dir` the template is cloned or updated there, and copied into temp from the cache
+//
+// returns the branch that has been cloned and the ref that has been checked out,
+// which is the `tag` or `revision` if one is given and the branch otherwise
+#[allow(clippy::too_many_arguments)]
 pub fn clone_git_template_into_temp(
     git: &str,
     branch: Option<&str>,
+    tag: Option<&str>,
+    revision: Option<&str>,
     identity: Option<&Path>,
-) -> anyhow::Result<(TempDir, String)> {
+    offline: bool,
+    clone_retries: u32,
+    cache_dir: Option<&Path>,
+    recurse_submodules: bool,
+    keep_history: bool,
+) -> anyhow::Result<(TempDir, String, String)> {
+    let cache_entry = cache_dir.map(|dir| cache::entry_path(dir, git, branch));
+    let is_cached = cache_entry.as_deref().is_some_and(cache::is_cached);
+    if offline && !is_local(git) && !is_cached {
+        return Err(crate::refuse_to_fetch_offline(git));
+    }
     let git_clone_dir = tempfile::tempdir()?;
 
-    let builder = RepoCloneBuilder::new_with(git, branch, identity)?;
-
-    let repo = builder
-        .clone_with_submodules(git_clone_dir.path())
-        .context("Please check if the Git user / repository exists.")?;
+    let repo = match cache_entry {
+        Some(ref cache_entry) => {
+            cache::update(
+                cache_entry,
+                git,
+                branch,
+                identity,
+                offline,
+                clone_retries,
+                recurse_submodules,
+            )?;
+            cache::copy_entry(cache_entry, git_clone_dir.path())?;
+            Repository::open(git_clone_dir.path())?
+        }
+        None => with_clone_retries(clone_retries, || {
+            let mut builder = RepoCloneBuilder::new_with(git, branch, identity)?;
+            builder.set_recurse_submodules(recurse_submodules);
+            builder.set_offline(offline);
+            builder.clone_with_submodules(git_clone_dir.path())
+        })
+        .context("Please check if the Git user / repository exists.")?,
+    };
     let branch = get_branch_name_repo(&repo)?;
+    if let Some(tag) = tag {
+        checkout_tag(&repo, tag, recurse_submodules, offline)?;
+    }
+    if let Some(revision) = revision {
+        checkout_revision(&repo, revision, recurse_submodules, offline)?;
+    }
+    if cache_entry.is_some() && !keep_history {
+        // the copied history is not needed anymore, the cache keeps it for the next update
+        remove_history(git_clone_dir.path())?;
+    }
+
+    let reference = tag
+        .or(revision)
+        .map_or_else(|| branch.clone(), String::from);
+
+    Ok((git_clone_dir, branch, reference))
+}
 
-    Ok((git_clone_dir, branch))
+/// local repositories can be cloned without any network access
+pub(super) fn is_local(git: &str) -> bool {
+    git.starts_with("file://") || Path::new(git).exists()
 }
 
 /// thanks to @extrawurst for pointing this out
@@ -0,0 +1,171 @@
+//! Handle `--github-release owner/repo@tag --asset <name>`, templates shipped as an archive attached to a GitHub release
+
+use std::{env, io};
+
+use anyhow::{anyhow, Context, Result};
+use console::style;
+use serde::Deserialize;
+use tempfile::{NamedTempFile, TempDir};
+
+use crate::{archive, emoji, info, user_parsed_input::GitHubReleaseUserInput};
+
+const DEFAULT_API_URL: &str = "https://api.github.com";
+
+#[derive(Deserialize)]
+struct Release {
+    assets: Vec<Asset>,
+}
+
+#[derive(Deserialize)]
+struct Asset {
+    name: String,
+    /// The API url of the asset, unlike the browser download url it also works with a token for private repositories
+    url: String,
+}
+
+/// downloads the asset of a GitHub release and extracts it into a fresh temp dir, like an `--archive`
+///
+/// `GITHUB_TOKEN` is sent if set, and `GITHUB_API_URL` selects another API than the one of github.com,
+/// like the one of a GitHub Enterprise server.
+pub fn extract_release_asset_into_temp(
+    release: &GitHubReleaseUserInput,
+    offline: bool,
+) -> Result<TempDir> {
+    let release_url = format!(
+        "{}/repos/{}/releases/tags/{}",
+        api_url(),
+        release.repo(),
+        release.tag()
+    );
+    if offline {
+        return Err(crate::refuse_to_fetch_offline(&release_url));
+    }
+    info!(
+        "{} `{}` {} `{}@{}`",
+        style("Downloading release asset").bold(),
+        style(release.asset()).bold().yellow(),
+        style("of").bold(),
+        style(release.repo()).bold().yellow(),
+        style(release.tag()).bold().yellow()
+    );
+
+    let response = request(&release_url, "application/vnd.github+json")
+        .call()
+        .map_err(|error| match error {
+            ureq::Error::Status(404, _) => anyhow!(
+                "{} {} {}",
+                emoji::ERROR,
+                style("Release Error:").bold().red(),
+                style(format!(
+                    "no release `{}` found in `{}`, or the repository is private and `GITHUB_TOKEN` is not set",
+                    release.tag(),
+                    release.repo()
+                ))
+                .bold()
+                .red(),
+            ),
+            error => anyhow::Error::new(error)
+                .context(format!("Failed to look up the release at {}", release_url)),
+        })?;
+    let found: Release = serde_json::from_reader(response.into_reader())
+        .with_context(|| format!("Unexpected response of {}", release_url))?;
+    let asset = find_asset(&found, release)?;
+
+    let response = request(&asset.url, "application/octet-stream")
+        .call()
+        .with_context(|| format!("Failed to download the release asset `{}`", asset.name))?;
+    let mut file = NamedTempFile::new()?;
+    io::copy(&mut response.into_reader(), &mut file)?;
+
+    archive::extract_archive_file_into_temp(file.path(), &asset.name)
+}
+
+fn api_url() -> String {
+    env::var("GITHUB_API_URL").map_or_else(
+        |_| DEFAULT_API_URL.to_owned(),
+        |url| url.trim_end_matches('/').to_owned(),
+    )
+}
+
+fn request(url: &str, accept: &str) -> ureq::Request {
+    let request = ureq::get(url)
+        .set("Accept", accept)
+        .set("User-Agent", "cargo-generate");
+    match env::var("GITHUB_TOKEN") {
+        Ok(token) if !token.is_empty() => {
+            request.set("Authorization", &format!("Bearer {}", token))
+        }
+        _ => request,
+    }
+}
+
+fn find_asset<'a>(found: &'a Release, release: &GitHubReleaseUserInput) -> Result<&'a Asset> {
+    found
+        .assets
+        .iter()
+        .find(|asset| asset.name == release.asset())
+        .ok_or_else(|| {
+            let names: Vec<&str> = found
+                .assets
+                .iter()
+                .map(|asset| asset.name.as_str())
+                .collect();
+            anyhow!(
+                "{} {} {}",
+                emoji::ERROR,
+                style("Release Error:").bold().red(),
+                style(format!(
+                    "release `{}` of `{}` has no asset `{}`, available are: {}",
+                    release.tag(),
+                    release.repo(),
+                    release.asset(),
+                    if names.is_empty() {
+                        "none".to_owned()
+                    } else {
+                        names.join(", ")
+                    }
+                ))
+                .bold()
+                .red(),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(asset: &str) -> GitHubReleaseUserInput {
+        GitHubReleaseUserInput::parse("owner/repo@v1.2.3", asset).unwrap()
+    }
+
+    #[test]
+    fn should_find_the_asset_by_name() {
+        let found: Release = serde_json::from_str(
+            r#"{"tag_name": "v1.2.3", "assets": [
+                {"name": "checksums.txt", "url": "https://api.github.com/repos/owner/repo/releases/assets/1"},
+                {"name": "template.tar.gz", "url": "https://api.github.com/repos/owner/repo/releases/assets/2"}
+            ]}"#,
+        )
+        .unwrap();
+
+        let asset = find_asset(&found, &release("template.tar.gz")).unwrap();
+        assert_eq!(
+            asset.url,
+            "https://api.github.com/repos/owner/repo/releases/assets/2"
+        );
+
+        let error = find_asset(&found, &release("template.zip"))
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(error.contains("no asset `template.zip`"));
+        assert!(error.contains("checksums.txt, template.tar.gz"));
+    }
+
+    #[test]
+    fn should_refuse_to_download_offline() {
+        let error = extract_release_asset_into_temp(&release("template.tar.gz"), true).unwrap_err();
+        assert!(error.to_string().contains("refusing to fetch"));
+    }
+}
@@ -0,0 +1,188 @@
+//! Handle `--hg` and template locations with a `hg+` prefix, cloned by the `hg` executable,
+//! as well as `--vcs hg` to initialize the generated project as Mercurial repository
+
+use std::io;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, Context, Result};
+use console::style;
+use remove_dir_all::remove_dir_all;
+use tempfile::TempDir;
+
+use crate::{emoji, info};
+
+const HG_PREFIX: &str = "hg+";
+
+/// strips the `hg+` prefix from urls like `hg+https://hg.example.com/template`,
+/// returns `None` if the location is not meant to be cloned with Mercurial
+pub fn strip_hg_prefix(location: &str) -> Option<&str> {
+    location.strip_prefix(HG_PREFIX)
+}
+
+/// clones a Mercurial repository into a fresh temp dir, without its history
+pub fn clone_hg_template_into_temp(
+    url: &str,
+    branch: Option<&str>,
+    offline: bool,
+) -> Result<TempDir> {
+    if offline && !Path::new(url).exists() {
+        return Err(crate::refuse_to_fetch_offline(url));
+    }
+    ensure_hg_is_installed()?;
+
+    let hg_clone_dir = tempfile::tempdir()?;
+    info!(
+        "{} `{}`",
+        style("Cloning Mercurial repository").bold(),
+        style(url).bold().yellow()
+    );
+
+    let mut command = Command::new("hg");
+    command.arg("clone");
+    if let Some(branch) = branch {
+        command.arg("--branch").arg(branch);
+    }
+    let output = command
+        .arg(url)
+        .arg(hg_clone_dir.path())
+        .stdin(Stdio::null())
+        .output()
+        .with_context(|| format!("Failed to execute `hg clone {}`", url))?;
+
+    if !output.status.success() {
+        bail!(
+            "{} {} {}\n{}",
+            emoji::ERROR,
+            style("Mercurial Error:").bold().red(),
+            style(format!("`hg clone {}` failed.", url)).bold().red(),
+            String::from_utf8_lossy(&output.stderr).trim(),
+        );
+    }
+    remove_history(hg_clone_dir.path())?;
+
+    Ok(hg_clone_dir)
+}
+
+/// Init `project_dir` as fresh Mercurial repository and commit all generated files.
+///
+/// If `project_dir` is already within a Mercurial repository nothing is done, unless `force` is set.
+pub fn init(project_dir: &Path, force: bool) -> Result<()> {
+    ensure_hg_is_installed()?;
+
+    let within_repository = Command::new("hg")
+        .arg("root")
+        .current_dir(project_dir)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success());
+    if within_repository && (!force || project_dir.join(".hg").is_dir()) {
+        return Ok(());
+    }
+
+    run_hg(project_dir, &["init"])?;
+    run_hg(
+        project_dir,
+        &["commit", "--addremove", "--message", "Initial commit"],
+    )
+}
+
+fn run_hg(dir: &Path, args: &[&str]) -> Result<()> {
+    let command = format!("hg {}", args.join(" "));
+    let output = Command::new("hg")
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .output()
+        .with_context(|| format!("Failed to execute `{}`", command))?;
+
+    if !output.status.success() {
+        bail!(
+            "{} {} {}\n{}",
+            emoji::ERROR,
+            style("Mercurial Error:").bold().red(),
+            style(format!("`{}` failed.", command)).bold().red(),
+            String::from_utf8_lossy(&output.stderr).trim(),
+        );
+    }
+    Ok(())
+}
+
+fn ensure_hg_is_installed() -> Result<()> {
+    match Command::new("hg")
+        .arg("--version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+    {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => bail!(
+            "{} {} {}",
+            emoji::ERROR,
+            style("Mercurial Error:").bold().red(),
+            style("`hg` was not found in PATH. Please install Mercurial (https://www.mercurial-scm.org/downloads) to use it with cargo-generate.")
+                .bold()
+                .red(),
+        ),
+        result => {
+            result.context("Failed to execute `hg --version`")?;
+            Ok(())
+        }
+    }
+}
+
+/// remove context of repository by removing `.hg` from filesystem
+fn remove_history(project_dir: &Path) -> io::Result<()> {
+    let hg_dir = project_dir.join(".hg");
+    if hg_dir.is_dir() {
+        remove_dir_all(&hg_dir)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_strip_hg_prefix() {
+        assert_eq!(
+            strip_hg_prefix("hg+https://hg.example.com/template"),
+            Some("https://hg.example.com/template")
+        );
+        assert_eq!(strip_hg_prefix("https://github.com/foo/bar.git"), None);
+        assert_eq!(strip_hg_prefix("foo/bar"), None);
+    }
+
+    #[test]
+    fn should_init_and_commit_the_project() {
+        if ensure_hg_is_installed().is_err() {
+            // Mercurial is optional, nothing to test without it
+            return;
+        }
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(tmp.path().join("Cargo.toml"), "[package]").unwrap();
+
+        init(tmp.path(), false).unwrap();
+
+        assert!(tmp.path().join(".hg").is_dir());
+        let output = Command::new("hg")
+            .args(&["status", "--unknown"])
+            .current_dir(tmp.path())
+            .output()
+            .unwrap();
+        assert!(output.stdout.is_empty());
+    }
+
+    #[test]
+    fn should_remove_hg_history() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(tmp.path().join(".hg/store")).unwrap();
+        std::fs::write(tmp.path().join("Cargo.toml"), "[package]").unwrap();
+
+        remove_history(tmp.path()).unwrap();
+
+        assert!(!tmp.path().join(".hg").exists());
+        assert!(tmp.path().join("Cargo.toml").exists());
+    }
+}
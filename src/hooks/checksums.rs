@@ -0,0 +1,285 @@
+//! Verifies the hook scripts of a template against a SHA-256 checksum manifest, see `--verify-checksums`
+
+use anyhow::{bail, Context, Result};
+use console::style;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Component, Path},
+};
+
+use crate::emoji;
+
+/// Checks that every hook script is listed in the `manifest` with its SHA-256 checksum, and that every file
+/// listed matches its checksum. Paths in the manifest are relative to the template dir, and must stay within it.
+///
+/// The manifest uses the format of `sha256sum`, one `<checksum>  <path>` per line.
+///
+/// The `config_file` of a template with `exec` hooks or a `choices_command` must be listed as well, as it holds
+/// their commands.
+pub fn verify_checksums(
+    dir: &Path,
+    manifest: &Path,
+    hook_files: &[String],
+    config_file: Option<&Path>,
+) -> Result<()> {
+    let contents = fs::read_to_string(manifest).with_context(|| {
+        format!(
+            "{} {} {}",
+            emoji::ERROR,
+            style("Unable to read the checksum manifest:").bold().red(),
+            style(manifest.display()).yellow(),
+        )
+    })?;
+    let checksums = parse_manifest(&contents)?;
+
+    for hook in hook_files {
+        if !checksums.contains_key(normalize(hook).as_str()) {
+            bail!(
+                "{} {} {}",
+                emoji::ERROR,
+                style("No checksum for hook:").bold().red(),
+                style(hook).yellow(),
+            );
+        }
+    }
+
+    if let Some(config_file) = config_file {
+        let config_file = config_file
+            .strip_prefix(dir)
+            .map(|path| normalize(&path.to_string_lossy()))
+            .ok()
+            .filter(|path| checksums.contains_key(path.as_str()));
+        if config_file.is_none() {
+            bail!(
+                "{} {}",
+                emoji::ERROR,
+                style("No checksum for the config holding commands")
+                    .bold()
+                    .red(),
+            );
+        }
+    }
+
+    for (path, expected) in &checksums {
+        let contents = fs::read(dir.join(path)).with_context(|| {
+            format!(
+                "{} {} {}",
+                emoji::ERROR,
+                style("Unable to read file listed in the checksum manifest:")
+                    .bold()
+                    .red(),
+                style(path).yellow(),
+            )
+        })?;
+        if sha256(&contents) != *expected {
+            bail!(
+                "{} {} {}",
+                emoji::ERROR,
+                style("Checksum mismatch, refusing to run hooks:")
+                    .bold()
+                    .red(),
+                style(path).yellow(),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_manifest(contents: &str) -> Result<HashMap<String, String>> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (checksum, path) = line
+                .split_once(char::is_whitespace)
+                .filter(|(checksum, _)| {
+                    checksum.len() == 64 && checksum.chars().all(|c| c.is_ascii_hexdigit())
+                })
+                .with_context(|| {
+                    format!(
+                        "{} {} {}",
+                        emoji::ERROR,
+                        style("Invalid line in the checksum manifest:").bold().red(),
+                        style(line).yellow(),
+                    )
+                })?;
+            // `sha256sum` marks files read in binary mode with a leading `*`
+            let path = normalize(path.trim_start().trim_start_matches('*'));
+            if !is_within_template(&path) {
+                bail!(
+                    "{} {} {}",
+                    emoji::ERROR,
+                    style("Path outside of the template in the checksum manifest:")
+                        .bold()
+                        .red(),
+                    style(path).yellow(),
+                );
+            }
+            Ok((path, checksum.to_lowercase()))
+        })
+        .collect()
+}
+
+/// paths are compared with forward slashes and without a leading `./`
+fn normalize(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    path.strip_prefix("./").unwrap_or(&path).to_string()
+}
+
+/// a listed file must neither be absolute nor leave the template dir with `..`
+fn is_within_template(path: &str) -> bool {
+    Path::new(path)
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+fn sha256(contents: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(contents))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HELLO_SHA256: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    fn template_with_manifest(manifest: &str) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("hooks")).unwrap();
+        fs::write(dir.path().join("hooks/pre.rhai"), "hello").unwrap();
+        fs::write(dir.path().join("checksums.txt"), manifest).unwrap();
+        dir
+    }
+
+    #[test]
+    fn it_accepts_matching_checksums_in_sha256sum_format() {
+        let dir = template_with_manifest(&format!(
+            "# generated by sha256sum\n{}  ./hooks/pre.rhai\n",
+            HELLO_SHA256
+        ));
+
+        verify_checksums(
+            dir.path(),
+            &dir.path().join("checksums.txt"),
+            &["hooks/pre.rhai".into()],
+            None,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn it_rejects_a_modified_hook() {
+        let dir = template_with_manifest(&format!("{} *hooks/pre.rhai\n", HELLO_SHA256));
+        fs::write(dir.path().join("hooks/pre.rhai"), "hello, modified").unwrap();
+
+        let error = verify_checksums(
+            dir.path(),
+            &dir.path().join("checksums.txt"),
+            &["hooks/pre.rhai".into()],
+            None,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("Checksum mismatch"));
+    }
+
+    #[test]
+    fn it_rejects_hooks_missing_in_the_manifest() {
+        let dir = template_with_manifest(&format!("{}  hooks/pre.rhai\n", HELLO_SHA256));
+
+        let error = verify_checksums(
+            dir.path(),
+            &dir.path().join("checksums.txt"),
+            &["hooks/pre.rhai".into(), "hooks/post.rhai".into()],
+            None,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("No checksum for hook"));
+    }
+
+    fn template_with_exec_hook() -> tempfile::TempDir {
+        let dir = template_with_manifest("");
+        let config = "[hooks]\npre = [{ type = \"exec\", command = [\"true\"] }]\n";
+        fs::write(dir.path().join("cargo-generate.toml"), config).unwrap();
+        fs::write(
+            dir.path().join("checksums.txt"),
+            format!(
+                "{}  hooks/pre.rhai\n{}  cargo-generate.toml\n",
+                HELLO_SHA256,
+                sha256(config.as_bytes())
+            ),
+        )
+        .unwrap();
+        dir
+    }
+
+    #[test]
+    fn it_verifies_the_config_of_exec_hooks() {
+        let dir = template_with_exec_hook();
+
+        verify_checksums(
+            dir.path(),
+            &dir.path().join("checksums.txt"),
+            &["hooks/pre.rhai".into()],
+            Some(&dir.path().join("cargo-generate.toml")),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn it_rejects_an_edited_exec_hook_command() {
+        let dir = template_with_exec_hook();
+        fs::write(
+            dir.path().join("cargo-generate.toml"),
+            "[hooks]\npre = [{ type = \"exec\", command = [\"false\"] }]\n",
+        )
+        .unwrap();
+
+        let error = verify_checksums(
+            dir.path(),
+            &dir.path().join("checksums.txt"),
+            &["hooks/pre.rhai".into()],
+            Some(&dir.path().join("cargo-generate.toml")),
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("Checksum mismatch"));
+    }
+
+    #[test]
+    fn it_rejects_exec_hooks_without_a_checksum_of_the_config() {
+        let dir = template_with_manifest(&format!("{}  hooks/pre.rhai\n", HELLO_SHA256));
+        fs::write(dir.path().join("cargo-generate.toml"), "[hooks]").unwrap();
+
+        let error = verify_checksums(
+            dir.path(),
+            &dir.path().join("checksums.txt"),
+            &["hooks/pre.rhai".into()],
+            Some(&dir.path().join("cargo-generate.toml")),
+        )
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("No checksum for the config holding commands"));
+    }
+
+    #[test]
+    fn it_rejects_malformed_lines() {
+        assert!(parse_manifest("not-a-checksum  hooks/pre.rhai").is_err());
+        assert!(parse_manifest(HELLO_SHA256).is_err());
+    }
+
+    #[test]
+    fn it_rejects_paths_outside_of_the_template() {
+        for path in [
+            "/etc/passwd",
+            "../other/hooks/pre.rhai",
+            "hooks/../../pre.rhai",
+        ] {
+            let error = parse_manifest(&format!("{}  {}", HELLO_SHA256, path)).unwrap_err();
+            assert!(error.to_string().contains("Path outside of the template"));
+        }
+    }
+}
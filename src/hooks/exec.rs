@@ -0,0 +1,149 @@
+//! Hooks of `type = "exec"`, running a command instead of a rhai script
+
+use anyhow::{bail, Result};
+use console::style;
+use liquid::ValueView;
+use liquid_core::Value;
+use std::{path::Path, process::Command};
+
+use crate::{emoji, out, warn};
+
+/// Prefix of the env vars the template variables are passed in, like `CARGO_GENERATE_VAR_PROJECT_NAME`
+const ENV_VAR_PREFIX: &str = "CARGO_GENERATE_VAR_";
+
+/// Runs `command` in `dir`, a non-zero exit fails the generation.
+///
+/// The output is captured and printed, so it doesn't interfere with `--output-format json`.
+pub fn run(dir: &Path, command: &[String], liquid_object: &liquid::Object) -> Result<()> {
+    let (program, args) = match command.split_first() {
+        Some(split) => split,
+        None => bail!(
+            "{} {}",
+            emoji::ERROR,
+            style("The command of an `exec` hook must not be empty")
+                .bold()
+                .red(),
+        ),
+    };
+    let full_command = command.join(" ");
+
+    let output = match Command::new(program)
+        .args(args)
+        .current_dir(dir)
+        .envs(variables_as_env(liquid_object))
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) => bail!(
+            "{} {} {}",
+            emoji::ERROR,
+            style(format!("Hook `{}` failed to execute:", full_command))
+                .bold()
+                .red(),
+            style(e).yellow(),
+        ),
+    };
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !stdout.trim().is_empty() {
+        out!("{}", stdout.trim_end());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !stderr.trim().is_empty() {
+        warn!("{}", stderr.trim_end());
+    }
+
+    if !output.status.success() {
+        bail!(
+            "{} {} {}",
+            emoji::ERROR,
+            style(format!("Hook `{}` returned non-zero status:", full_command))
+                .bold()
+                .red(),
+            style(output.status).yellow(),
+        );
+    }
+    Ok(())
+}
+
+/// The template variables as env vars, names are uppercased with `-` and `.` replaced by `_`.
+/// Objects like `now` are flattened, like `CARGO_GENERATE_VAR_NOW_YEAR`, arrays are joined by `,`.
+fn variables_as_env(liquid_object: &liquid::Object) -> Vec<(String, String)> {
+    fn collect(prefix: &str, object: &liquid::Object, vars: &mut Vec<(String, String)>) {
+        for (key, value) in object.iter() {
+            let name = format!("{}{}", prefix, key.to_uppercase().replace(['-', '.'], "_"));
+            match value {
+                Value::Object(inner) => collect(&format!("{}_", name), inner, vars),
+                Value::Array(values) => vars.push((
+                    name,
+                    values
+                        .iter()
+                        .map(|v| v.to_kstr().to_string())
+                        .collect::<Vec<_>>()
+                        .join(","),
+                )),
+                Value::Nil => (),
+                _ => vars.push((name, value.to_kstr().to_string())),
+            }
+        }
+    }
+
+    let mut vars = vec![];
+    collect(ENV_VAR_PREFIX, liquid_object, &mut vars);
+    vars
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variables_are_passed_as_env_vars() {
+        let mut now = liquid::Object::new();
+        now.insert("year".into(), Value::scalar(2022));
+        let mut object = liquid::Object::new();
+        object.insert("project-name".into(), Value::scalar("foo-bar"));
+        object.insert("use_serde".into(), Value::scalar(true));
+        object.insert(
+            "features".into(),
+            Value::Array(vec![Value::scalar("a"), Value::scalar("b")]),
+        );
+        object.insert("now".into(), Value::Object(now));
+        object.insert("nothing".into(), Value::Nil);
+
+        let mut vars = variables_as_env(&object);
+        vars.sort();
+        assert_eq!(
+            vars,
+            vec![
+                ("CARGO_GENERATE_VAR_FEATURES".into(), "a,b".into()),
+                ("CARGO_GENERATE_VAR_NOW_YEAR".into(), "2022".into()),
+                ("CARGO_GENERATE_VAR_PROJECT_NAME".into(), "foo-bar".into()),
+                ("CARGO_GENERATE_VAR_USE_SERDE".into(), "true".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_non_zero_exit_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let object = liquid::Object::new();
+
+        if Command::new("sh").arg("-c").arg("true").output().is_err() {
+            return; // no `sh` available, e.g. on windows
+        }
+        assert!(run(
+            dir.path(),
+            &["sh".into(), "-c".into(), "exit 0".into()],
+            &object
+        )
+        .is_ok());
+        assert!(run(
+            dir.path(),
+            &["sh".into(), "-c".into(), "exit 3".into()],
+            &object
+        )
+        .is_err());
+        assert!(run(dir.path(), &[], &object).is_err());
+    }
+}
@@ -1,23 +1,33 @@
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use console::style;
 use heck::{
     ToKebabCase, ToLowerCamelCase, ToPascalCase, ToShoutyKebabCase, ToShoutySnakeCase, ToSnakeCase,
     ToTitleCase, ToUpperCamelCase,
 };
-use rhai::EvalAltResult;
+use rhai::{Dynamic, EvalAltResult, Position};
 use std::cell::RefCell;
 use std::rc::Rc;
-use std::{env, path::Path};
+use std::{env, ffi::OsStr, path::Path};
+use walkdir::WalkDir;
 
-use crate::config;
+use crate::config::{self, Hook, TypedHook};
 use crate::emoji;
+use crate::{info, out, warn};
 
+mod checksums;
+mod exec;
 mod file_mod;
 mod system_mod;
 mod variable_mod;
 
+pub use checksums::verify_checksums;
+
 type HookResult<T> = std::result::Result<T, Box<EvalAltResult>>;
 
+/// Raised by `abort("message")` in a hook script, to stop the generation with a clean message
+#[derive(Debug, Clone)]
+struct HookAbort(String);
+
 struct CleanupJob<F: FnOnce()>(Option<F>);
 
 impl<F: FnOnce()> CleanupJob<F> {
@@ -37,10 +47,22 @@ pub fn execute_pre_hooks(
     liquid_object: Rc<RefCell<liquid::Object>>,
     template_cfg: &mut config::Config,
     allow_commands: bool,
-    silent: bool,
+    deny_hooks: bool,
+    skip_hooks: &[String],
+    dry_run: bool,
 ) -> Result<()> {
-    let engine = create_rhai_engine(dir, liquid_object, allow_commands, silent);
-    evaluate_scripts(dir, &template_cfg.get_pre_hooks(), engine)
+    let hooks = template_cfg.get_pre_hooks();
+    if deny_hooks {
+        skip_scripts("pre", &hooks, "`--deny-hooks`");
+        return Ok(());
+    }
+    let hooks = filter_skipped_scripts("pre", hooks, skip_hooks);
+    if dry_run {
+        list_scripts_of_dry_run("pre", &hooks);
+        return Ok(());
+    }
+    let engine = create_rhai_engine(dir, Rc::clone(&liquid_object), allow_commands);
+    evaluate_scripts(dir, &hooks, engine, &liquid_object, allow_commands)
 }
 
 pub fn execute_post_hooks(
@@ -48,41 +70,213 @@ pub fn execute_post_hooks(
     liquid_object: Rc<RefCell<liquid::Object>>,
     template_cfg: &config::Config,
     allow_commands: bool,
-    silent: bool,
+    deny_hooks: bool,
+    skip_hooks: &[String],
+    dry_run: bool,
 ) -> Result<()> {
-    let engine = create_rhai_engine(dir, liquid_object, allow_commands, silent);
-    evaluate_scripts(dir, &template_cfg.get_post_hooks(), engine)
+    let hooks = template_cfg.get_post_hooks();
+    if deny_hooks {
+        skip_scripts("post", &hooks, "`--deny-hooks`");
+        return Ok(());
+    }
+    let hooks = filter_skipped_scripts("post", hooks, skip_hooks);
+    if dry_run {
+        list_scripts_of_dry_run("post", &hooks);
+        return Ok(());
+    }
+    let mut engine = create_rhai_engine(dir, Rc::clone(&liquid_object), allow_commands);
+
+    let files = generated_files(dir, &template_cfg.get_hook_files());
+    engine.register_fn("generated_files", move || files.clone());
+
+    evaluate_scripts(dir, &hooks, engine, &liquid_object, allow_commands)
+}
+
+/// All files of the generated project, relative to `dir`, without the hook scripts that are
+/// removed after the post hooks
+fn generated_files(dir: &Path, hook_files: &[String]) -> rhai::Array {
+    let hook_files = hook_files.iter().map(|f| dir.join(f)).collect::<Vec<_>>();
+    WalkDir::new(dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git")
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_file() && !hook_files.contains(&e.path().to_path_buf()))
+        .filter_map(|e| {
+            e.path()
+                .strip_prefix(dir)
+                .ok()
+                .map(|p| Dynamic::from(p.to_string_lossy().replace('\\', "/")))
+        })
+        .collect()
+}
+
+fn skip_scripts(kind: &str, hooks: &[Hook], reason: &str) {
+    for hook in hooks {
+        warn!(
+            "{} {} {}",
+            style(format!("Skipping {} hook", kind)).bold(),
+            style(hook.name()).bold().yellow(),
+            style(format!("because of {}", reason)).bold(),
+        );
+    }
+}
+
+/// A dry run doesn't run any hook, as they could write files or run commands
+fn list_scripts_of_dry_run(kind: &str, hooks: &[Hook]) {
+    for hook in hooks {
+        info!(
+            "{} {}",
+            style(format!("Dry run, would run {} hook", kind)).bold(),
+            style(hook.name()).bold().yellow(),
+        );
+    }
+}
+
+/// Removes the hooks named by `--skip-hook`, listing each one skipped
+fn filter_skipped_scripts(kind: &str, hooks: Vec<Hook>, skip_hooks: &[String]) -> Vec<Hook> {
+    let (skipped, hooks): (Vec<_>, Vec<_>) = hooks
+        .into_iter()
+        .partition(|hook| is_named(&hook.name(), skip_hooks));
+    skip_scripts(kind, &skipped, "`--skip-hook`");
+    hooks
+}
+
+/// A hook is named by its path as configured, or by its file name without extension
+fn is_named(script: &str, names: &[String]) -> bool {
+    let stem = Path::new(script).file_stem();
+    names
+        .iter()
+        .any(|name| name == script || stem == Some(OsStr::new(name)))
 }
 
-fn evaluate_scripts(dir: &Path, scripts: &[String], engine: rhai::Engine) -> Result<()> {
+/// Compiles the rhai scripts of the hooks in `dir` without running them, returns the problems found,
+/// like syntax errors or missing scripts
+pub fn check_scripts(dir: &Path, template_cfg: &config::Config) -> Vec<String> {
+    let liquid_object = Rc::new(RefCell::new(liquid::Object::new()));
+    let engine = create_rhai_engine(dir, liquid_object, false);
+    let mut problems = Vec::new();
+    for hook in template_cfg
+        .get_pre_hooks()
+        .iter()
+        .chain(template_cfg.get_post_hooks().iter())
+    {
+        match hook {
+            Hook::Rhai(file) | Hook::Typed(TypedHook::Rhai { file }) => {
+                if let Err(e) = engine.compile_file(dir.join(file)) {
+                    problems.push(format!("hook `{}` doesn't compile: {}", file, e));
+                }
+            }
+            Hook::Typed(TypedHook::Exec {
+                file: Some(file), ..
+            }) if !dir.join(file).is_file() => {
+                problems.push(format!("hook `{}` doesn't exist", file));
+            }
+            Hook::Typed(TypedHook::Exec { .. }) => (),
+        }
+    }
+    problems
+}
+
+fn evaluate_scripts(
+    dir: &Path,
+    hooks: &[Hook],
+    engine: rhai::Engine,
+    liquid_object: &Rc<RefCell<liquid::Object>>,
+    allow_commands: bool,
+) -> Result<()> {
     let cwd = env::current_dir()?;
-    let _ = CleanupJob::new(move || {
+    let _cleanup = CleanupJob::new(move || {
         env::set_current_dir(cwd).ok();
     });
     env::set_current_dir(dir)?;
 
-    for script in scripts {
-        engine
-            .eval_file::<()>(script.into())
-            .map_err(|e| anyhow::anyhow!(e.to_string()))
-            .context(format!(
+    for hook in hooks {
+        let script = match hook {
+            Hook::Rhai(file) | Hook::Typed(TypedHook::Rhai { file }) => file,
+            Hook::Typed(TypedHook::Exec { command, .. }) => {
+                if !allow_commands {
+                    bail!(
+                        "{} {} {}",
+                        emoji::ERROR,
+                        style(format!(
+                            "The hook `{}` runs a system command, which is not allowed.",
+                            hook.name()
+                        ))
+                        .bold()
+                        .red(),
+                        style("Review the template and use `--allow-commands` to allow it.")
+                            .yellow(),
+                    );
+                }
+                exec::run(dir, command, &RefCell::borrow(liquid_object))?;
+                continue;
+            }
+        };
+        if let Err(e) = engine.eval_file::<()>(script.into()) {
+            if let Some(HookAbort(message)) = find_abort(&e) {
+                bail!(
+                    "{} {} {}",
+                    emoji::ERROR,
+                    style("Template aborted:").bold().red(),
+                    style(message).yellow(),
+                );
+            }
+            if calls_system_module(&e) {
+                bail!(
+                    "{} {} {}",
+                    emoji::ERROR,
+                    style(format!(
+                        "The script `{}` tries to run system commands, which are not allowed.",
+                        script
+                    ))
+                    .bold()
+                    .red(),
+                    style("Review the template and use `--allow-commands` to allow them.").yellow(),
+                );
+            }
+
+            return Err(anyhow::anyhow!(e.to_string())).context(format!(
                 "{} {} {}",
                 emoji::ERROR,
                 style("Failed executing script:").bold().red(),
                 style(script.to_owned()).yellow(),
-            ))?;
+            ));
+        }
     }
 
     Ok(())
 }
 
+/// an `abort` might be wrapped, e.g. when called inside a function of the script
+fn find_abort(error: &EvalAltResult) -> Option<HookAbort> {
+    match error {
+        EvalAltResult::ErrorRuntime(value, _) => value.clone().try_cast::<HookAbort>(),
+        EvalAltResult::ErrorInFunctionCall(_, _, inner, _)
+        | EvalAltResult::ErrorInModule(_, inner, _) => find_abort(inner),
+        _ => None,
+    }
+}
+
+/// the `system` module is only registered with `--allow-commands`, so calling it fails otherwise
+fn calls_system_module(error: &EvalAltResult) -> bool {
+    match error {
+        EvalAltResult::ErrorFunctionNotFound(signature, _) => signature.starts_with("system::"),
+        EvalAltResult::ErrorModuleNotFound(name, _) => name == "system",
+        EvalAltResult::ErrorInFunctionCall(_, _, inner, _)
+        | EvalAltResult::ErrorInModule(_, inner, _) => calls_system_module(inner),
+        _ => false,
+    }
+}
+
 fn create_rhai_engine(
     dir: &Path,
     liquid_object: Rc<RefCell<liquid::Object>>,
     allow_commands: bool,
-    silent: bool,
 ) -> rhai::Engine {
     let mut engine = rhai::Engine::new();
+    // keep stdout free for `--output-format json`
+    engine.on_print(|message| out!("{}", message));
 
     // register modules
     let module = variable_mod::create_module(liquid_object);
@@ -91,8 +285,11 @@ fn create_rhai_engine(
     let module = file_mod::create_module(dir);
     engine.register_static_module("file", module.into());
 
-    let module = system_mod::create_module(allow_commands, silent);
-    engine.register_static_module("system", module.into());
+    // without `--allow-commands` hooks are sandboxed, there is no way to run system commands
+    if allow_commands {
+        let module = system_mod::create_module();
+        engine.register_static_module("system", module.into());
+    }
 
     // register functions for changing case
     engine.register_fn("to_kebab_case", |str: &str| str.to_kebab_case());
@@ -110,7 +307,11 @@ fn create_rhai_engine(
 
     // other free-standing functions
     engine.register_result_fn("abort", |error: &str| -> HookResult<String> {
-        Err(error.into())
+        Err(EvalAltResult::ErrorRuntime(
+            Dynamic::from(HookAbort(error.to_string())),
+            Position::NONE,
+        )
+        .into())
     });
 
     engine
@@ -6,7 +6,9 @@ use std::cell::RefCell;
 use std::rc::Rc;
 
 use crate::interactive::prompt_for_variable;
-use crate::project_variables::{StringEntry, TemplateSlots, VarInfo};
+use crate::project_variables::{
+    liquid_value_into_dynamic, number_into_value, StringEntry, TemplateSlots, VarInfo,
+};
 
 use super::HookResult;
 
@@ -26,11 +28,11 @@ pub fn create_module(liquid_object: Rc<RefCell<Object>>) -> Module {
     module.set_native_fn("get", {
         let liquid_object = liquid_object.clone();
         move |name: &str| -> HookResult<Dynamic> {
-            match liquid_object.get_value(name) {
-                NamedValue::NonExistant => Ok(Dynamic::from(String::from(""))),
-                NamedValue::Bool(v) => Ok(Dynamic::from(v)),
-                NamedValue::String(v) => Ok(Dynamic::from(v)),
-            }
+            Ok(liquid_object
+                .borrow()
+                .get(name)
+                .and_then(liquid_value_into_dynamic)
+                .unwrap_or_else(|| Dynamic::from(String::from(""))))
         }
     });
 
@@ -39,7 +41,7 @@ pub fn create_module(liquid_object: Rc<RefCell<Object>>) -> Module {
 
         move |name: &str, value: &str| -> HookResult<()> {
             match liquid_object.get_value(name) {
-                NamedValue::NonExistant | NamedValue::String(_) => {
+                NamedValue::NonExistant | NamedValue::String => {
                     liquid_object.borrow_mut().insert(
                         name.to_string().into(),
                         Value::Scalar(value.to_string().into()),
@@ -56,7 +58,7 @@ pub fn create_module(liquid_object: Rc<RefCell<Object>>) -> Module {
 
         move |name: &str, value: bool| -> HookResult<()> {
             match liquid_object.get_value(name) {
-                NamedValue::NonExistant | NamedValue::Bool(_) => {
+                NamedValue::NonExistant | NamedValue::Bool => {
                     liquid_object
                         .borrow_mut()
                         .insert(name.to_string().into(), Value::Scalar(value.into()));
@@ -67,6 +69,38 @@ pub fn create_module(liquid_object: Rc<RefCell<Object>>) -> Module {
         }
     });
 
+    module.set_native_fn("set", {
+        let liquid_object = liquid_object.clone();
+
+        move |name: &str, value: i64| -> HookResult<()> {
+            match liquid_object.get_value(name) {
+                NamedValue::NonExistant | NamedValue::Number => {
+                    liquid_object
+                        .borrow_mut()
+                        .insert(name.to_string().into(), Value::Scalar(value.into()));
+                    Ok(())
+                }
+                _ => Err(format!("Variable {} not a number", name).into()),
+            }
+        }
+    });
+
+    module.set_native_fn("set", {
+        let liquid_object = liquid_object.clone();
+
+        move |name: &str, value: f64| -> HookResult<()> {
+            match liquid_object.get_value(name) {
+                NamedValue::NonExistant | NamedValue::Number => {
+                    liquid_object
+                        .borrow_mut()
+                        .insert(name.to_string().into(), number_into_value(value));
+                    Ok(())
+                }
+                _ => Err(format!("Variable {} not a number", name).into()),
+            }
+        }
+    });
+
     module.set_native_fn("set", {
         move |name: &str, value: Array| -> HookResult<()> {
             match liquid_object.get_value(name) {
@@ -86,6 +120,8 @@ pub fn create_module(liquid_object: Rc<RefCell<Object>>) -> Module {
         move |prompt: &str, default_value: bool| -> HookResult<bool> {
             let value = prompt_for_variable(&TemplateSlots {
                 prompt: prompt.into(),
+                prompt_if: None,
+                group: None,
                 var_name: "".into(),
                 var_info: VarInfo::Bool {
                     default: Some(default_value),
@@ -103,12 +139,18 @@ pub fn create_module(liquid_object: Rc<RefCell<Object>>) -> Module {
         move |prompt: &str| -> HookResult<String> {
             let value = prompt_for_variable(&TemplateSlots {
                 prompt: prompt.into(),
+                prompt_if: None,
+                group: None,
                 var_name: "".into(),
                 var_info: VarInfo::String {
                     entry: Box::new(StringEntry {
                         default: None,
                         choices: None,
                         regex: None,
+                        regex_hint: None,
+                        choices_command: None,
+                        editor: false,
+                        transform: None,
                     }),
                 },
             });
@@ -124,12 +166,18 @@ pub fn create_module(liquid_object: Rc<RefCell<Object>>) -> Module {
         move |prompt: &str, default_value: &str| -> HookResult<String> {
             let value = prompt_for_variable(&TemplateSlots {
                 prompt: prompt.into(),
+                prompt_if: None,
+                group: None,
                 var_name: "".into(),
                 var_info: VarInfo::String {
                     entry: Box::new(StringEntry {
                         default: Some(default_value.into()),
                         choices: None,
                         regex: None,
+                        regex_hint: None,
+                        choices_command: None,
+                        editor: false,
+                        transform: None,
                     }),
                 },
             });
@@ -145,12 +193,18 @@ pub fn create_module(liquid_object: Rc<RefCell<Object>>) -> Module {
         move |prompt: &str, default_value: &str, regex: &str| -> HookResult<String> {
             let value = prompt_for_variable(&TemplateSlots {
                 prompt: prompt.into(),
+                prompt_if: None,
+                group: None,
                 var_name: "".into(),
                 var_info: VarInfo::String {
                     entry: Box::new(StringEntry {
                         default: Some(default_value.into()),
                         choices: None,
                         regex: Some(Regex::new(regex).map_err(|_| "Invalid regex")?),
+                        regex_hint: None,
+                        choices_command: None,
+                        editor: false,
+                        transform: None,
                     }),
                 },
             });
@@ -166,6 +220,8 @@ pub fn create_module(liquid_object: Rc<RefCell<Object>>) -> Module {
         move |prompt: &str, default_value: &str, choices: rhai::Array| -> HookResult<String> {
             let value = prompt_for_variable(&TemplateSlots {
                 prompt: prompt.into(),
+                prompt_if: None,
+                group: None,
                 var_name: "".into(),
                 var_info: VarInfo::String {
                     entry: Box::new(StringEntry {
@@ -177,6 +233,10 @@ pub fn create_module(liquid_object: Rc<RefCell<Object>>) -> Module {
                                 .collect(),
                         ),
                         regex: None,
+                        regex_hint: None,
+                        choices_command: None,
+                        editor: false,
+                        transform: None,
                     }),
                 },
             });
@@ -191,10 +251,12 @@ pub fn create_module(liquid_object: Rc<RefCell<Object>>) -> Module {
     module
 }
 
+/// The type of a variable, a variable can only be set to a value of the same type
 enum NamedValue {
     NonExistant,
-    Bool(bool),
-    String(String),
+    Bool,
+    Number,
+    String,
 }
 
 trait GetNamedValue {
@@ -203,19 +265,12 @@ trait GetNamedValue {
 
 impl GetNamedValue for Rc<RefCell<Object>> {
     fn get_value(&self, name: &str) -> NamedValue {
-        match self.borrow().get(name) {
-            Some(value) => value
-                .as_scalar()
-                .map(|scalar| {
-                    scalar.to_bool().map_or_else(
-                        || {
-                            let v = scalar.to_kstr();
-                            NamedValue::String(String::from(v.as_str()))
-                        },
-                        NamedValue::Bool,
-                    )
-                })
-                .unwrap_or_else(|| NamedValue::NonExistant),
+        match self.borrow().get(name).and_then(Value::as_scalar) {
+            Some(scalar) => match scalar.type_name() {
+                "boolean" => NamedValue::Bool,
+                "whole number" | "fractional number" => NamedValue::Number,
+                _ => NamedValue::String,
+            },
             None => NamedValue::NonExistant,
         }
     }
@@ -225,13 +280,15 @@ fn rhai_to_liquid_value(val: Dynamic) -> HookResult<Value> {
     val.as_bool()
         .map(Into::into)
         .map(Value::Scalar)
+        .or_else(|_| val.as_int().map(Into::into).map(Value::Scalar))
+        .or_else(|_| val.as_float().map(number_into_value))
         .or_else(|_| val.clone().into_string().map(Into::into).map(Value::Scalar))
         .or_else(|_| {
             val.clone()
                 .try_cast::<Array>()
                 .ok_or_else(|| {
                     format!(
-                        "expecting type to be string, bool or array but found a '{}' instead",
+                        "expecting type to be string, bool, number or array but found a '{}' instead",
                         val.type_name()
                     )
                     .into()
@@ -277,4 +334,49 @@ mod tests {
             ]))
         );
     }
+
+    #[test]
+    fn numbers_can_be_set_and_read_back() {
+        let mut engine = rhai::Engine::new();
+        let liquid_object = Rc::new(RefCell::new(liquid::Object::new()));
+        liquid_object
+            .borrow_mut()
+            .insert("port".into(), Value::Scalar(8080.into()));
+
+        let module = create_module(liquid_object.clone());
+        engine.register_static_module("variable", module.into());
+
+        let next_port = engine
+            .eval::<i64>(
+                r#"
+            variable::set("workers", 4);
+            variable::set("ratio", 0.5);
+            variable::set("port", variable::get("port") + 1);
+            variable::get("port")
+        "#,
+            )
+            .unwrap();
+        assert_eq!(next_port, 8081);
+
+        let liquid_object = liquid_object.borrow();
+        assert_eq!(liquid_object.get("workers"), Some(&Value::Scalar(4.into())));
+        assert_eq!(liquid_object.get("ratio"), Some(&Value::Scalar(0.5.into())));
+    }
+
+    #[test]
+    fn the_type_of_a_variable_cannot_be_changed() {
+        let mut engine = rhai::Engine::new();
+        let liquid_object = Rc::new(RefCell::new(liquid::Object::new()));
+        liquid_object
+            .borrow_mut()
+            .insert("name".into(), Value::Scalar("demo".into()));
+
+        let module = create_module(liquid_object);
+        engine.register_static_module("variable", module.into());
+
+        assert!(engine.eval::<()>(r#"variable::set("name", 1);"#).is_err());
+        assert!(engine
+            .eval::<()>(r#"variable::set("name", true);"#)
+            .is_err());
+    }
 }
@@ -1,4 +1,4 @@
-use ignore::WalkBuilder;
+use ignore::{gitignore::GitignoreBuilder, WalkBuilder};
 use remove_dir_all::*;
 use std::fs::remove_file;
 use std::{
@@ -7,7 +7,10 @@ use std::{
 };
 
 use crate::config::CONFIG_FILE_NAME;
+use crate::out;
 pub const IGNORE_FILE_NAME: &str = ".genignore";
+/// gitignore-like file at the template root, merged with the `ignore` list of the config
+pub const CARGO_GENERATE_IGNORE_FILE_NAME: &str = ".cargo-generate-ignore";
 
 // We ignore the `.cargo-ok` file if one is present. This file is a somewhat
 // obscure marker that cargo leaves around after extracting a tarball to
@@ -21,13 +24,20 @@ const CARGO_OK_FILE_NAME: &str = ".cargo-ok";
 
 /// Takes the directory path and removes the files/directories specified in the
 /// `.genignore` file
+/// The active template config is always removed, wherever it has been found within `dir`,
+/// other `cargo-generate.toml` files are only removed from the root of `dir`.
 /// It handles all errors internally
 pub fn remove_unneeded_files(
     dir: &Path,
     ignored_files: &Option<Vec<String>>,
+    template_config_path: Option<&Path>,
     verbose: bool,
 ) -> anyhow::Result<()> {
     let mut items = get_ignored(dir);
+    items.append(&mut get_ignored_by_cargo_generate_ignore(dir)?);
+    if let Some(template_config_path) = template_config_path.filter(|p| p.starts_with(dir)) {
+        items.push(template_config_path.to_path_buf());
+    }
     if let Some(ignored_files) = ignored_files {
         for f in ignored_files {
             let mut p = PathBuf::new();
@@ -48,9 +58,14 @@ fn check_if_genignore_exists(location: &Path) -> bool {
 }
 
 fn get_ignored(location: &Path) -> Vec<PathBuf> {
-    let default_ignored = [IGNORE_FILE_NAME, CONFIG_FILE_NAME, CARGO_OK_FILE_NAME]
-        .iter()
-        .map(|&file_name| location.join(file_name));
+    let default_ignored = [
+        IGNORE_FILE_NAME,
+        CARGO_GENERATE_IGNORE_FILE_NAME,
+        CONFIG_FILE_NAME,
+        CARGO_OK_FILE_NAME,
+    ]
+    .iter()
+    .map(|&file_name| location.join(file_name));
     if !check_if_genignore_exists(location) {
         return default_ignored.collect();
     }
@@ -71,6 +86,32 @@ fn get_ignored(location: &Path) -> Vec<PathBuf> {
         .collect()
 }
 
+/// Everything matched by the `.cargo-generate-ignore` file at `location`, with gitignore semantics
+fn get_ignored_by_cargo_generate_ignore(location: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let ignore_file = location.join(CARGO_GENERATE_IGNORE_FILE_NAME);
+    if !ignore_file.is_file() {
+        return Ok(Vec::new());
+    }
+
+    let mut builder = GitignoreBuilder::new(location);
+    if let Some(e) = builder.add(&ignore_file) {
+        return Err(e.into());
+    }
+    let gitignore = builder.build()?;
+
+    Ok(WalkBuilder::new(location)
+        .standard_filters(false)
+        .build()
+        .map(unwrap_path)
+        .filter(|path| path != location)
+        .filter(|path| {
+            gitignore
+                .matched_path_or_any_parents(path, path.is_dir())
+                .is_ignore()
+        })
+        .collect())
+}
+
 fn unwrap_path(it: Result<ignore::DirEntry, ignore::Error>) -> PathBuf {
     it.expect("Found invalid path: Aborting").into_path()
 }
@@ -85,18 +126,82 @@ pub fn remove_dir_files(files: impl IntoIterator<Item = impl Into<PathBuf>>, ver
         if item.is_dir() {
             remove_dir_all(&item).unwrap();
             if verbose {
-                println!("{}", ignore_message);
+                out!("{}", ignore_message);
             }
         } else if item.is_file() {
             remove_file(&item).unwrap();
             if verbose {
-                println!("{}", ignore_message);
+                out!("{}", ignore_message);
             }
         } else {
-            println!(
+            out!(
                 "The given paths are neither files nor directories! {}",
                 &item.display()
             );
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn it_removes_only_the_active_template_config() -> anyhow::Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let dir = tmp.path();
+        fs::create_dir_all(dir.join("template"))?;
+        fs::create_dir_all(dir.join("examples"))?;
+        fs::write(dir.join("template").join(CONFIG_FILE_NAME), "")?;
+        fs::write(dir.join("examples").join(CONFIG_FILE_NAME), "")?;
+
+        remove_unneeded_files(
+            dir,
+            &None,
+            Some(&dir.join("template").join(CONFIG_FILE_NAME)),
+            false,
+        )?;
+
+        assert!(!dir.join("template").join(CONFIG_FILE_NAME).exists());
+        assert!(dir.join("examples").join(CONFIG_FILE_NAME).exists());
+        Ok(())
+    }
+
+    #[test]
+    fn it_removes_files_matched_by_cargo_generate_ignore() -> anyhow::Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let dir = tmp.path();
+        fs::create_dir_all(dir.join("docs"))?;
+        fs::write(
+            dir.join(CARGO_GENERATE_IGNORE_FILE_NAME),
+            "*.trash\n!keep.trash\n/docs\n",
+        )?;
+        fs::write(dir.join("delete.trash"), "")?;
+        fs::write(dir.join("keep.trash"), "")?;
+        fs::write(dir.join("docs").join("index.md"), "")?;
+        fs::write(dir.join("main.rs"), "")?;
+
+        remove_unneeded_files(dir, &None, None, false)?;
+
+        assert!(!dir.join("delete.trash").exists());
+        assert!(dir.join("keep.trash").exists());
+        assert!(!dir.join("docs").exists());
+        assert!(dir.join("main.rs").exists());
+        assert!(!dir.join(CARGO_GENERATE_IGNORE_FILE_NAME).exists());
+        Ok(())
+    }
+
+    #[test]
+    fn it_keeps_files_outside_of_dir() -> anyhow::Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let dir = tmp.path().join("template");
+        fs::create_dir_all(&dir)?;
+        fs::write(tmp.path().join(CONFIG_FILE_NAME), "")?;
+
+        remove_unneeded_files(&dir, &None, Some(&tmp.path().join(CONFIG_FILE_NAME)), false)?;
+
+        assert!(tmp.path().join(CONFIG_FILE_NAME).exists());
+        Ok(())
+    }
+}
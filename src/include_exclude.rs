@@ -1,13 +1,16 @@
 use crate::{
     config::{TemplateConfig, CONFIG_FILE_NAME},
-    emoji,
+    emoji, out,
 };
 use anyhow::Result;
-use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::{
+    gitignore::{Gitignore, GitignoreBuilder},
+    Match,
+};
 use std::path::Path;
 
 #[derive(Default)]
-pub struct Matcher(Option<MatcherKind>, Vec<String>);
+pub struct Matcher(Option<MatcherKind>, Option<Gitignore>, Vec<String>);
 
 pub enum ShouldInclude {
     Include,
@@ -28,7 +31,7 @@ impl Matcher {
     ) -> Result<Self> {
         if template_config.include.is_some() && template_config.exclude.is_some() {
             template_config.exclude = None;
-            println!(
+            out!(
                 "{0} Your {1} contains both an include and exclude list. \
                     Only the include list will be considered. \
                     You should remove the exclude list for clarity. {0}",
@@ -46,20 +49,48 @@ impl Matcher {
                 template_config
             ),
         };
-        Ok(Self(kind, permanent_excluded.into()))
+        let raw = match &template_config.raw {
+            Some(it) => Some(Self::create_matcher(project_dir, it)?),
+            None => None,
+        };
+        Ok(Self(kind, raw, permanent_excluded.into()))
     }
 
     fn create_matcher(project_dir: &Path, patterns: &[String]) -> Result<Gitignore> {
         let mut builder = GitignoreBuilder::new(project_dir);
-        for rule in patterns {
-            builder.add_line(None, rule)?;
+        for rule in patterns.iter().flat_map(|p| expand_braces(p)) {
+            builder.add_line(None, &rule)?;
         }
         Ok(builder.build()?)
     }
 
+    /// The `include` or `exclude` pattern that matches `relative_path`, if any, to explain [`Self::should_include`]
+    pub fn matched_rule(&self, relative_path: &Path) -> Option<String> {
+        let matcher = match &self.0 {
+            Some(MatcherKind::Include(it) | MatcherKind::Exclude(it)) => it,
+            None => return None,
+        };
+        match matcher.matched_path_or_any_parents(relative_path, /* is_dir */ false) {
+            Match::Ignore(glob) | Match::Whitelist(glob) => Some(glob.original().to_string()),
+            Match::None => None,
+        }
+    }
+
+    /// The `raw` pattern that matches `relative_path`, if any, such files are copied without rendering
+    pub fn matched_raw_rule(&self, relative_path: &Path) -> Option<String> {
+        match self
+            .1
+            .as_ref()?
+            .matched_path_or_any_parents(relative_path, /* is_dir */ false)
+        {
+            Match::Ignore(glob) => Some(glob.original().to_string()),
+            Match::Whitelist(_) | Match::None => None,
+        }
+    }
+
     pub fn should_include(&self, relative_path: &Path) -> ShouldInclude {
         if self
-            .1
+            .2
             .iter()
             .any(|e| relative_path.to_str().map(|p| p == e).unwrap_or_default())
         {
@@ -86,3 +117,158 @@ impl Matcher {
         }
     }
 }
+
+/// Expands brace groups into one pattern per alternative, like `src/{lib,main}.rs` into `src/lib.rs`
+/// and `src/main.rs`. Groups may be nested, escaped braces and commas like `\{` are kept as literals.
+fn expand_braces(pattern: &str) -> Vec<String> {
+    match find_brace_group(pattern) {
+        Some((open, close, commas)) => {
+            let mut bounds = vec![open];
+            bounds.extend(commas);
+            bounds.push(close);
+            bounds
+                .windows(2)
+                .flat_map(|w| {
+                    let expanded = format!(
+                        "{}{}{}",
+                        &pattern[..open],
+                        &pattern[w[0] + 1..w[1]],
+                        &pattern[close + 1..]
+                    );
+                    expand_braces(&expanded)
+                })
+                .collect()
+        }
+        None => vec![pattern.to_string()],
+    }
+}
+
+/// Finds the first unescaped `{` with a matching `}` and at least one `,` on the same level,
+/// returns the byte positions of the braces and of those commas
+fn find_brace_group(pattern: &str) -> Option<(usize, usize, Vec<usize>)> {
+    let bytes = pattern.as_bytes();
+    let mut start = 0;
+    while let Some(open) = find_unescaped(bytes, start, b'{') {
+        let mut depth = 0;
+        let mut commas = vec![];
+        let mut i = open + 1;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'\\' => i += 1,
+                b'{' => depth += 1,
+                b'}' if depth > 0 => depth -= 1,
+                b'}' if !commas.is_empty() => return Some((open, i, commas)),
+                b'}' => break,
+                b',' if depth == 0 => commas.push(i),
+                _ => (),
+            }
+            i += 1;
+        }
+        start = open + 1;
+    }
+    None
+}
+
+fn find_unescaped(bytes: &[u8], start: usize, needle: u8) -> Option<usize> {
+    let mut i = start;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b if b == needle => return Some(i),
+            _ => (),
+        }
+        i += 1;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_expands_brace_groups() {
+        assert_eq!(
+            expand_braces("src/{lib,main}.rs"),
+            ["src/lib.rs", "src/main.rs"]
+        );
+        assert_eq!(expand_braces("**/*.{rs,toml}"), ["**/*.rs", "**/*.toml"]);
+        assert_eq!(
+            expand_braces("{src,tests}/*.{rs,toml}"),
+            ["src/*.rs", "src/*.toml", "tests/*.rs", "tests/*.toml"]
+        );
+        assert_eq!(
+            expand_braces("!docs/{a,b}.md"),
+            ["!docs/a.md", "!docs/b.md"]
+        );
+        assert_eq!(expand_braces("no-braces.rs"), ["no-braces.rs"]);
+    }
+
+    #[test]
+    fn it_expands_nested_brace_groups() {
+        assert_eq!(
+            expand_braces("src/{bin/{a,b},lib}.rs"),
+            ["src/bin/a.rs", "src/bin/b.rs", "src/lib.rs"]
+        );
+        assert_eq!(expand_braces("{a,{b,c}d}"), ["a", "bd", "cd"]);
+    }
+
+    #[test]
+    fn it_keeps_escaped_and_incomplete_braces() {
+        assert_eq!(expand_braces(r"weird\{a,b\}.txt"), [r"weird\{a,b\}.txt"]);
+        assert_eq!(expand_braces(r"{a\,b,c}"), [r"a\,b", "c"]);
+        assert_eq!(expand_braces("{single}.rs"), ["{single}.rs"]);
+        assert_eq!(expand_braces("open{a,b"), ["open{a,b"]);
+    }
+
+    #[test]
+    fn it_matches_expanded_patterns() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = TemplateConfig {
+            include: Some(vec![
+                "src/{lib,bin/{a,b}}.rs".into(),
+                r"weird\{name\}.txt".into(),
+            ]),
+            ..Default::default()
+        };
+        let matcher = Matcher::new(&mut config, dir.path(), &[]).unwrap();
+        let included = |path: &str| {
+            matches!(
+                matcher.should_include(Path::new(path)),
+                ShouldInclude::Include
+            )
+        };
+
+        assert!(included("src/lib.rs"));
+        assert!(included("src/bin/a.rs"));
+        assert!(included("src/bin/b.rs"));
+        assert!(!included("src/main.rs"));
+        assert!(included("weird{name}.txt"));
+        assert!(!included("weird.txt"));
+    }
+
+    #[test]
+    fn it_matches_raw_patterns_alongside_include() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = TemplateConfig {
+            include: Some(vec!["src/*.rs".into()]),
+            raw: Some(vec!["src/{macros,vendored}.rs".into()]),
+            ..Default::default()
+        };
+        let matcher = Matcher::new(&mut config, dir.path(), &[]).unwrap();
+
+        assert_eq!(
+            matcher.matched_raw_rule(Path::new("src/macros.rs")),
+            Some("src/macros.rs".into())
+        );
+        assert_eq!(
+            matcher.matched_raw_rule(Path::new("src/vendored.rs")),
+            Some("src/vendored.rs".into())
+        );
+        assert_eq!(matcher.matched_raw_rule(Path::new("src/lib.rs")), None);
+        assert!(matches!(
+            matcher.should_include(Path::new("src/macros.rs")),
+            ShouldInclude::Include
+        ));
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
-    emoji,
-    project_variables::{StringEntry, TemplateSlots, VarInfo},
+    emoji, out,
+    project_variables::{ConversionError, StringEntry, TemplateSlots, VarInfo},
 };
 use anyhow::Result;
 use console::style;
@@ -8,26 +8,38 @@ use dialoguer::theme::ColorfulTheme;
 use dialoguer::Input;
 use liquid_core::Value;
 use std::ops::Index;
+use std::path::Path;
 
 pub fn name() -> Result<String> {
     let valid_ident = regex::Regex::new(r"^([a-zA-Z][a-zA-Z0-9_-]+)$")?;
     let project_var = TemplateSlots {
         var_name: "crate_name".into(),
         prompt: "Project Name".into(),
+        prompt_if: None,
+        group: None,
         var_info: VarInfo::String {
             entry: Box::new(StringEntry {
                 default: None,
                 choices: None,
                 regex: Some(valid_ident),
+                regex_hint: None,
+                choices_command: None,
+                editor: false,
+                transform: None,
             }),
         },
     };
     prompt_for_variable(&project_var)
 }
 
+/// Asks for a line of input, where an empty input takes the `default` if there is one.
+///
+/// The default is not shown by dialoguer, as the prompts show it as `[default: ...]` already.
 pub fn user_question(prompt: &str, default: &Option<String>) -> Result<String> {
     let mut i = Input::<String>::new();
-    i.with_prompt(prompt.to_string());
+    i.with_prompt(prompt.to_string())
+        .allow_empty(true)
+        .show_default(false);
     if let Some(s) = default {
         i.default(s.to_owned());
     }
@@ -35,7 +47,7 @@ pub fn user_question(prompt: &str, default: &Option<String>) -> Result<String> {
 }
 
 pub fn prompt_for_variable(variable: &TemplateSlots) -> Result<String> {
-    use dialoguer::Select;
+    use dialoguer::{Editor, MultiSelect, Select};
 
     let prompt = format!("{} {}", emoji::SHRUG, style(&variable.prompt).bold(),);
 
@@ -50,6 +62,33 @@ pub fn prompt_for_variable(variable: &TemplateSlots) -> Result<String> {
 
             Ok(choices.index(chosen).to_string())
         }
+        VarInfo::String { entry } if entry.editor => {
+            eprintln!("{} {}", prompt, style("[opening your editor]").dim());
+            let mut content = entry.default.clone().unwrap_or_default();
+            loop {
+                let edited = Editor::new().edit(&content)?.unwrap_or_default();
+                // saving without content, or quitting without saving, takes the default
+                let edited = if edited.trim().is_empty() {
+                    String::new()
+                } else {
+                    edited.trim_end_matches(['\r', '\n']).to_string()
+                };
+                match resolve_string_input(transform(&variable.var_name, entry, edited)?, entry) {
+                    Ok(value) => break Ok(value),
+                    Err(rejected) => {
+                        eprintln!(
+                            "{} {} {}",
+                            emoji::WARN,
+                            style("Sorry, the content").bold().red(),
+                            style(regex_mismatch_reason(&variable.var_name, entry))
+                                .bold()
+                                .red()
+                        );
+                        content = rejected;
+                    }
+                }
+            }
+        }
         VarInfo::String { entry } => match &entry.choices {
             Some(choices) => {
                 let default = entry
@@ -75,42 +114,370 @@ pub fn prompt_for_variable(variable: &TemplateSlots) -> Result<String> {
                 );
                 let default = entry.default.as_ref().map(|v| v.into());
 
-                match &entry.regex {
-                    Some(regex) => loop {
-                        let user_entry = user_question(prompt.as_str(), &default)?;
-                        if regex.is_match(&user_entry) {
-                            break Ok(user_entry);
-                        }
-                        eprintln!(
+                loop {
+                    let user_entry = user_question(prompt.as_str(), &default)?;
+                    let user_entry = transform(&variable.var_name, entry, user_entry)?;
+                    match resolve_string_input(user_entry, entry) {
+                        Ok(value) => break Ok(value),
+                        Err(user_entry) => eprintln!(
                             "{} {} \"{}\" {}",
                             emoji::WARN,
                             style("Sorry,").bold().red(),
                             style(&user_entry).bold().yellow(),
-                            style(format!("is not a valid value for {}", variable.var_name))
+                            style(regex_mismatch_reason(&variable.var_name, entry))
                                 .bold()
                                 .red()
-                        );
-                    },
-                    None => Ok(user_question(prompt.as_str(), &default)?),
+                        ),
+                    }
                 }
             }
         },
+        VarInfo::MultiSelect { entry } => {
+            let defaults = entry
+                .choices
+                .iter()
+                .map(|choice| {
+                    entry
+                        .default
+                        .as_ref()
+                        .is_some_and(|default| default.contains(choice))
+                })
+                .collect::<Vec<_>>();
+            let chosen = MultiSelect::with_theme(&ColorfulTheme::default())
+                .items(&entry.choices)
+                .with_prompt(&prompt)
+                .defaults(&defaults)
+                .interact()?;
+
+            Ok(chosen
+                .into_iter()
+                .map(|i| entry.choices[i].as_str())
+                .collect::<Vec<_>>()
+                .join(","))
+        }
+        VarInfo::Number { entry } => {
+            let prompt = format!(
+                "{} {}",
+                prompt,
+                entry
+                    .default
+                    .map_or_else(String::new, |d| format!("[default: {}]", style(d).bold()))
+            );
+            let default = entry.default.map(|v| v.to_string());
+
+            loop {
+                let user_entry = user_question(prompt.as_str(), &default)?;
+                match entry.parse(&variable.var_name, &user_entry) {
+                    Ok(_) => break Ok(user_entry),
+                    Err(e) => eprintln!(
+                        "{} {} {}",
+                        emoji::WARN,
+                        style("Sorry,").bold().red(),
+                        style(e).bold().red()
+                    ),
+                }
+            }
+        }
     }
 }
 
-pub(super) fn variable(variable: &TemplateSlots, provided_value: Option<&str>) -> Result<Value> {
-    let user_input = provided_value
-        .map(|v| Ok(v.to_string()))
-        .unwrap_or_else(|| prompt_for_variable(variable))?;
-    into_value(user_input, &variable.var_info)
+/// Fuzzy searchable selection among the favorites, returns the index of the chosen one
+pub fn choose_favorite(items: &[String]) -> Result<usize> {
+    dialoguer::FuzzySelect::with_theme(&ColorfulTheme::default())
+        .items(items)
+        .with_prompt(format!(
+            "{} {}",
+            emoji::SHRUG,
+            style("Which favorite should be generated?").bold()
+        ))
+        .default(0)
+        .interact()
+        .map_err(Into::into)
+}
+
+/// Selection among the branches of a template, the `default` one is preselected and marked
+pub fn choose_branch(branches: &[String], default: Option<&str>) -> Result<String> {
+    let items = branches
+        .iter()
+        .map(|branch| match default {
+            Some(default) if default == branch => format!("{} (default)", branch),
+            _ => branch.clone(),
+        })
+        .collect::<Vec<_>>();
+    let chosen = dialoguer::Select::with_theme(&ColorfulTheme::default())
+        .items(&items)
+        .with_prompt(format!(
+            "{} {}",
+            emoji::SHRUG,
+            style("Which branch should be generated from?").bold()
+        ))
+        .default(
+            default
+                .and_then(|default| branches.iter().position(|branch| branch == default))
+                .unwrap_or(0),
+        )
+        .interact()?;
+
+    Ok(branches[chosen].clone())
+}
+
+/// How to handle a file of the template that already exists in the destination
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Collision {
+    Overwrite,
+    Skip,
+    Abort,
 }
 
-fn into_value(user_entry: String, var_info: &VarInfo) -> Result<Value> {
+pub fn collision(path: &Path) -> Result<Collision> {
+    let choices = ["overwrite", "skip", "abort"];
+    let chosen = dialoguer::Select::with_theme(&ColorfulTheme::default())
+        .items(&choices)
+        .with_prompt(format!(
+            "{} {} {}",
+            emoji::SHRUG,
+            style(path.display()).bold().yellow(),
+            style("already exists, what should be done?").bold()
+        ))
+        .default(2)
+        .interact()?;
+
+    Ok(match chosen {
+        0 => Collision::Overwrite,
+        1 => Collision::Skip,
+        _ => Collision::Abort,
+    })
+}
+
+/// Applies the `transform` of the placeholder to a non-empty input, an empty one takes the default as it is.
+fn transform(var_name: &str, entry: &StringEntry, user_entry: String) -> Result<String> {
+    if user_entry.is_empty() {
+        return Ok(user_entry);
+    }
+    Ok(entry.transform(var_name, user_entry)?)
+}
+
+/// An empty input means the default if there is one, otherwise a truly empty string.
+/// Either way the value has to match the regex, the rejected input is returned as error.
+fn resolve_string_input(user_entry: String, entry: &StringEntry) -> Result<String, String> {
+    let value = match &entry.default {
+        Some(default) if user_entry.is_empty() => default.clone(),
+        _ => user_entry,
+    };
+    match &entry.regex {
+        Some(regex) if !regex.is_match(&value) => Err(value),
+        _ => Ok(value),
+    }
+}
+
+/// Explains why a value entered for `var_name` has been rejected by the regex, with the
+/// `regex_hint` of the template if it has one
+fn regex_mismatch_reason(var_name: &str, entry: &StringEntry) -> String {
+    entry.regex_hint.as_ref().map_or_else(
+        || format!("is not a valid value for {}", var_name),
+        |hint| format!("is not a valid value for {}: {}", var_name, hint),
+    )
+}
+
+/// Takes the provided value if it's valid, otherwise prompts the user for it.
+///
+/// In `silent` mode an invalid provided value is an error, as there is no one to ask.
+///
+/// `prompted_group` is the group of the previous prompt, its header is only printed once for the placeholders of a group.
+pub(super) fn variable(
+    variable: &TemplateSlots,
+    provided_value: Option<&str>,
+    silent: bool,
+    prompted_group: &mut Option<String>,
+) -> Result<Value> {
+    if let Some(provided_value) = provided_value {
+        let provided_value = match &variable.var_info {
+            VarInfo::String { entry } => {
+                transform(&variable.var_name, entry, provided_value.to_string())?
+            }
+            _ => provided_value.to_string(),
+        };
+        match into_value(provided_value, &variable.var_name, &variable.var_info) {
+            Ok(value) => return Ok(value),
+            Err(e) if silent => return Err(e),
+            Err(e) => eprintln!(
+                "{} {} {}",
+                emoji::WARN,
+                style("Provided value is invalid, please enter a new one:")
+                    .bold()
+                    .red(),
+                style(e).bold().red()
+            ),
+        }
+    }
+
+    announce_group(variable.group.as_deref(), prompted_group);
+    let user_input = prompt_for_variable(variable)?;
+    into_value(user_input, &variable.var_name, &variable.var_info)
+}
+
+/// Prints the header of a group before the first of its placeholders is prompted,
+/// nothing is printed for the ones that are provided
+fn announce_group(group: Option<&str>, prompted_group: &mut Option<String>) {
+    if prompted_group.as_deref() != group {
+        if let Some(group) = group {
+            out!("{}", style(format!("--- {} ---", group)).bold().cyan());
+        }
+        *prompted_group = group.map(String::from);
+    }
+}
+
+fn into_value(user_entry: String, var_name: &str, var_info: &VarInfo) -> Result<Value> {
     match var_info {
         VarInfo::Bool { .. } => {
             let as_bool = user_entry.parse::<bool>()?; // this shouldn't fail if checked before
             Ok(Value::Scalar(as_bool.into()))
         }
-        VarInfo::String { .. } => Ok(Value::Scalar(user_entry.into())),
+        VarInfo::String { entry } => {
+            if let Some(choices) = &entry.choices {
+                if !choices.contains(&user_entry) {
+                    anyhow::bail!(ConversionError::InvalidChoice {
+                        var_name: var_name.into(),
+                        value: user_entry,
+                        choices: choices.clone(),
+                    });
+                }
+            }
+            entry.check_regex(var_name, &user_entry)?;
+            Ok(Value::Scalar(user_entry.into()))
+        }
+        VarInfo::Number { entry } => {
+            // provided values are not checked before, so the range is validated here again
+            let number = entry.parse(var_name, &user_entry)?;
+            Ok(number.into_value())
+        }
+        VarInfo::MultiSelect { entry } => {
+            let values = entry.parse(var_name, &user_entry)?;
+            Ok(Value::Array(
+                values.into_iter().map(Value::scalar).collect(),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry_with_hint(regex_hint: Option<&str>) -> StringEntry {
+        StringEntry {
+            default: None,
+            choices: None,
+            regex: Some(regex::Regex::new("^[a-z-]+$").unwrap()),
+            regex_hint: regex_hint.map(Into::into),
+            choices_command: None,
+            editor: false,
+            transform: None,
+        }
+    }
+
+    #[test]
+    fn enter_on_empty_input_takes_the_default() {
+        let entry = StringEntry {
+            default: Some("my-default".into()),
+            ..entry_with_hint(None)
+        };
+        assert_eq!(
+            resolve_string_input("".into(), &entry),
+            Ok("my-default".into())
+        );
+        assert_eq!(
+            resolve_string_input("other".into(), &entry),
+            Ok("other".into())
+        );
+    }
+
+    #[test]
+    fn enter_on_empty_input_without_default_is_an_empty_string_if_the_regex_permits_it() {
+        let entry = StringEntry {
+            regex: None,
+            ..entry_with_hint(None)
+        };
+        assert_eq!(resolve_string_input("".into(), &entry), Ok("".into()));
+
+        let entry = entry_with_hint(None);
+        assert_eq!(resolve_string_input("".into(), &entry), Err("".into()));
+
+        let entry = StringEntry {
+            regex: Some(regex::Regex::new("^[a-z-]*$").unwrap()),
+            ..entry_with_hint(None)
+        };
+        assert_eq!(resolve_string_input("".into(), &entry), Ok("".into()));
+    }
+
+    #[test]
+    fn the_reprompt_explains_the_regex_with_its_hint() {
+        let entry = entry_with_hint(Some("lowercase and hyphens only"));
+        assert_eq!(
+            regex_mismatch_reason("crate", &entry),
+            "is not a valid value for crate: lowercase and hyphens only"
+        );
+
+        let entry = entry_with_hint(None);
+        assert_eq!(
+            regex_mismatch_reason("crate", &entry),
+            "is not a valid value for crate"
+        );
+    }
+
+    #[test]
+    fn an_invalid_provided_value_fails_with_the_hint_in_silent_mode() {
+        let slot = TemplateSlots {
+            var_name: "crate".into(),
+            prompt: "Crate?".into(),
+            prompt_if: None,
+            group: None,
+            var_info: VarInfo::String {
+                entry: Box::new(entry_with_hint(Some("lowercase and hyphens only"))),
+            },
+        };
+
+        let error = variable(&slot, Some("Not Valid"), true, &mut None).unwrap_err();
+        assert_eq!(
+            error.downcast::<ConversionError>().unwrap(),
+            ConversionError::RegexDoesntMatchHint {
+                var_name: "crate".into(),
+                value: "Not Valid".into(),
+                hint: "lowercase and hyphens only".into(),
+            }
+        );
+        assert!(variable(&slot, Some("valid-name"), true, &mut None).is_ok());
+    }
+
+    #[test]
+    fn a_provided_value_is_validated_after_its_transform() {
+        let slot = TemplateSlots {
+            var_name: "crate".into(),
+            prompt: "Crate?".into(),
+            prompt_if: None,
+            group: None,
+            var_info: VarInfo::String {
+                entry: Box::new(StringEntry {
+                    transform: crate::template_filters::case_conversion("kebab")
+                        .map(crate::project_variables::Transform::Case),
+                    ..entry_with_hint(None)
+                }),
+            },
+        };
+
+        assert_eq!(
+            variable(&slot, Some("Not Valid"), true, &mut None).unwrap(),
+            Value::scalar("not-valid")
+        );
+    }
+
+    #[test]
+    fn a_group_is_announced_once_for_its_prompts() {
+        let mut prompted_group = None;
+
+        announce_group(Some("Database"), &mut prompted_group);
+        assert_eq!(prompted_group.as_deref(), Some("Database"));
+        announce_group(None, &mut prompted_group);
+        assert_eq!(prompted_group, None);
     }
 }
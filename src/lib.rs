@@ -23,62 +23,174 @@
 )]
 
 mod app_config;
+mod archive;
 mod args;
+mod check;
 mod config;
 mod emoji;
+mod error;
+mod extends;
 mod favorites;
 mod filenames;
+mod fmt;
 mod git;
+mod github_release;
+mod hg;
 mod hooks;
 mod ignore_me;
 mod include_exclude;
 mod interactive;
+mod list_variables;
 mod log;
 mod progressbar;
 mod project_variables;
+mod requirements;
 mod template;
 mod template_filters;
 mod template_variables;
 mod user_parsed_input;
 
 pub use args::*;
+pub use error::{ErrorKind, GenerateError};
 
 use anyhow::{anyhow, bail, Context, Result};
-use config::{locate_template_configs, Config, CONFIG_FILE_NAME};
-use console::style;
-use favorites::list_favorites;
+use config::{
+    locate_template_configs, Config, TemplateConfig, CONFIG_FILE_NAME, DEFAULT_RENDER_EXTENSION,
+};
+use console::{style, Term};
+use error::Categorize;
+use favorites::{choose_favorite, list_favorites};
 use git::DEFAULT_BRANCH;
-use hooks::{execute_post_hooks, execute_pre_hooks};
+use hooks::{execute_post_hooks, execute_pre_hooks, verify_checksums};
+use ignore::WalkBuilder;
 use ignore_me::remove_dir_files;
-use interactive::prompt_for_variable;
+use interactive::{prompt_for_variable, Collision};
 use liquid::ValueView;
+use list_variables::list_variables;
+use progressbar::ProgressMode;
 use project_variables::{StringEntry, TemplateSlots, VarInfo};
-use std::ffi::OsString;
+use serde::Serialize;
+use std::ffi::OsStr;
 use std::{
     borrow::Borrow,
     cell::RefCell,
     collections::HashMap,
-    env, fs,
-    path::{Path, PathBuf},
+    env, fs, io,
+    path::{Component, Path, PathBuf},
     rc::Rc,
 };
 use user_parsed_input::{TemplateLocation, UserParsedInput};
 
 use tempfile::TempDir;
+use walkdir::WalkDir;
 
-use crate::template_variables::load_env_and_args_template_values;
+use crate::template::TemplateRef;
+use crate::template_variables::{load_env_and_args_template_values, raw_definitions};
 use crate::{
     app_config::{app_config_path, AppConfig},
     project_variables::ConversionError,
-    template_variables::{CrateType, ProjectName},
+    template_variables::{get_now, CrateType, Now, ProjectName},
 };
 
+/// The outcome of a successful [`generate_with_result`] call.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GenerateResult {
+    /// The directory the project has been generated into
+    pub project_dir: PathBuf,
+    /// The resolved project name, as used for the `project-name` placeholder
+    pub project_name: String,
+    /// The `name` of the template, from its `cargo-generate.toml`
+    pub template_name: Option<String>,
+    /// The `description` of the template, or of the favorite it has been chosen by
+    pub template_description: Option<String>,
+    /// All resolved placeholders, including the builtin ones
+    pub variables: liquid::Object,
+    /// All files written into `project_dir`, or that would be written on a dry run
+    pub files: Vec<PathBuf>,
+    /// If a fresh git repository has been initialized in `project_dir`
+    pub git_initialized: bool,
+}
+
+/// # Panics
+pub fn generate(args: GenerateArgs) -> Result<()> {
+    let print_json = args.output_format == OutputFormat::Json
+        && !args.list_favorites
+        && !args.list_variables
+        && !args.print_template_dir
+        && !args.to_stdout_tar;
+    let result = generate_with_result(args)?;
+    if print_json {
+        println!("{}", serde_json::to_string(&result)?);
+    }
+
+    Ok(())
+}
+
+/// Fetches the template of `args.template_path` like [`generate`] does, and validates it without prompting
+/// or generating anything, see `cargo generate check`.
+pub fn check(args: GenerateArgs) -> Result<()> {
+    let mut app_config: AppConfig = app_config_path(&args.config)?.as_path().try_into()?;
+    if let Some(ref profile) = args.profile {
+        app_config.apply_profile(profile)?;
+    }
+    let source_template = UserParsedInput::try_from_args_and_config(&app_config, &args)?;
+    let cache_dir = resolve_template_cache_dir(&app_config, &args)?;
+    let (template_base_dir, _, _) = prepare_local_template(
+        &source_template,
+        args.offline,
+        args.clone_retries,
+        cache_dir.as_deref(),
+        args.respect_gitignore,
+        args.recurse_submodules,
+        false,
+    )?;
+    // all templates within the subfolder are checked, so there is nothing to choose from
+    let template_dir = template_base_dir
+        .path()
+        .join(source_template.subfolder().unwrap_or_default());
+    if !template_dir.is_dir() {
+        bail!(
+            "{} {} {}",
+            emoji::ERROR,
+            style("Subfolder Error:").bold().red(),
+            style("The specified subfolder must be a valid folder.")
+                .bold()
+                .red(),
+        );
+    }
+
+    check::check_template(&template_dir)
+}
+
+/// Same as [`generate`], but returns what has been generated, for tools that embed cargo-generate.
+///
+/// Set `args.silent` to suppress the progress output.
+///
 /// # Panics
-pub fn generate(mut args: GenerateArgs) -> Result<()> {
-    let app_config: AppConfig = app_config_path(&args.config)?.as_path().try_into()?;
+pub fn generate_with_result(mut args: GenerateArgs) -> Result<GenerateResult> {
+    // with `--to-stdout-tar`, stdout belongs to the archive
+    log::set_diagnostics_to_stderr(!args.output_format.is_human() || args.to_stdout_tar);
+    log::set_quiet(args.quiet);
+    log::set_verbose(args.verbose);
+    log::set_log_format(args.log_format);
+    let mut app_config: AppConfig = app_config_path(&args.config)?.as_path().try_into()?;
+    if let Some(ref profile) = args.profile {
+        app_config.apply_profile(profile)?;
+    }
+    args.vcs = args.vcs.or_else(|| {
+        app_config
+            .defaults
+            .as_ref()
+            .and_then(|defaults| defaults.vcs)
+    });
 
     if args.list_favorites {
-        return list_favorites(&app_config, &args);
+        list_favorites(&app_config, &args)?;
+        return Ok(GenerateResult::default());
+    }
+
+    if args.template_path.is_empty() {
+        args.template_path.favorite = Some(choose_favorite(&app_config, &args)?);
     }
 
     if args.ssh_identity.is_none()
@@ -94,118 +206,747 @@ pub fn generate(mut args: GenerateArgs) -> Result<()> {
             .cloned();
     }
 
-    let mut source_template = UserParsedInput::try_from_args_and_config(&app_config, &args);
+    let mut source_template = UserParsedInput::try_from_args_and_config(&app_config, &args)?;
     source_template
         .template_values_mut()
         .extend(load_env_and_args_template_values(&args)?);
+    if !args.silent && !args.offline && !args.list_variables && !args.print_template_dir {
+        choose_git_branch(&mut source_template, args.clone_retries)?;
+    }
+
+    let cache_dir = resolve_template_cache_dir(&app_config, &args)?;
+    let (template_base_dir, branch, reference) = prepare_local_template(
+        &source_template,
+        args.offline,
+        args.clone_retries,
+        cache_dir.as_deref(),
+        args.respect_gitignore,
+        args.recurse_submodules,
+        args.keep_history,
+    )?;
+    if let Some(project_name) = positional_project_name(&args, template_base_dir.path()) {
+        args.template_path.take_positional_subfolder();
+        args.name = Some(project_name);
+        source_template.set_subfolder(args.template_path.subfolder());
+    }
+    let template_folders = resolve_template_folders(
+        &template_base_dir,
+        &source_template,
+        &args.template_path.subfolders(),
+        args.select_template.as_deref(),
+    )?;
+    let history = args
+        .keep_history
+        .then(|| check_keep_history(&args, &template_base_dir, &template_folders))
+        .transpose()?;
+
+    if args.print_template_dir {
+        print_template_dirs(
+            source_template.location(),
+            &template_base_dir,
+            &template_folders,
+            &args,
+        )?;
+        return Ok(GenerateResult::default());
+    }
 
-    let (template_base_dir, template_folder, branch) = prepare_local_template(&source_template)?;
+    let mut templates = Vec::with_capacity(template_folders.len());
+    for (template_folder, sub_dir) in template_folders {
+        let template_config_path =
+            locate_template_file(CONFIG_FILE_NAME, &template_base_dir, &template_folder).ok();
+        let template_config = Config::from_path(&template_config_path)?.unwrap_or_default();
+        let extends_base = extends::extends_of(&template_config).is_some();
+        let template_config = extends::extend_template(
+            source_template.location(),
+            template_base_dir.path(),
+            &template_folder,
+            template_config_path.as_deref(),
+            template_config,
+            &extends::FetchOptions {
+                offline: args.offline,
+                clone_retries: args.clone_retries,
+                cache_dir: cache_dir.as_deref(),
+                identity: args.ssh_identity.as_deref(),
+                respect_gitignore: args.respect_gitignore,
+                recurse_submodules: args.recurse_submodules,
+            },
+        )?;
+        if extends_base && args.verify_checksums.is_some() {
+            extends::refuse_unverifiable_commands(&template_config)?;
+        }
 
-    let template_config = Config::from_path(
-        &locate_template_file(CONFIG_FILE_NAME, &template_base_dir, &template_folder).ok(),
-    )?
-    .unwrap_or_default();
+        check_cargo_generate_version(&template_config)?;
+        if !args.ignore_required_commands {
+            requirements::check_required_commands(&template_config)?;
+        }
+        if !args.ignore_rust_version {
+            requirements::check_min_rust_version(&template_config)?;
+        }
+        templates.push((
+            template_folder,
+            sub_dir,
+            template_config_path,
+            template_config,
+        ));
+    }
 
-    check_cargo_generate_version(&template_config)?;
+    if args.list_variables {
+        for (_, _, _, template_config) in &templates {
+            list_variables(
+                template_config,
+                source_template.favorite_description(),
+                &args,
+            )?;
+        }
+        return Ok(GenerateResult::default());
+    }
 
     let base_dir = env::current_dir()?;
     let project_name = resolve_project_name(&args)?;
     let project_dir = resolve_project_dir(&base_dir, &project_name, &args)?;
+    let resolved_name = if args.force {
+        project_name.raw()
+    } else {
+        project_name.kebab_case()
+    };
 
-    println!(
-        "{} {} {}",
-        emoji::WRENCH,
-        style(format!("Basedir: {}", base_dir.display())).bold(),
-        style("...").bold()
-    );
+    let template_name = templates
+        .iter()
+        .find_map(|(_, _, _, config)| config.template.as_ref()?.name.clone());
+    let template_description = templates
+        .iter()
+        .find_map(|(_, _, _, config)| config.template.as_ref()?.description.clone())
+        .or_else(|| source_template.favorite_description().map(String::from));
+
+    let show_progress = !args.silent && !args.quiet && args.output_format.is_human();
+    if show_progress {
+        out!(
+            "{} {} {}",
+            emoji::WRENCH,
+            style(format!("Basedir: {}", base_dir.display())).bold(),
+            style("...").bold()
+        );
 
-    println!(
-        "{} {} {}",
-        emoji::WRENCH,
-        style("Generating template").bold(),
-        style("...").bold()
-    );
+        let generating = template_name.as_ref().map_or_else(
+            || "Generating template".to_string(),
+            |name| format!("Generating '{}'", name),
+        );
+        out!(
+            "{} {} {}",
+            emoji::WRENCH,
+            style(generating).bold(),
+            style("...").bold()
+        );
+        if let Some(ref description) = template_description {
+            out!("   {}", style(description).dim());
+        }
+    }
 
-    expand_template(
-        &project_dir,
-        &project_name,
-        &template_folder,
-        source_template.template_values(),
-        template_config,
-        &args,
-    )?;
+    // all subfolders see the same `now`, the start of the generation
+    let now = get_now();
+    // the placeholders resolved for one subfolder are reused for the next ones, so they are prompted only once
+    let mut template_values = source_template.template_values().clone();
+    let mut outputs = Vec::with_capacity(templates.len());
+    let mut variables: Option<liquid::Object> = None;
+    for (template_folder, sub_dir, template_config_path, template_config) in templates {
+        let output_dir =
+            sub_dir.map_or_else(|| project_dir.clone(), |sub_dir| project_dir.join(sub_dir));
+        let render_extension = template_config
+            .template
+            .as_ref()
+            .map_or(DEFAULT_RENDER_EXTENSION, TemplateConfig::render_extension)
+            .to_string();
+        let placeholder_names: Vec<String> = template_config
+            .placeholders
+            .as_ref()
+            .map(|placeholders| placeholders.0.keys().cloned().collect())
+            .unwrap_or_default();
+
+        let expanded = expand_template(
+            &output_dir,
+            &project_name,
+            &template_folder,
+            &template_values,
+            template_config,
+            template_config_path.as_deref(),
+            TemplateRef::new(source_template.location(), &branch),
+            &now,
+            &args,
+        )?;
+
+        share_resolved_values(&expanded, &placeholder_names, &mut template_values);
+        variables = Some(match variables {
+            Some(mut variables) => {
+                for (key, value) in expanded {
+                    variables.entry(key).or_insert(value);
+                }
+                variables
+            }
+            None => expanded,
+        });
+        outputs.push((template_folder, output_dir, render_extension));
+    }
+    let variables = variables.unwrap_or_default();
+
+    if args.update {
+        for (template_folder, output_dir, render_extension) in &outputs {
+            print_changes(&compare_with_project(
+                template_folder,
+                output_dir,
+                render_extension,
+            )?);
+        }
+    }
 
-    println!(
-        "{} {} `{}`{}",
-        emoji::WRENCH,
-        style("Moving generated files into:").bold(),
-        style(project_dir.display()).bold().yellow(),
-        style("...").bold()
-    );
-    copy_dir_all(&template_folder, &project_dir)?;
+    if args.dry_run {
+        let mut files = vec![];
+        for (template_folder, output_dir, render_extension) in &outputs {
+            out!(
+                "{} {} `{}`{}",
+                emoji::WRENCH,
+                style("Dry run, files that would be generated into:").bold(),
+                style(output_dir.display()).bold().yellow(),
+                style("...").bold()
+            );
+            files.extend(copy_dir_all(
+                template_folder,
+                output_dir,
+                args.overwrite,
+                true,
+                args.update,
+                Symlinks::from_args(&args),
+                render_extension,
+            )?);
+        }
 
-    if !args.vcs.is_none() && (!args.init || args.force_git_init) {
-        info!("{}", style("Initializing a fresh Git repository").bold());
-        args.vcs
-            .initialize(&project_dir, branch, args.force_git_init)?;
+        out!(
+            "{} {} {}",
+            emoji::SPARKLE,
+            style("Done!").bold().green(),
+            style("Dry run finished, nothing has been written").bold(),
+        );
+        return Ok(GenerateResult {
+            project_dir,
+            project_name: resolved_name,
+            template_name,
+            template_description,
+            variables,
+            files,
+            git_initialized: false,
+        });
     }
 
-    println!(
-        "{} {} {} {}",
-        emoji::SPARKLE,
-        style("Done!").bold().green(),
-        style("New project created").bold(),
-        style(&project_dir.display()).underlined()
-    );
+    if args.to_stdout_tar {
+        let root = project_dir.parent().unwrap_or(&project_dir);
+        let files = archive::write_tar(
+            &outputs,
+            root,
+            Symlinks::from_args(&args),
+            io::stdout().lock(),
+        )?;
+        return Ok(GenerateResult {
+            project_dir,
+            project_name: resolved_name,
+            template_name,
+            template_description,
+            variables,
+            files,
+            git_initialized: false,
+        });
+    }
+
+    if show_progress {
+        out!(
+            "{} {} `{}`{}",
+            emoji::WRENCH,
+            style("Moving generated files into:").bold(),
+            style(project_dir.display()).bold().yellow(),
+            style("...").bold()
+        );
+    }
+    let project_dir_existed = project_dir.exists();
+    let (files, git_initialized) =
+        write_project_dir(&outputs, &project_dir, reference, history.as_deref(), &args)
+            .inspect_err(|_| {
+                cleanup_failed_project_dir(&project_dir, project_dir_existed, &args)
+            })?;
+    if args.fmt {
+        fmt::format_project(&project_dir);
+    }
+
+    if args.output_format.is_human() && !args.quiet {
+        out!(
+            "{} {} {} {}",
+            emoji::SPARKLE,
+            style("Done!").bold().green(),
+            style(if args.update {
+                "Project updated"
+            } else {
+                "New project created"
+            })
+            .bold(),
+            style(&project_dir.display()).underlined()
+        );
+    }
+    Ok(GenerateResult {
+        project_dir,
+        project_name: resolved_name,
+        template_name,
+        template_description,
+        variables,
+        files,
+        git_initialized,
+    })
+}
+
+/// Lets the user choose the branch to generate from, if the git template is not pinned to any branch, tag or revision
+/// and its repository has more than one branch. Without a terminal to ask, or if the branches can't be listed,
+/// the default branch is cloned as usual.
+fn choose_git_branch(source_template: &mut UserParsedInput, clone_retries: u32) -> Result<()> {
+    let git = match source_template.location_mut() {
+        TemplateLocation::Git(git)
+            if git.branch().is_none() && git.tag().is_none() && git.revision().is_none() =>
+        {
+            git
+        }
+        _ => return Ok(()),
+    };
+    if !Term::stderr().is_term() {
+        return Ok(());
+    }
+
+    let branches = match git::list_remote_branches(git.url(), git.identity(), clone_retries) {
+        Ok(branches) => branches,
+        Err(e) => {
+            warn!(
+                "{} {}",
+                style("Listing the branches failed, falling back to the default branch:").bold(),
+                e
+            );
+            return Ok(());
+        }
+    };
+    if branches.names.len() < 2 {
+        return Ok(());
+    }
+    let branch = interactive::choose_branch(&branches.names, branches.default.as_deref())?;
+    // the default branch is cloned anyway, and keeps using the same cache entry
+    if branches.default.as_ref() != Some(&branch) {
+        git.set_branch(branch);
+    }
+    Ok(())
+}
+
+/// Prints the resolved template folders and their `cargo-generate.toml`, for `--print-template-dir`.
+///
+/// For local templates the paths point into the original template, otherwise they are relative
+/// to the root of the fetched template, as its temp dir is gone once cargo-generate exits.
+fn print_template_dirs(
+    location: &TemplateLocation,
+    template_base_dir: &TempDir,
+    template_folders: &[(PathBuf, Option<String>)],
+    args: &GenerateArgs,
+) -> Result<()> {
+    let template_root = fs::canonicalize(template_base_dir.path())?;
+    let source_root = match location {
+        TemplateLocation::Path(path) => Some(fs::canonicalize(path)?),
+        _ => None,
+    };
+    let display_path = |path: &Path| -> Result<PathBuf> {
+        let relative = fs::canonicalize(path)?
+            .strip_prefix(&template_root)?
+            .to_path_buf();
+        Ok(match source_root {
+            Some(ref source_root) => source_root.join(relative),
+            None if relative.as_os_str().is_empty() => PathBuf::from("."),
+            None => relative,
+        })
+    };
+
+    let mut template_dirs = Vec::with_capacity(template_folders.len());
+    for (template_folder, _) in template_folders {
+        let config_path =
+            locate_template_file(CONFIG_FILE_NAME, template_base_dir, template_folder).ok();
+        template_dirs.push((
+            display_path(template_folder.as_path())?,
+            config_path.as_deref().map(&display_path).transpose()?,
+        ));
+    }
+
+    if args.output_format == OutputFormat::Json {
+        let template_dirs: Vec<_> = template_dirs
+            .iter()
+            .map(|(template_dir, config_path)| {
+                serde_json::json!({
+                    "template_dir": template_dir,
+                    "config_path": config_path,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string(&template_dirs)?);
+        return Ok(());
+    }
+
+    for (template_dir, config_path) in &template_dirs {
+        println!(
+            "{} {} {}",
+            emoji::WRENCH,
+            style("Template dir:").bold(),
+            style(template_dir.display()).yellow()
+        );
+        match config_path {
+            Some(config_path) => println!(
+                "{} {} {}",
+                emoji::WRENCH,
+                style("Config file:").bold(),
+                style(config_path.display()).yellow()
+            ),
+            None => println!(
+                "{} {}",
+                emoji::WARN,
+                style(format!("No {} found", CONFIG_FILE_NAME)).bold()
+            ),
+        }
+    }
     Ok(())
 }
 
+/// Adds the resolved values of `placeholder_names` to `template_values`, unless they are provided already
+fn share_resolved_values(
+    liquid_object: &liquid::Object,
+    placeholder_names: &[String],
+    template_values: &mut HashMap<String, toml::Value>,
+) {
+    for name in placeholder_names {
+        if template_values.contains_key(name) {
+            continue;
+        }
+        if let Some(value) = liquid_object
+            .get(name.as_str())
+            .and_then(|value| toml::Value::try_from(value).ok())
+        {
+            template_values.insert(name.clone(), value);
+        }
+    }
+}
+
+/// Copies the expanded templates into their output dirs and initializes the vcs in `project_dir`.
+///
+/// Returns the written files and if a repository has been initialized.
+fn write_project_dir(
+    outputs: &[(PathBuf, PathBuf, String)],
+    project_dir: &Path,
+    branch: String,
+    history: Option<&Path>,
+    args: &GenerateArgs,
+) -> Result<(Vec<PathBuf>, bool)> {
+    let mut files = vec![];
+    for (template_folder, output_dir, render_extension) in outputs {
+        files.extend(copy_dir_all(
+            template_folder,
+            output_dir,
+            args.overwrite,
+            false,
+            args.update,
+            Symlinks::from_args(args),
+            render_extension,
+        )?);
+    }
+
+    if let Some(template_dir) = history {
+        if !args.silent {
+            info!(
+                "{}",
+                style("Keeping the git history of the template").bold()
+            );
+        }
+        git::copy_history(template_dir, project_dir)?;
+        return Ok((files, true));
+    }
+
+    let vcs = args.vcs.unwrap_or(Vcs::Git);
+    let initialize_vcs = !vcs.is_none() && (!(args.init || args.update) || args.force_git_init);
+    if initialize_vcs {
+        if !args.silent {
+            info!(
+                "{}",
+                style(format!("Initializing a fresh {} repository", vcs.name())).bold()
+            );
+        }
+        let branch = args.initial_branch.clone().unwrap_or(branch);
+        vcs.initialize(project_dir, branch, args.force_git_init)?;
+    }
+
+    Ok((files, initialize_vcs))
+}
+
+/// Removes a half-written `project_dir` after a failure, unless `--keep-project-on-failure` is given.
+///
+/// A directory that existed before, like the current dir on `--init`, is never removed.
+fn cleanup_failed_project_dir(project_dir: &Path, project_dir_existed: bool, args: &GenerateArgs) {
+    if args.init || project_dir_existed || !project_dir.exists() {
+        return;
+    }
+
+    if args.keep_project_on_failure {
+        warn!(
+            "{} `{}`",
+            style("Keeping the partially generated project for inspection at").bold(),
+            style(project_dir.display()).bold().yellow()
+        );
+    } else if let Err(e) = remove_dir_all::remove_dir_all(project_dir) {
+        warn!(
+            "{} `{}`: {}",
+            style("Failed to remove the partially generated project at").bold(),
+            style(project_dir.display()).bold().yellow(),
+            e
+        );
+    }
+}
+
+/// Makes sure `--keep-history` can be honoured, returns the dir whose `.git` is copied into the project.
+///
+/// The history only matches the generated files if the template is generated from the root of its repository.
+fn check_keep_history(
+    args: &GenerateArgs,
+    template_base_dir: &TempDir,
+    template_folders: &[(PathBuf, Option<String>)],
+) -> Result<PathBuf> {
+    if !matches!(args.vcs, None | Some(Vcs::Git)) {
+        bail!(
+            "{} {} {}",
+            emoji::ERROR,
+            style("Keep history Error:").bold().red(),
+            style("`--keep-history` requires `--vcs git`.").bold().red(),
+        );
+    }
+    let base_dir = fs::canonicalize(template_base_dir.path())?;
+    let from_root = match template_folders {
+        [(template_folder, None)] => fs::canonicalize(template_folder)? == base_dir,
+        _ => false,
+    };
+    if !from_root {
+        bail!(
+            "{} {} {}",
+            emoji::ERROR,
+            style("Keep history Error:").bold().red(),
+            style("`--keep-history` can't be used with a subfolder of the template.")
+                .bold()
+                .red(),
+        );
+    }
+    if !base_dir.join(".git").is_dir() {
+        bail!(
+            "{} {} {}",
+            emoji::ERROR,
+            style("Keep history Error:").bold().red(),
+            style("The template has no git history to keep.")
+                .bold()
+                .red(),
+        );
+    }
+    Ok(base_dir)
+}
+
+/// Fetches the template, with all templates to overlay on it.
+///
+/// With `keep_history` the `.git` of the template is kept, the ones of the overlays never are.
 fn prepare_local_template(
     source_template: &UserParsedInput,
-) -> Result<(TempDir, PathBuf, String), anyhow::Error> {
-    let (temp_dir, branch) = get_source_template_into_temp(source_template.location())?;
-    let template_folder = resolve_template_dir(&temp_dir, source_template.subfolder())?;
+    offline: bool,
+    clone_retries: u32,
+    cache_dir: Option<&Path>,
+    respect_gitignore: bool,
+    recurse_submodules: bool,
+    keep_history: bool,
+) -> Result<(TempDir, String, String), anyhow::Error> {
+    let (temp_dir, branch, reference) = get_source_template_into_temp(
+        source_template.location(),
+        offline,
+        clone_retries,
+        cache_dir,
+        respect_gitignore,
+        recurse_submodules,
+        keep_history,
+    )
+    .categorize(ErrorKind::TemplateNotFound)?;
+    for overlay_location in source_template.overlay_locations() {
+        let (overlay_dir, _, _) = get_source_template_into_temp(
+            overlay_location,
+            offline,
+            clone_retries,
+            cache_dir,
+            respect_gitignore,
+            recurse_submodules,
+            false,
+        )
+        .categorize(ErrorKind::TemplateNotFound)?;
+        overlay_template(overlay_dir.path(), temp_dir.path())?;
+    }
+    Ok((temp_dir, branch, reference))
+}
 
-    Ok((temp_dir, template_folder, branch))
+/// Without `--name`, a positional subfolder that is not a dir of the template is taken as the project name,
+/// so `cargo generate <template> <name>` works as expected.
+fn positional_project_name(args: &GenerateArgs, template_dir: &Path) -> Option<String> {
+    if args.name.is_some() {
+        return None;
+    }
+    let subfolder = args.template_path.positional_subfolder()?;
+    (!template_dir.join(subfolder).is_dir()).then(|| subfolder.clone())
+}
+
+/// Resolves the template folders to expand.
+///
+/// With multiple `subfolders` each is paired with the dir within the project dir it is generated into.
+fn resolve_template_folders(
+    temp_dir: &TempDir,
+    source_template: &UserParsedInput,
+    subfolders: &[&str],
+    select_template: Option<&str>,
+) -> Result<Vec<(PathBuf, Option<String>)>> {
+    let template_folders = if subfolders.len() > 1 {
+        resolve_template_dirs(temp_dir, subfolders, select_template)
+            .categorize(ErrorKind::TemplateNotFound)?
+            .into_iter()
+            .map(|(sub_dir, template_folder)| (template_folder, Some(sub_dir)))
+            .collect()
+    } else {
+        vec![(
+            resolve_template_dir(temp_dir, source_template.subfolder(), select_template)
+                .categorize(ErrorKind::TemplateNotFound)?,
+            None,
+        )]
+    };
+    Ok(template_folders)
 }
 
+/// Returns the template dir with the branch of the template and the ref that has been checked out,
+/// only a git template can have a ref that differs from its branch, like a `--tag`
 fn get_source_template_into_temp(
     template_location: &TemplateLocation,
-) -> Result<(TempDir, String)> {
+    offline: bool,
+    clone_retries: u32,
+    cache_dir: Option<&Path>,
+    respect_gitignore: bool,
+    recurse_submodules: bool,
+    keep_history: bool,
+) -> Result<(TempDir, String, String)> {
     let temp_dir: TempDir;
     let branch: String;
+    let mut reference = None;
     match template_location {
         TemplateLocation::Git(git) => {
-            let (temp_dir2, branch2) =
-                git::clone_git_template_into_temp(git.url(), git.branch(), git.identity())?;
+            let (temp_dir2, branch2, reference2) = git::clone_git_template_into_temp(
+                git.url(),
+                git.branch(),
+                git.tag(),
+                git.revision(),
+                git.identity(),
+                offline,
+                clone_retries,
+                cache_dir,
+                recurse_submodules,
+                keep_history,
+            )?;
             temp_dir = temp_dir2;
             branch = branch2;
+            reference = Some(reference2);
+        }
+        TemplateLocation::Mercurial(hg) => {
+            temp_dir = hg::clone_hg_template_into_temp(hg.url(), hg.branch(), offline)?;
+            branch = String::from(DEFAULT_BRANCH);
         }
         TemplateLocation::Path(path) => {
-            temp_dir = copy_path_template_into_temp(path)?;
+            temp_dir = copy_path_template_into_temp(path, respect_gitignore, keep_history)?;
             branch = String::from(DEFAULT_BRANCH); // FIXME is here any reason to set branch when path is used?
         }
+        TemplateLocation::Archive(archive) => {
+            temp_dir = archive::extract_archive_into_temp(archive, offline)?;
+            branch = String::from(DEFAULT_BRANCH);
+        }
+        TemplateLocation::GitHubRelease(release) => {
+            temp_dir = github_release::extract_release_asset_into_temp(release, offline)?;
+            branch = String::from(DEFAULT_BRANCH);
+        }
     };
 
-    Ok((temp_dir, branch))
+    let reference = reference.unwrap_or_else(|| branch.clone());
+    Ok((temp_dir, branch, reference))
+}
+
+/// The template cache is opt-in by `cache_templates = true` in the `[defaults]` of the app config
+fn resolve_template_cache_dir(
+    app_config: &AppConfig,
+    args: &GenerateArgs,
+) -> Result<Option<PathBuf>> {
+    let enabled = !args.no_cache
+        && app_config
+            .defaults
+            .as_ref()
+            .and_then(|defaults| defaults.cache_templates)
+            .unwrap_or(false);
+    enabled.then(git::template_cache_dir).transpose()
+}
+
+/// The error for `--offline`, when a template would have to be fetched from `url`
+pub(crate) fn refuse_to_fetch_offline(url: &str) -> anyhow::Error {
+    anyhow!(
+        "{} {} {}",
+        emoji::ERROR,
+        style("Offline mode:").bold().red(),
+        style(format!("refusing to fetch {}", url)).bold().red(),
+    )
 }
 
 fn resolve_project_name(args: &GenerateArgs) -> Result<ProjectName> {
     match args.name {
         Some(ref n) => Ok(ProjectName::new(n)),
-        None if !args.silent => Ok(ProjectName::new(interactive::name()?)),
-        None => Err(anyhow!(
-            "{} {} {}",
-            emoji::ERROR,
-            style("Project Name Error:").bold().red(),
-            style("Option `--silent` provided, but project name was not set. Please use `--name`.")
+        None => match infer_project_name(args) {
+            Some(name) => Ok(ProjectName::new(name)),
+            None if !args.silent => Ok(ProjectName::new(interactive::name()?)),
+            None => Err(anyhow!(
+                "{} {} {}",
+                emoji::ERROR,
+                style("Project Name Error:").bold().red(),
+                style(
+                    "Option `--silent` provided, but project name was not set. Please use `--name`."
+                )
                 .bold()
                 .red(),
-        )),
+            )),
+        },
     }
 }
 
-fn resolve_template_dir(template_base_dir: &TempDir, subfolder: Option<&str>) -> Result<PathBuf> {
+/// Without `--name`, the project is named after the dir it is generated into,
+/// that is `--destination`, or the current dir with `--init`.
+///
+/// An existing `--destination` with content is the parent of the project, like with `--name`,
+/// so the name can't be inferred from it.
+fn infer_project_name(args: &GenerateArgs) -> Option<String> {
+    let dir = match (&args.destination, args.init) {
+        (Some(destination), _)
+            if args.directory_name.is_none() && (args.update || is_empty_dir(destination)) =>
+        {
+            destination.clone()
+        }
+        (None, true) => env::current_dir().ok()?,
+        _ => return None,
+    };
+    let dir = fs::canonicalize(&dir).unwrap_or(dir);
+    dir.file_name()
+        .and_then(OsStr::to_str)
+        .filter(|name| !name.trim().is_empty())
+        .map(String::from)
+}
+
+/// Resolves the dir of the template within `subfolder`, see [`auto_locate_template_dir`] for `select_template`.
+fn resolve_template_dir(
+    template_base_dir: &TempDir,
+    subfolder: Option<&str>,
+    select_template: Option<&str>,
+) -> Result<PathBuf> {
     if let Some(subfolder) = subfolder {
         let template_base_dir = fs::canonicalize(template_base_dir.path())?;
         let template_dir =
@@ -241,15 +982,87 @@ fn resolve_template_dir(template_base_dir: &TempDir, subfolder: Option<&str>) ->
 
         Ok(auto_locate_template_dir(
             &template_dir,
+            select_template,
             prompt_for_variable,
         )?)
     } else {
-        auto_locate_template_dir(template_base_dir.path(), prompt_for_variable)
+        auto_locate_template_dir(
+            template_base_dir.path(),
+            select_template,
+            prompt_for_variable,
+        )
     }
 }
 
+/// Resolves each of `subfolders` like [`resolve_template_dir`], paired with the name of the dir it is generated into.
+///
+/// Fails if two subfolders would be generated into the same dir, or if one contains another,
+/// as the templates are expanded in place.
+fn resolve_template_dirs(
+    template_base_dir: &TempDir,
+    subfolders: &[&str],
+    select_template: Option<&str>,
+) -> Result<Vec<(String, PathBuf)>> {
+    let mut template_dirs: Vec<(String, PathBuf)> = Vec::with_capacity(subfolders.len());
+    for subfolder in subfolders {
+        let sub_dir = Path::new(subfolder)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .filter(|name| !name.trim().is_empty());
+        let sub_dir = match sub_dir {
+            Some(sub_dir) => sub_dir,
+            None => bail!(
+                "{} {} {}",
+                emoji::ERROR,
+                style("Subfolder Error:").bold().red(),
+                style(format!("`{}` has no name to generate it into", subfolder))
+                    .bold()
+                    .red(),
+            ),
+        };
+        if template_dirs.iter().any(|(other, _)| *other == sub_dir) {
+            bail!(
+                "{} {} {}",
+                emoji::ERROR,
+                style("Subfolder Error:").bold().red(),
+                style(format!(
+                    "multiple subfolders would be generated into `{}`",
+                    sub_dir
+                ))
+                .bold()
+                .red(),
+            );
+        }
+
+        let template_dir =
+            resolve_template_dir(template_base_dir, Some(subfolder), select_template)?;
+        if let Some((other, _)) = template_dirs
+            .iter()
+            .find(|(_, other)| other.starts_with(&template_dir) || template_dir.starts_with(other))
+        {
+            bail!(
+                "{} {} {}",
+                emoji::ERROR,
+                style("Subfolder Error:").bold().red(),
+                style(format!(
+                    "the templates of `{}` and `{}` overlap",
+                    other, sub_dir
+                ))
+                .bold()
+                .red(),
+            );
+        }
+        template_dirs.push((sub_dir, template_dir));
+    }
+    Ok(template_dirs)
+}
+
+/// Locates the template by its `cargo-generate.toml` within `template_base_dir`.
+///
+/// If there are several, the one at `select_template` is taken, otherwise the user is prompted.
 fn auto_locate_template_dir(
     template_base_dir: &Path,
+    select_template: Option<&str>,
     prompt: impl Fn(&TemplateSlots) -> Result<String>,
 ) -> Result<PathBuf> {
     let config_paths = locate_template_configs(template_base_dir)?;
@@ -257,14 +1070,41 @@ fn auto_locate_template_dir(
         0 => Ok(template_base_dir.to_owned()),
         1 => Ok(template_base_dir.join(&config_paths[0])),
         _ => {
+            if let Some(selected) = select_template {
+                let selected = Path::new(selected);
+                return config_paths
+                    .iter()
+                    .find(|path| Path::new(path).components().eq(selected.components()))
+                    .map(|path| template_base_dir.join(path))
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "{} {} {}",
+                            emoji::ERROR,
+                            style("Template Error:").bold().red(),
+                            style(format!(
+                                "`{}` is none of the templates, choose one of: {}",
+                                selected.display(),
+                                config_paths.join(", ")
+                            ))
+                            .bold()
+                            .red(),
+                        )
+                    });
+            }
             let prompt_args = TemplateSlots {
                 prompt: "Which template should be expanded?".into(),
+                prompt_if: None,
+                group: None,
                 var_name: "Template".into(),
                 var_info: VarInfo::String {
                     entry: Box::new(StringEntry {
                         default: Some(config_paths[0].clone()),
                         choices: Some(config_paths),
                         regex: None,
+                        regex_hint: None,
+                        choices_command: None,
+                        editor: false,
+                        transform: None,
                     }),
                 },
             };
@@ -274,73 +1114,444 @@ fn auto_locate_template_dir(
     }
 }
 
-fn copy_path_template_into_temp(src_path: &Path) -> Result<TempDir> {
+/// Copies the template `src` over the template in `dst`, for a repeated `--git`.
+///
+/// Files of `src` replace the ones of `dst`, only `cargo-generate.toml` files at the same path are merged,
+/// see [`config::merge_overlay_config`].
+fn overlay_template(src: &Path, dst: &Path) -> Result<()> {
+    let entries = WalkDir::new(src)
+        .into_iter()
+        .filter_entry(|entry| entry.file_name() != ".git");
+    for entry in entries {
+        let entry = entry?;
+        let dst_path = dst.join(entry.path().strip_prefix(src)?);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&dst_path)?;
+        } else if entry.file_name() == CONFIG_FILE_NAME && dst_path.is_file() {
+            let merged = config::merge_overlay_config(
+                &fs::read_to_string(&dst_path)?,
+                &fs::read_to_string(entry.path())?,
+            )
+            .with_context(|| {
+                format!(
+                    "Failed to merge `{}` of the overlaid templates",
+                    entry
+                        .path()
+                        .strip_prefix(src)
+                        .unwrap_or(entry.path())
+                        .display()
+                )
+            })?;
+            fs::write(&dst_path, merged)?;
+        } else {
+            fs::copy(entry.path(), &dst_path)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn copy_path_template_into_temp(
+    src_path: &Path,
+    respect_gitignore: bool,
+    keep_history: bool,
+) -> Result<TempDir> {
     let path_clone_dir = tempfile::tempdir()?;
-    copy_dir_all(src_path, path_clone_dir.path())?;
-    git::remove_history(path_clone_dir.path())?;
+    if respect_gitignore {
+        copy_dir_respecting_gitignore(src_path, path_clone_dir.path())?;
+    } else {
+        // the template is copied as is, the render extension is stripped once it has been expanded
+        copy_dir_all(
+            src_path,
+            path_clone_dir.path(),
+            Overwrite::Never,
+            false,
+            false,
+            Symlinks::Preserve,
+            "",
+        )?;
+    }
+    if keep_history && src_path.join(".git").is_dir() {
+        git::copy_history(src_path, path_clone_dir.path())?;
+    } else {
+        git::remove_history(path_clone_dir.path())?;
+    }
 
     Ok(path_clone_dir)
 }
 
-pub(crate) fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> Result<()> {
-    fn check_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> Result<()> {
+/// Copies a local template like [`copy_dir_all`] does, but leaves out everything ignored by the `.gitignore` files
+/// of the template, like a `target` dir, see `--respect-gitignore`.
+fn copy_dir_respecting_gitignore(src: &Path, dst: &Path) -> Result<()> {
+    let entries = WalkBuilder::new(src)
+        .standard_filters(false)
+        .git_ignore(true)
+        .require_git(false)
+        .filter_entry(|entry| entry.file_name() != OsStr::new(".git"))
+        .build();
+    for entry in entries {
+        let entry = entry?;
+        let dst_path = dst.join(entry.path().strip_prefix(src)?);
+        match entry.file_type() {
+            Some(file_type) if file_type.is_dir() => fs::create_dir_all(&dst_path)?,
+            Some(file_type) if file_type.is_symlink() => {
+                create_symlink(&fs::read_link(entry.path())?, &dst_path)?;
+            }
+            Some(file_type) if file_type.is_file() => {
+                fs::copy(entry.path(), &dst_path)?;
+            }
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+/// What [`copy_dir_all`] does with symbolic links
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Symlinks {
+    /// Links are not copied, and fail if something exists at their destination
+    Unsupported,
+    /// Links are recreated as they are, as long as they don't point outside of the destination, see `--allow-symlinks`
+    Recreate,
+    /// Links are recreated as they are, for a copy of the template itself
+    Preserve,
+}
+
+impl Symlinks {
+    const fn from_args(args: &GenerateArgs) -> Self {
+        if args.allow_symlinks {
+            Self::Recreate
+        } else {
+            Self::Unsupported
+        }
+    }
+}
+
+/// Copies all files from `src` to `dst`, existing files are handled according to `overwrite`.
+///
+/// With `dry_run` the collision check is done as usual, but the target paths are only printed.
+/// As nothing is written on a dry run, `Overwrite::Prompt` doesn't ask and reports the file instead.
+/// With `skip_identical` existing files with the same content are neither a collision nor copied, see `--update`.
+/// The `render_extension` is stripped from the names of the files.
+/// Returns the paths of all files copied, or that would be copied.
+pub(crate) fn copy_dir_all(
+    src: impl AsRef<Path>,
+    dst: impl AsRef<Path>,
+    overwrite: Overwrite,
+    dry_run: bool,
+    skip_identical: bool,
+    symlinks: Symlinks,
+    render_extension: &str,
+) -> Result<Vec<PathBuf>> {
+    /// Collects the existing files the user chose to keep, or that are identical with `skip_identical`
+    #[allow(clippy::too_many_arguments)]
+    fn check_dir_all(
+        src: impl AsRef<Path>,
+        dst: impl AsRef<Path>,
+        overwrite: Overwrite,
+        dry_run: bool,
+        skip_identical: bool,
+        symlinks: Symlinks,
+        render_extension: &str,
+        skipped: &mut Vec<PathBuf>,
+    ) -> Result<()> {
         if !dst.as_ref().exists() {
             return Ok(());
         }
 
         for src_entry in fs::read_dir(src)? {
             let src_entry = src_entry?;
+            if is_git_entry(&src_entry) {
+                continue;
+            }
             let filename = src_entry.file_name().to_string_lossy().to_string();
             let entry_type = src_entry.file_type()?;
 
             if entry_type.is_dir() {
                 let dst_path = dst.as_ref().join(filename);
-                check_dir_all(src_entry.path(), dst_path)?;
-            } else if entry_type.is_file() {
-                let filename = filename.strip_suffix(".liquid").unwrap_or(&filename);
+                check_dir_all(
+                    src_entry.path(),
+                    dst_path,
+                    overwrite,
+                    dry_run,
+                    skip_identical,
+                    symlinks,
+                    render_extension,
+                    skipped,
+                )?;
+            } else if entry_type.is_file()
+                || (entry_type.is_symlink() && symlinks != Symlinks::Unsupported)
+            {
+                let filename: &str = if entry_type.is_file() {
+                    filename.strip_suffix(render_extension).unwrap_or(&filename)
+                } else {
+                    &filename
+                };
                 let dst_path = dst.as_ref().join(filename);
-                if dst_path.exists() {
-                    bail!(
-                        "{} {} {}",
-                        crate::emoji::WARN,
-                        style("File already exists:").bold().red(),
-                        style(dst_path.display()).bold().red(),
-                    )
+                if dst_path.symlink_metadata().is_err() {
+                    continue;
+                }
+                if skip_identical
+                    && entry_type.is_file()
+                    && is_identical(&src_entry.path(), &dst_path)?
+                {
+                    skipped.push(dst_path);
+                    continue;
+                }
+                match overwrite {
+                    Overwrite::Never => {
+                        return Err(anyhow!(
+                            "{} {} {}",
+                            crate::emoji::WARN,
+                            style("File already exists:").bold().red(),
+                            style(dst_path.display()).bold().red(),
+                        ))
+                        .categorize(ErrorKind::Collision)
+                    }
+                    Overwrite::Always => (),
+                    Overwrite::Prompt if dry_run => {
+                        out!(
+                            "{} {}",
+                            style("Would ask to overwrite:").bold(),
+                            style(dst_path.display()).bold().yellow()
+                        );
+                    }
+                    Overwrite::Prompt => match interactive::collision(&dst_path)? {
+                        Collision::Overwrite => (),
+                        Collision::Skip => skipped.push(dst_path),
+                        Collision::Abort => {
+                            return Err(anyhow!(
+                                "{} {} {}",
+                                crate::emoji::ERROR,
+                                style("Aborted, the file already exists:").bold().red(),
+                                style(dst_path.display()).bold().red(),
+                            ))
+                            .categorize(ErrorKind::Collision)
+                        }
+                    },
                 }
             } else {
                 bail!(
                     "{} {}",
                     crate::emoji::WARN,
-                    style("Symbolic links not supported").bold().red(),
+                    style("Symbolic links not supported, see `--allow-symlinks`")
+                        .bold()
+                        .red(),
                 )
             }
         }
         Ok(())
     }
-    fn copy_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> Result<()> {
-        fs::create_dir_all(&dst)?;
-        let git_file_name: OsString = ".git".into();
+    fn copy_all(
+        src: impl AsRef<Path>,
+        dst: impl AsRef<Path>,
+        root: &Path,
+        skipped: &[PathBuf],
+        dry_run: bool,
+        symlinks: Symlinks,
+        render_extension: &str,
+    ) -> Result<Vec<PathBuf>> {
+        if !dry_run {
+            fs::create_dir_all(&dst)?;
+        }
+        let mut files = vec![];
         for src_entry in fs::read_dir(src)? {
             let src_entry = src_entry?;
+            if is_git_entry(&src_entry) {
+                continue;
+            }
             let filename = src_entry.file_name().to_string_lossy().to_string();
             let entry_type = src_entry.file_type()?;
             if entry_type.is_dir() {
                 let dst_path = dst.as_ref().join(filename);
-                if git_file_name == src_entry.file_name() {
+                files.extend(copy_all(
+                    src_entry.path(),
+                    dst_path,
+                    root,
+                    skipped,
+                    dry_run,
+                    symlinks,
+                    render_extension,
+                )?);
+            } else if entry_type.is_symlink() && symlinks != Symlinks::Unsupported {
+                let dst_path = dst.as_ref().join(filename);
+                if skipped.contains(&dst_path) {
                     continue;
                 }
-                copy_dir_all(src_entry.path(), dst_path)?;
+                let target = fs::read_link(src_entry.path())?;
+                let link_dir = dst.as_ref().strip_prefix(root)?;
+                if symlinks == Symlinks::Recreate && !link_stays_within(link_dir, &target) {
+                    bail!(
+                        "{} {} `{}` -> `{}`",
+                        crate::emoji::ERROR,
+                        style("Symbolic link points outside of the project:")
+                            .bold()
+                            .red(),
+                        style(dst_path.display()).bold(),
+                        style(target.display()).bold(),
+                    );
+                }
+                if dry_run {
+                    out!("{} {}", emoji::DIAMOND, style(dst_path.display()).bold());
+                } else {
+                    if dst_path.symlink_metadata().is_ok() {
+                        fs::remove_file(&dst_path)?;
+                    }
+                    create_symlink(&target, &dst_path)?;
+                }
+                files.push(dst_path);
             } else if entry_type.is_file() {
-                let filename = filename.strip_suffix(".liquid").unwrap_or(&filename);
+                let filename = filename.strip_suffix(render_extension).unwrap_or(&filename);
                 let dst_path = dst.as_ref().join(filename);
-                fs::copy(src_entry.path(), dst_path)?;
+                if skipped.contains(&dst_path) {
+                    continue;
+                }
+                if dry_run {
+                    out!("{} {}", emoji::DIAMOND, style(dst_path.display()).bold());
+                } else {
+                    fs::copy(src_entry.path(), &dst_path)?;
+                }
+                files.push(dst_path);
             }
         }
-        Ok(())
+        Ok(files)
+    }
+
+    /// A `.git` of the template is never copied, so the repository of the destination is protected,
+    /// e.g. on `--init` into an existing project
+    fn is_git_entry(entry: &fs::DirEntry) -> bool {
+        entry.file_name() == OsStr::new(".git")
+    }
+
+    let mut skipped = Vec::new();
+    check_dir_all(
+        &src,
+        &dst,
+        overwrite,
+        dry_run,
+        skip_identical,
+        symlinks,
+        render_extension,
+        &mut skipped,
+    )?;
+    copy_all(
+        &src,
+        &dst,
+        dst.as_ref(),
+        &skipped,
+        dry_run,
+        symlinks,
+        render_extension,
+    )
+}
+
+/// `true` if `dst` is a file with the same content as `src`
+fn is_identical(src: &Path, dst: &Path) -> Result<bool> {
+    let dst_metadata = fs::metadata(dst)?;
+    if !dst_metadata.is_file() || fs::metadata(src)?.len() != dst_metadata.len() {
+        return Ok(false);
     }
+    Ok(fs::read(src)? == fs::read(dst)?)
+}
 
-    check_dir_all(&src, &dst)?;
-    copy_all(src, dst)
+/// How the files of an expanded template compare to the ones of the project, for `--update`
+#[derive(Debug, Default, PartialEq)]
+struct Changes {
+    added: Vec<PathBuf>,
+    modified: Vec<PathBuf>,
+    unchanged: Vec<PathBuf>,
+}
+
+fn compare_with_project(
+    template_folder: &Path,
+    output_dir: &Path,
+    render_extension: &str,
+) -> Result<Changes> {
+    let mut changes = Changes::default();
+    let entries = WalkDir::new(template_folder)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| entry.file_name() != OsStr::new(".git"));
+    for entry in entries {
+        let entry = entry?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let file_name = entry.file_name().to_string_lossy();
+        let file_name = file_name
+            .strip_suffix(render_extension)
+            .unwrap_or(&file_name);
+        let dst_path = output_dir
+            .join(entry.path().strip_prefix(template_folder)?)
+            .with_file_name(file_name);
+        if dst_path.symlink_metadata().is_err() {
+            changes.added.push(dst_path);
+        } else if is_identical(entry.path(), &dst_path)? {
+            changes.unchanged.push(dst_path);
+        } else {
+            changes.modified.push(dst_path);
+        }
+    }
+    Ok(changes)
+}
+
+fn print_changes(changes: &Changes) {
+    for (marker, files) in [("+", &changes.added), ("~", &changes.modified)] {
+        for file in files {
+            out!("    {} {}", style(marker).bold(), file.display());
+        }
+    }
+    out!(
+        "{} {}",
+        emoji::WRENCH,
+        style(format!(
+            "{} added, {} modified, {} unchanged",
+            changes.added.len(),
+            changes.modified.len(),
+            changes.unchanged.len()
+        ))
+        .bold()
+    );
+}
+
+/// `true` if the relative `target` of a link in `link_dir`, relative to the project dir, stays within the project dir
+pub(crate) fn link_stays_within(link_dir: &Path, target: &Path) -> bool {
+    let mut depth = 0_usize;
+    for component in link_dir.join(target).components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+            Component::ParentDir if depth > 0 => depth -= 1,
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return false,
+        }
+    }
+    true
+}
+
+#[cfg(unix)]
+fn create_symlink(target: &Path, link: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+fn create_symlink(target: &Path, link: &Path) -> io::Result<()> {
+    // windows distinguishes links to dirs from links to files
+    let is_dir = link
+        .parent()
+        .map_or(false, |link_dir| link_dir.join(target).is_dir());
+    if is_dir {
+        std::os::windows::fs::symlink_dir(target, link)
+    } else {
+        std::os::windows::fs::symlink_file(target, link)
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn create_symlink(_target: &Path, _link: &Path) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "symbolic links are not supported on this platform",
+    ))
 }
 
 fn locate_template_file(
@@ -368,7 +1579,8 @@ fn locate_template_file(
 /// Resolves the project dir.
 ///
 /// if `args.init == true` it returns the path of `$CWD` and if let some `args.destination`,
-/// it returns the given path.
+/// it returns the given path. Without `args.name` an empty destination itself is the project dir,
+/// as the project is named after it. Missing parents of the project dir are created.
 fn resolve_project_dir(
     base_dir: &Path,
     name: &ProjectName,
@@ -378,55 +1590,139 @@ fn resolve_project_dir(
         return Ok(base_dir.into());
     }
 
+    // the project is named after the destination, so it is generated right into it
+    if let (None, Some(destination)) = (&args.name, &args.destination) {
+        if infer_project_name(args).is_some() {
+            create_parent_dirs(destination, args)?;
+            return Ok(destination.clone());
+        }
+    }
+
     let base_path = args
         .destination
         .as_ref()
         .map(|p| p.to_path_buf())
         .unwrap_or_else(|| env::current_dir().unwrap_or_else(|_| ".".into()));
 
-    let dir_name = args.force.then(|| name.raw()).unwrap_or_else(|| {
-        rename_warning(name);
-        name.kebab_case()
-    });
+    let dir_name = match args.directory_name {
+        Some(ref dir_name) => dir_name.clone(),
+        None if args.force || args.no_rename => name.raw(),
+        None => {
+            rename_warning(name);
+            name.kebab_case()
+        }
+    };
 
     let project_dir = base_path.join(&dir_name);
 
-    if project_dir.exists() {
-        bail!(
+    // nothing is written with `--to-stdout-tar`, and `--update` is meant for an existing dir
+    if project_dir.exists() && !args.to_stdout_tar && !args.update {
+        return Err(anyhow!(
             "{} {}",
             emoji::ERROR,
             style("Target directory already exists, aborting!")
                 .bold()
                 .red()
-        );
+        ))
+        .categorize(ErrorKind::Collision);
     }
+    create_parent_dirs(&project_dir, args)?;
 
     Ok(project_dir)
 }
 
+/// A dir that doesn't exist yet counts as empty
+fn is_empty_dir(dir: &Path) -> bool {
+    dir.read_dir()
+        .map_or(true, |mut entries| entries.next().is_none())
+}
+
+/// Creates the missing parents of the `project_dir`, like a deep `--destination`, but not the project dir itself
+fn create_parent_dirs(project_dir: &Path, args: &GenerateArgs) -> Result<()> {
+    if args.dry_run || args.to_stdout_tar {
+        return Ok(());
+    }
+    match project_dir.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => {
+            fs::create_dir_all(parent).with_context(|| {
+                format!(
+                    "{} {} `{}`",
+                    emoji::ERROR,
+                    style("Failed to create the destination directory")
+                        .bold()
+                        .red(),
+                    style(parent.display()).bold().yellow()
+                )
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn expand_template(
     project_dir: &Path,
     name: &ProjectName,
     dir: &Path,
     template_values: &HashMap<String, toml::Value>,
     mut template_config: Config,
+    template_config_path: Option<&Path>,
+    template_ref: TemplateRef,
+    now: &Now,
     args: &GenerateArgs,
-) -> Result<()> {
+) -> Result<liquid::Object> {
+    // a `choices_command` runs while filling the placeholders, so checking the hooks has to come first
+    if let Some(ref manifest) = args.verify_checksums {
+        let commands_config = template_config_path.filter(|_| template_config.has_commands());
+        verify_checksums(
+            dir,
+            manifest,
+            &template_config.get_hook_files(),
+            commands_config,
+        )
+        .categorize(ErrorKind::HookFailed)?;
+    }
+
     let crate_type: CrateType = args.into();
-    let liquid_object = template::create_liquid_object(args, project_dir, name, &crate_type)?;
+    let raw_definitions = raw_definitions(&args.define)?;
+    let mut liquid_object =
+        template::create_liquid_object(args, project_dir, name, &crate_type, template_ref, now)?;
+    let builtins =
+        project_variables::take_overridden_builtins(&mut liquid_object, &template_config);
+    let value_provider = |slot: &TemplateSlots, prompted_group: &mut Option<String>| {
+        let slot = &slot.with_command_choices(dir, args.allow_commands);
+        // a string keeps the text of `--define`, the coerced `0.10` would render as `0.1`
+        let provided_value = match (&slot.var_info, raw_definitions.get(&slot.var_name)) {
+            (VarInfo::String { .. }, Some(raw)) => Some(raw.clone()),
+            _ => template_values
+                .get(&slot.var_name)
+                .and_then(provided_value_as_string),
+        };
+        if provided_value.is_none() && args.silent {
+            anyhow::bail!(ConversionError::MissingPlaceholderVariable {
+                var_name: slot.var_name.clone()
+            })
+        }
+        interactive::variable(slot, provided_value.as_deref(), args.silent, prompted_group)
+    };
     let liquid_object =
-        project_variables::fill_project_variables(liquid_object, &template_config, |slot| {
-            let provided_value = template_values.get(&slot.var_name).and_then(|v| v.as_str());
-            if provided_value.is_none() && args.silent {
-                anyhow::bail!(ConversionError::MissingPlaceholderVariable {
-                    var_name: slot.var_name.clone()
-                })
-            }
-            interactive::variable(slot, provided_value)
-        })?;
-    let liquid_object = add_missing_provided_values(liquid_object, template_values)?;
+        project_variables::fill_project_variables(liquid_object, &template_config, value_provider)?;
+    // the values of conditional placeholders are checked against their slot once their condition is known
+    let conditional_names = template_config.conditional_placeholder_names();
+    let unconditional_values = template_values
+        .iter()
+        .filter(|(name, _)| !conditional_names.contains(&name.as_str()))
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect();
+    let liquid_object = add_missing_provided_values(liquid_object, &unconditional_values);
     let (mut template_cfg, liquid_object) =
-        merge_conditionals(&template_config, liquid_object, args)?;
+        merge_conditionals(&template_config, liquid_object, value_provider)?;
+    let mut liquid_object = add_missing_provided_values(liquid_object, template_values);
+    project_variables::restore_builtins(&mut liquid_object, builtins);
+
+    if args.dry_run && !args.quiet {
+        print_resolved_placeholders(&liquid_object);
+    }
 
     let all_hook_files = template_config.get_hook_files();
 
@@ -437,10 +1733,27 @@ fn expand_template(
         Rc::clone(&liquid_object),
         &mut template_config,
         args.allow_commands,
-        args.silent,
+        args.deny_hooks,
+        &args.skip_hooks,
+        args.dry_run,
+    )
+    .categorize(ErrorKind::HookFailed)?;
+    ignore_me::remove_unneeded_files(
+        dir,
+        &template_cfg.ignore,
+        template_config_path,
+        args.verbose || args.dry_run,
     )?;
-    ignore_me::remove_unneeded_files(dir, &template_cfg.ignore, args.verbose)?;
-    let mut pbar = progressbar::new();
+    if let Some(config_dir) = template_config_path.and_then(Path::parent) {
+        let placeholders_include = template_config.placeholders_include_paths(config_dir);
+        remove_dir_files(
+            placeholders_include
+                .into_iter()
+                .filter(|path| path.starts_with(dir)),
+            args.verbose || args.dry_run,
+        );
+    }
+    let pbar = progressbar::new(ProgressMode::detect(args.no_progress || args.quiet));
 
     // SAFETY: We gave a clone of the Rc to `execute_pre_hooks` which by now has already been dropped. Therefore, there
     // is no other pointer into this Rc which makes it safe to `get_mut`.
@@ -451,51 +1764,114 @@ fn expand_template(
         liquid_object_ref,
         &mut template_cfg,
         &all_hook_files,
-        &mut pbar,
+        &pbar,
+        args.jobs,
     )?;
     pbar.join().unwrap();
+    if let Some(ref renames) = template_cfg.rename {
+        filenames::apply_renames(dir, renames, template_cfg.render_extension())?;
+    }
 
     execute_post_hooks(
         dir,
         Rc::clone(&liquid_object),
         &template_config,
         args.allow_commands,
-        args.silent,
-    )?;
-    remove_dir_files(all_hook_files, false);
+        args.deny_hooks,
+        &args.skip_hooks,
+        args.dry_run,
+    )
+    .categorize(ErrorKind::HookFailed)?;
+    remove_dir_files(all_hook_files.iter().map(|file| dir.join(file)), false);
+
+    let liquid_object = RefCell::borrow(&liquid_object).clone();
+    Ok(liquid_object)
+}
 
-    Ok(())
+fn provided_value_as_string(value: &toml::Value) -> Option<String> {
+    match value {
+        toml::Value::String(content) => Some(content.clone()),
+        toml::Value::Integer(_) | toml::Value::Float(_) | toml::Value::Boolean(_) => {
+            Some(value.to_string())
+        }
+        toml::Value::Array(values) => values
+            .iter()
+            .map(toml::Value::as_str)
+            .collect::<Option<Vec<_>>>()
+            .map(|values| values.join(",")),
+        _ => None,
+    }
 }
 
+/// Builtin placeholders a provided value takes precedence over, e.g. by `--define authors=..`
+const OVERRIDABLE_BUILTINS: [&str; 3] = ["authors", "username", "email"];
+
 pub(crate) fn add_missing_provided_values(
     mut liquid_object: liquid::Object,
     template_values: &HashMap<String, toml::Value>,
-) -> Result<liquid::Object, anyhow::Error> {
-    template_values.iter().try_for_each(|(k, v)| {
-        if liquid_object.contains_key(k.as_str()) {
-            return Ok(());
+) -> liquid::Object {
+    for (k, v) in template_values {
+        if liquid_object.contains_key(k.as_str()) && !OVERRIDABLE_BUILTINS.contains(&k.as_str()) {
+            continue;
+        }
+        let value = provided_value_into_liquid(v);
+
+        // a dotted key like `now.year` overrides a field of a builtin object
+        if let Some((object_name, field)) = k.split_once('.') {
+            if let Some(liquid_core::Value::Object(object)) = liquid_object.get_mut(object_name) {
+                object.insert(field.to_string().into(), value);
+                continue;
+            }
         }
-        let value = match v {
-            toml::Value::String(content) => liquid_core::Value::Scalar(content.clone().into()),
-            toml::Value::Boolean(content) => liquid_core::Value::Scalar((*content).into()),
-            _ => anyhow::bail!(format!(
-                "{} {}",
-                emoji::ERROR,
-                style("Unsupported value type. Only Strings and Booleans are supported.")
-                    .bold()
-                    .red(),
-            )),
-        };
         liquid_object.insert(k.clone().into(), value);
-        Ok(())
-    })?;
-    Ok(liquid_object)
+    }
+    liquid_object
 }
 
+/// Converts a provided value, arrays and tables recursively, so templates can iterate over them
+/// with `{% for %}` or access their fields
+fn provided_value_into_liquid(value: &toml::Value) -> liquid_core::Value {
+    match value {
+        toml::Value::String(content) => liquid_core::Value::Scalar(content.clone().into()),
+        toml::Value::Boolean(content) => liquid_core::Value::Scalar((*content).into()),
+        toml::Value::Integer(content) => liquid_core::Value::Scalar((*content).into()),
+        toml::Value::Float(content) => liquid_core::Value::Scalar((*content).into()),
+        toml::Value::Datetime(content) => liquid_core::Value::Scalar(content.to_string().into()),
+        toml::Value::Array(values) => {
+            liquid_core::Value::Array(values.iter().map(provided_value_into_liquid).collect())
+        }
+        toml::Value::Table(table) => liquid_core::Value::Object(
+            table
+                .iter()
+                .map(|(key, value)| (key.clone().into(), provided_value_into_liquid(value)))
+                .collect(),
+        ),
+    }
+}
+
+fn print_resolved_placeholders(liquid_object: &liquid::Object) {
+    out!(
+        "{} {}",
+        emoji::WRENCH,
+        style("Resolved placeholders:").bold()
+    );
+    let mut placeholders = liquid_object.iter().collect::<Vec<_>>();
+    placeholders.sort_unstable_by_key(|(key, _)| *key);
+    for (key, value) in placeholders {
+        out!(
+            "    {} {} = {}",
+            emoji::DIAMOND,
+            style(key).bold(),
+            value.to_kstr()
+        );
+    }
+}
+
+/// Adds the config of the conditionals whose condition holds, and asks the `value_provider` for their placeholders
 fn merge_conditionals(
     template_config: &Config,
     liquid_object: liquid::Object,
-    args: &GenerateArgs,
+    value_provider: impl Fn(&TemplateSlots, &mut Option<String>) -> Result<liquid_core::Value>,
 ) -> Result<(config::TemplateConfig, liquid::Object), anyhow::Error> {
     let mut template_config = (*template_config).clone();
     let mut template_cfg = template_config.template.unwrap_or_default();
@@ -505,28 +1881,17 @@ fn merge_conditionals(
     }
 
     let mut conditionals = conditionals.unwrap();
-    let mut engine = rhai::Engine::new();
-    #[allow(deprecated)]
-    engine.on_var({
-        let liqobj = liquid_object.clone();
-        move |name, _, _| match liqobj.get(name) {
-            Some(value) => Ok(value.as_view().as_scalar().map(|scalar| {
-                scalar.to_bool().map_or_else(
-                    || {
-                        let v = scalar.to_kstr();
-                        v.as_str().into()
-                    },
-                    |v| v.into(),
-                )
-            })),
-            None => Ok(None),
-        }
-    });
+    let engine = project_variables::rhai_engine(&liquid_object, &[]);
+    let mut platform = rhai::Scope::new();
+    for (name, value) in template_variables::get_platform_variables() {
+        platform.push_constant(name, value.to_string());
+    }
 
-    for (_, conditional_template_cfg) in conditionals
-        .iter_mut()
-        .filter(|(key, _)| engine.eval_expression::<bool>(key).unwrap_or_default())
-    {
+    for (_, conditional_template_cfg) in conditionals.iter_mut().filter(|(key, _)| {
+        engine
+            .eval_expression_with_scope::<bool>(&mut platform, key)
+            .unwrap_or_default()
+    }) {
         if let Some(mut extra_includes) = conditional_template_cfg.include.take() {
             let mut includes = template_cfg.include.unwrap_or_default();
             includes.append(&mut extra_includes);
@@ -558,14 +1923,7 @@ fn merge_conditionals(
 
     template_config.template = Some(template_cfg);
     let template =
-        project_variables::fill_project_variables(liquid_object, &template_config, |slot| {
-            if args.silent {
-                anyhow::bail!(ConversionError::MissingPlaceholderVariable {
-                    var_name: slot.var_name.clone()
-                })
-            }
-            interactive::variable(slot, None)
-        })?;
+        project_variables::fill_project_variables(liquid_object, &template_config, value_provider)?;
     template_cfg = template_config.template.unwrap_or_default();
 
     Ok((template_cfg, template))
@@ -613,8 +1971,13 @@ fn check_cargo_generate_version(template_config: &Config) -> Result<(), anyhow::
 
 #[cfg(test)]
 mod tests {
-    use crate::{auto_locate_template_dir, project_variables::VarInfo};
+    use crate::{
+        auto_locate_template_dir, cleanup_failed_project_dir, copy_dir_all, link_stays_within,
+        project_variables::VarInfo, Cli, GenerateArgs, Overwrite, Symlinks,
+        DEFAULT_RENDER_EXTENSION,
+    };
     use anyhow::anyhow;
+    use clap::Parser;
     use std::{
         fs,
         io::Write,
@@ -629,7 +1992,7 @@ mod tests {
         create_file(&tmp, "dir2/dir2_1/Cargo.toml", "")?;
         create_file(&tmp, "dir3/Cargo.toml", "")?;
 
-        let r = auto_locate_template_dir(tmp.path(), |_slots| Err(anyhow!("test")))?;
+        let r = auto_locate_template_dir(tmp.path(), None, |_slots| Err(anyhow!("test")))?;
         assert_eq!(tmp.path(), r);
         Ok(())
     }
@@ -643,7 +2006,7 @@ mod tests {
         create_file(&tmp, "dir2/dir2_2/cargo-generate.toml", "")?;
         create_file(&tmp, "dir3/Cargo.toml", "")?;
 
-        let r = auto_locate_template_dir(tmp.path(), |_slots| Err(anyhow!("test")))?;
+        let r = auto_locate_template_dir(tmp.path(), None, |_slots| Err(anyhow!("test")))?;
         assert_eq!(tmp.path().join("dir2/dir2_2"), r);
         Ok(())
     }
@@ -657,8 +2020,10 @@ mod tests {
         create_file(&tmp, "dir3/Cargo.toml", "")?;
         create_file(&tmp, "dir4/cargo-generate.toml", "")?;
 
-        let r = auto_locate_template_dir(tmp.path(), |slots| match &slots.var_info {
-            VarInfo::Bool { .. } => anyhow::bail!("Wrong prompt type"),
+        let r = auto_locate_template_dir(tmp.path(), None, |slots| match &slots.var_info {
+            VarInfo::Bool { .. } | VarInfo::Number { .. } | VarInfo::MultiSelect { .. } => {
+                anyhow::bail!("Wrong prompt type")
+            }
             VarInfo::String { entry } => {
                 if let Some(mut choices) = entry.choices.clone() {
                     choices.sort();
@@ -678,6 +2043,161 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn auto_locate_template_takes_the_selected_template() -> anyhow::Result<()> {
+        let tmp = tempdir().unwrap();
+        create_file(&tmp, "dir2/dir2_2/cargo-generate.toml", "")?;
+        create_file(&tmp, "dir4/cargo-generate.toml", "")?;
+
+        let r = auto_locate_template_dir(tmp.path(), Some("dir4/"), |_slots| Err(anyhow!("test")))?;
+        assert_eq!(tmp.path().join("dir4"), r);
+
+        let error =
+            auto_locate_template_dir(tmp.path(), Some("dir3"), |_slots| Err(anyhow!("test")))
+                .unwrap_err();
+        assert!(error.to_string().contains("dir4"));
+        Ok(())
+    }
+
+    fn parse_args(args: &[&str]) -> GenerateArgs {
+        let Cli::Generate(args) = Cli::parse_from(
+            ["cargo", "generate", "--git", "template", "--name", "foo"]
+                .iter()
+                .chain(args),
+        );
+        args
+    }
+
+    #[test]
+    fn cleanup_removes_freshly_created_project_dir() -> anyhow::Result<()> {
+        let tmp = tempdir().unwrap();
+        create_file(&tmp, "foo/Cargo.toml", "")?;
+
+        cleanup_failed_project_dir(&tmp.path().join("foo"), false, &parse_args(&[]));
+        assert!(!tmp.path().join("foo").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn cleanup_keeps_project_dir_when_asked_to() -> anyhow::Result<()> {
+        let tmp = tempdir().unwrap();
+        create_file(&tmp, "foo/Cargo.toml", "")?;
+
+        cleanup_failed_project_dir(
+            &tmp.path().join("foo"),
+            false,
+            &parse_args(&["--keep-project-on-failure"]),
+        );
+        assert!(tmp.path().join("foo/Cargo.toml").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn cleanup_never_removes_existing_dirs() -> anyhow::Result<()> {
+        let tmp = tempdir().unwrap();
+        create_file(&tmp, "foo/Cargo.toml", "")?;
+
+        cleanup_failed_project_dir(&tmp.path().join("foo"), true, &parse_args(&[]));
+        cleanup_failed_project_dir(tmp.path(), false, &parse_args(&["--init"]));
+        assert!(tmp.path().join("foo/Cargo.toml").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn copy_dir_all_never_touches_the_git_dir_of_the_destination() -> anyhow::Result<()> {
+        let src = tempdir().unwrap();
+        create_file(&src, ".git/HEAD", "ref: refs/heads/template")?;
+        create_file(&src, "rustfmt.toml", "")?;
+        let dst = tempdir().unwrap();
+        create_file(&dst, ".git/HEAD", "ref: refs/heads/main")?;
+        create_file(&dst, "Cargo.toml", "")?;
+
+        let files = copy_dir_all(
+            src.path(),
+            dst.path(),
+            Overwrite::Never,
+            false,
+            false,
+            Symlinks::Unsupported,
+            DEFAULT_RENDER_EXTENSION,
+        )?;
+
+        assert_eq!(files, vec![dst.path().join("rustfmt.toml")]);
+        assert_eq!(
+            fs::read_to_string(dst.path().join(".git/HEAD"))?,
+            "ref: refs/heads/main"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn links_must_stay_within_the_project() {
+        assert!(link_stays_within(Path::new(""), Path::new("LICENSE-MIT")));
+        assert!(link_stays_within(
+            Path::new("docs"),
+            Path::new("../LICENSE")
+        ));
+        assert!(link_stays_within(Path::new("a/b"), Path::new("./../../c")));
+        assert!(!link_stays_within(Path::new(""), Path::new("../LICENSE")));
+        assert!(!link_stays_within(
+            Path::new("docs"),
+            Path::new("../../LICENSE")
+        ));
+        assert!(!link_stays_within(Path::new(""), Path::new("/etc/passwd")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn copy_dir_all_recreates_symlinks_only_if_allowed() -> anyhow::Result<()> {
+        let src = tempdir().unwrap();
+        create_file(&src, "LICENSE-MIT", "MIT")?;
+        std::os::unix::fs::symlink("../LICENSE-MIT", src.path().join("docs-LICENSE"))?;
+        fs::create_dir(src.path().join("docs"))?;
+        std::os::unix::fs::symlink("../LICENSE-MIT", src.path().join("docs/LICENSE"))?;
+
+        let dst = tempdir().unwrap();
+        let files = copy_dir_all(
+            src.path(),
+            dst.path().join("project"),
+            Overwrite::Never,
+            false,
+            false,
+            Symlinks::Unsupported,
+            DEFAULT_RENDER_EXTENSION,
+        )?;
+        assert_eq!(files, vec![dst.path().join("project/LICENSE-MIT")]);
+
+        let dst = tempdir().unwrap();
+        assert!(copy_dir_all(
+            src.path(),
+            dst.path(),
+            Overwrite::Never,
+            false,
+            false,
+            Symlinks::Recreate,
+            DEFAULT_RENDER_EXTENSION,
+        )
+        .is_err());
+
+        fs::remove_file(src.path().join("docs-LICENSE"))?;
+        let dst = tempdir().unwrap();
+        copy_dir_all(
+            src.path(),
+            dst.path(),
+            Overwrite::Never,
+            false,
+            false,
+            Symlinks::Recreate,
+            DEFAULT_RENDER_EXTENSION,
+        )?;
+        assert_eq!(
+            fs::read_link(dst.path().join("docs/LICENSE"))?,
+            PathBuf::from("../LICENSE-MIT")
+        );
+        assert_eq!(fs::read_to_string(dst.path().join("docs/LICENSE"))?, "MIT");
+        Ok(())
+    }
+
     pub trait PathString {
         fn to_string(&self) -> String;
     }
@@ -0,0 +1,216 @@
+//! Module dealing with the `--list-variables` arg, to inspect the placeholders of a template
+
+use crate::{
+    config::{Config, TemplateSlotsTable},
+    emoji,
+    project_variables::{try_into_template_slots, TemplateSlots, VarInfo},
+    GenerateArgs, OutputFormat,
+};
+use anyhow::Result;
+use console::style;
+use serde::Serialize;
+use serde_json::json;
+
+/// A placeholder as declared in `cargo-generate.toml`
+#[derive(Debug, PartialEq, Serialize)]
+pub struct VariableInfo {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub var_type: &'static str,
+    pub prompt: Option<String>,
+    pub default: Option<serde_json::Value>,
+    pub choices: Option<Vec<String>>,
+    /// The rhai expression of a `type = "derived"` placeholder
+    pub expr: Option<String>,
+    /// The condition of the `[conditional]` block the placeholder is declared in
+    pub condition: Option<String>,
+    /// The condition the placeholder is only prompted for if it is true
+    pub prompt_if: Option<String>,
+}
+
+/// Lists the placeholders of the template, along with its `name` and `description`.
+/// A template without `description` is described by the favorite it has been chosen by, if any.
+pub fn list_variables(
+    template_config: &Config,
+    favorite_description: Option<&str>,
+    args: &GenerateArgs,
+) -> Result<()> {
+    let variables = collect_variables(template_config)?;
+    let template = template_config.template.as_ref();
+    let name = template.and_then(|template| template.name.as_deref());
+    let description = template
+        .and_then(|template| template.description.as_deref())
+        .or(favorite_description);
+
+    if args.output_format == OutputFormat::Json {
+        let listing = json!({
+            "name": name,
+            "description": description,
+            "placeholders": variables,
+        });
+        println!("{}", serde_json::to_string(&listing)?);
+        return Ok(());
+    }
+
+    if let Some(name) = name {
+        println!("{} {} {}", emoji::WRENCH, style("Template:").bold(), name);
+    }
+    if let Some(description) = description {
+        println!("   {}", style(description).dim());
+    }
+
+    if variables.is_empty() {
+        println!(
+            "{} {}",
+            emoji::WARN,
+            style("No placeholders defined").bold().red()
+        );
+        return Ok(());
+    }
+
+    println!("{} {}", emoji::WRENCH, style("Placeholders:").bold());
+    for variable in &variables {
+        println!(
+            "    {} {} ({}){}",
+            emoji::DIAMOND,
+            style(&variable.name).bold(),
+            variable.var_type,
+            variable
+                .prompt
+                .as_ref()
+                .map(|prompt| format!(": {}", prompt))
+                .unwrap_or_default()
+        );
+        if let Some(ref default) = variable.default {
+            println!("        default: {}", default);
+        }
+        if let Some(ref choices) = variable.choices {
+            println!("        choices: {}", choices.join(", "));
+        }
+        if let Some(ref expr) = variable.expr {
+            println!("        expr: {}", expr);
+        }
+        if let Some(ref condition) = variable.condition {
+            println!("        only if: {}", condition);
+        }
+        if let Some(ref prompt_if) = variable.prompt_if {
+            println!("        prompted if: {}", prompt_if);
+        }
+    }
+    println!("{} {}", emoji::SPARKLE, style("Done").bold().green());
+
+    Ok(())
+}
+
+/// Collects all placeholders, the unconditional ones first, each group sorted by name
+fn collect_variables(template_config: &Config) -> Result<Vec<VariableInfo>> {
+    let mut variables = template_config
+        .placeholders
+        .as_ref()
+        .map(|placeholders| table_into_variables(placeholders, None))
+        .transpose()?
+        .unwrap_or_default();
+
+    let mut conditionals = template_config
+        .conditional
+        .iter()
+        .flatten()
+        .filter_map(|(condition, cfg)| cfg.placeholders.as_ref().map(|p| (condition, p)))
+        .collect::<Vec<_>>();
+    conditionals.sort_by_key(|(condition, _)| *condition);
+    for (condition, placeholders) in conditionals {
+        variables.append(&mut table_into_variables(placeholders, Some(condition))?);
+    }
+
+    Ok(variables)
+}
+
+fn table_into_variables(
+    table: &TemplateSlotsTable,
+    condition: Option<&String>,
+) -> Result<Vec<VariableInfo>> {
+    let (slots, derived_entries) = try_into_template_slots(table, None)?;
+
+    let mut variables = slots
+        .into_iter()
+        .map(|slot| slot_into_variable(slot, condition))
+        .chain(derived_entries.into_iter().map(|entry| VariableInfo {
+            name: entry.var_name,
+            var_type: "derived",
+            prompt: None,
+            default: None,
+            choices: None,
+            expr: Some(entry.expr),
+            condition: condition.cloned(),
+            prompt_if: None,
+        }))
+        .collect::<Vec<_>>();
+    variables.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(variables)
+}
+
+fn slot_into_variable(slot: TemplateSlots, condition: Option<&String>) -> VariableInfo {
+    let (var_type, default, choices) = match slot.var_info {
+        VarInfo::Bool { default } => ("bool", default.map(|d| json!(d)), None),
+        VarInfo::String { entry } => ("string", entry.default.map(|d| json!(d)), entry.choices),
+        VarInfo::Number { entry } => ("number", entry.default.map(|d| json!(d)), None),
+        VarInfo::MultiSelect { entry } => (
+            "multiselect",
+            entry.default.map(|d| json!(d)),
+            Some(entry.choices),
+        ),
+    };
+
+    VariableInfo {
+        name: slot.var_name,
+        var_type,
+        prompt: Some(slot.prompt),
+        default,
+        choices,
+        expr: None,
+        condition: condition.cloned(),
+        prompt_if: slot.prompt_if,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn it_collects_conditional_placeholders_after_the_others() {
+        let config = Config::try_from(
+            r#"
+            [placeholders]
+            zeta = { type = "bool", prompt = "Zeta?", default = true }
+            alpha = { type = "string", prompt = "Alpha?", choices = ["a", "b"], default = "a" }
+            upper = { type = "derived", expr = "alpha" }
+
+            [conditional.'zeta == true'.placeholders]
+            port = { type = "number", prompt = "Port?", default = 8080 }
+            host = { type = "string", prompt = "Host?", prompt_if = "port != 80" }
+            "#
+            .to_string(),
+        )
+        .unwrap();
+
+        let variables = collect_variables(&config).unwrap();
+        let names = variables
+            .iter()
+            .map(|v| v.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["alpha", "upper", "zeta", "host", "port"]);
+
+        assert_eq!(variables[0].var_type, "string");
+        assert_eq!(variables[0].default, Some(json!("a")));
+        assert_eq!(variables[0].choices, Some(vec!["a".into(), "b".into()]));
+        assert_eq!(variables[1].expr.as_deref(), Some("alpha"));
+        assert_eq!(variables[3].prompt_if.as_deref(), Some("port != 80"));
+        assert_eq!(variables[4].var_type, "number");
+        assert_eq!(variables[4].condition.as_deref(), Some("zeta == true"));
+        assert_eq!(variables[4].prompt_if, None);
+        assert_eq!(variables[2].condition, None);
+    }
+}
@@ -1,19 +1,164 @@
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+use crate::{emoji, LogFormat};
+
+static DIAGNOSTICS_TO_STDERR: AtomicBool = AtomicBool::new(false);
+static QUIET: AtomicBool = AtomicBool::new(false);
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+static LOG_FORMAT: AtomicU8 = AtomicU8::new(0);
+
+/// Routes all diagnostic output to stderr, to keep stdout free for machine-readable output
+pub fn set_diagnostics_to_stderr(to_stderr: bool) {
+    DIAGNOSTICS_TO_STDERR.store(to_stderr, Ordering::Relaxed);
+}
+
+#[doc(hidden)]
+pub fn diagnostics_to_stderr() -> bool {
+    DIAGNOSTICS_TO_STDERR.load(Ordering::Relaxed)
+}
+
+/// Suppresses [`info!`] lines, only warnings and errors are printed, see `--quiet`
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+#[doc(hidden)]
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Enables [`debug!`] lines, see `--verbose`
+pub fn set_verbose(verbose: bool) {
+    VERBOSE.store(verbose, Ordering::Relaxed);
+}
+
+#[doc(hidden)]
+pub fn is_verbose() -> bool {
+    VERBOSE.load(Ordering::Relaxed)
+}
+
+/// Selects how diagnostic lines are written, see `--log-format`
+pub fn set_log_format(log_format: LogFormat) {
+    LOG_FORMAT.store(log_format as u8, Ordering::Relaxed);
+}
+
+fn log_format() -> LogFormat {
+    match LOG_FORMAT.load(Ordering::Relaxed) {
+        1 => LogFormat::Plain,
+        2 => LogFormat::Json,
+        3 => LogFormat::Log,
+        _ => LogFormat::Human,
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy)]
+pub enum Level {
+    Debug,
+    Info,
+    Warn,
+}
+
+impl Level {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Debug => "debug",
+            Self::Info => "info",
+            Self::Warn => "warn",
+        }
+    }
+}
+
+impl From<Level> for ::log::Level {
+    fn from(level: Level) -> Self {
+        match level {
+            Level::Debug => Self::Debug,
+            Level::Info => Self::Info,
+            Level::Warn => Self::Warn,
+        }
+    }
+}
+
+/// Writes a diagnostic line in the selected [`LogFormat`], the styled `line` as it is for humans.
+/// For any other format, styles and emojis are stripped from it.
+#[doc(hidden)]
+pub fn write(level: Level, line: &str) {
+    let print = |line: &str| {
+        if diagnostics_to_stderr() {
+            eprintln!("{}", line);
+        } else {
+            println!("{}", line);
+        }
+    };
+    match log_format() {
+        LogFormat::Human => print(line),
+        LogFormat::Plain => print(&format!("{}: {}", level.as_str(), unstyled(line))),
+        LogFormat::Json => print(
+            &serde_json::json!({
+                "level": level.as_str(),
+                "message": unstyled(line),
+            })
+            .to_string(),
+        ),
+        LogFormat::Log => ::log::log!(
+            target: "cargo_generate",
+            ::log::Level::from(level),
+            "{}",
+            unstyled(line)
+        ),
+    }
+}
+
+/// The line without colors and emojis, nor the space that separated a leading emoji
+fn unstyled(line: &str) -> String {
+    let line = console::strip_ansi_codes(line);
+    emoji::ALL
+        .iter()
+        .fold(line.into_owned(), |line, emoji| line.replace(emoji.0, ""))
+        .trim_start()
+        .to_string()
+}
+
+/// Prints a diagnostic line, to stdout by default or to stderr if
+/// [`set_diagnostics_to_stderr`] has been enabled
+#[macro_export]
+macro_rules! out {
+    ($($arg:tt)*) => ({
+        $crate::log::write($crate::log::Level::Info, &format!($($arg)*));
+    })
+}
+
 #[macro_export]
 macro_rules! warn {
     ($($arg:tt)*) => ({
-        println!("{} {}",
+        $crate::log::write($crate::log::Level::Warn, &format!("{} {}",
             $crate::emoji::WARN,
             format!($($arg)*)
-        );
+        ));
     })
 }
 
 #[macro_export]
 macro_rules! info {
     ($($arg:tt)*) => ({
-        println!("{} {}",
-            $crate::emoji::INFO,
-            format!($($arg)*)
-        );
+        if !$crate::log::is_quiet() {
+            $crate::log::write($crate::log::Level::Info, &format!("{} {}",
+                $crate::emoji::INFO,
+                format!($($arg)*)
+            ));
+        }
+    })
+}
+
+/// Prints a line only with `--verbose`, for details like why a file has been skipped
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)*) => ({
+        if $crate::log::is_verbose() {
+            $crate::log::write($crate::log::Level::Debug, &format!("{} {}",
+                $crate::emoji::DIAMOND,
+                format!($($arg)*)
+            ));
+        }
     })
 }
@@ -1,10 +1,27 @@
-use anyhow::Result;
-use cargo_generate::{generate, Cli};
+use cargo_generate::{check, generate, Cli, ErrorKind, GenerateCommand};
 use clap::Parser;
+use std::{io, process};
 
-fn main() -> Result<()> {
-    let Cli::Generate(args) = Cli::parse();
-    generate(args)?;
-
-    Ok(())
+fn main() {
+    let Cli::Generate(mut args) = Cli::parse();
+    let result = match args.command.take() {
+        Some(GenerateCommand::Completions { shell }) => {
+            clap_complete::generate(
+                shell,
+                &mut cargo_generate::command(),
+                "cargo-generate",
+                &mut io::stdout(),
+            );
+            return;
+        }
+        Some(GenerateCommand::Check(check_args)) => {
+            args.template_path = check_args.template_path;
+            check(args)
+        }
+        None => generate(args),
+    };
+    if let Err(error) = result {
+        eprintln!("Error: {:?}", error);
+        process::exit(ErrorKind::of(&error).map_or(1, ErrorKind::exit_code));
+    }
 }
@@ -1,7 +1,95 @@
-use indicatif::{MultiProgress, ProgressStyle};
+use console::Term;
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 
-pub fn new() -> MultiProgress {
-    MultiProgress::new()
+use crate::info;
+
+/// How the progress of processing the template files is shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressMode {
+    /// An animated spinner per file
+    Animated,
+    /// One line per finished file, for CI logs and pipes
+    Plain,
+    /// Nothing at all, see `--no-progress`
+    Hidden,
+}
+
+impl ProgressMode {
+    /// Spinners only make sense on a terminal, otherwise their escape codes clutter the output
+    pub fn detect(no_progress: bool) -> Self {
+        if no_progress {
+            Self::Hidden
+        } else if Term::stdout().is_term() {
+            Self::Animated
+        } else {
+            Self::Plain
+        }
+    }
+}
+
+/// The progress of all files, a stand-in for `MultiProgress` that can also log plain lines
+pub struct Progress {
+    mode: ProgressMode,
+    multi: MultiProgress,
+}
+
+impl Progress {
+    pub fn add(&self, prefix: String) -> FileProgress {
+        match self.mode {
+            ProgressMode::Animated => {
+                let pb = self.multi.add(ProgressBar::new(50));
+                pb.set_style(spinner());
+                pb.set_prefix(prefix);
+                FileProgress::Bar(pb)
+            }
+            ProgressMode::Plain => FileProgress::Plain(prefix),
+            ProgressMode::Hidden => FileProgress::Hidden,
+        }
+    }
+
+    /// Waits for all bars to finish, returns immediately when no bar has been drawn
+    pub fn join(&self) -> std::io::Result<()> {
+        match self.mode {
+            ProgressMode::Animated => self.multi.join(),
+            ProgressMode::Plain | ProgressMode::Hidden => Ok(()),
+        }
+    }
+}
+
+/// The progress of a single file
+pub enum FileProgress {
+    Bar(ProgressBar),
+    Plain(String),
+    Hidden,
+}
+
+impl FileProgress {
+    pub fn set_message(&self, msg: String) {
+        if let Self::Bar(pb) = self {
+            pb.set_message(msg);
+        }
+    }
+
+    pub fn inc(&self, delta: u64) {
+        if let Self::Bar(pb) = self {
+            pb.inc(delta);
+        }
+    }
+
+    pub fn finish_with_message(&self, msg: String) {
+        match self {
+            Self::Bar(pb) => pb.finish_with_message(msg),
+            Self::Plain(prefix) => info!("{} {}", prefix, msg),
+            Self::Hidden => (),
+        }
+    }
+}
+
+pub fn new(mode: ProgressMode) -> Progress {
+    Progress {
+        mode,
+        multi: MultiProgress::new(),
+    }
 }
 
 pub fn spinner() -> ProgressStyle {
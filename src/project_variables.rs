@@ -1,23 +1,112 @@
-use anyhow::Result;
-use liquid::Object;
-use liquid_core::model::map::Entry;
-use liquid_core::Value;
+use anyhow::{bail, Result};
+use console::style;
+use liquid::{Object, ValueView};
+use liquid_core::{model::Scalar, Value};
 use regex::Regex;
+use rhai::{Dynamic, Engine, EvalAltResult};
+use serde::Serialize;
+use std::{cmp::Ordering, fmt, path::Path, process::Command};
 use thiserror::Error;
 
 use crate::config::{Config, TemplateSlotsTable};
+use crate::template_filters::case_conversion;
+use crate::warn;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TemplateSlots {
     pub(crate) var_name: String,
     pub(crate) var_info: VarInfo,
     pub(crate) prompt: String,
+    /// A rhai expression over other variables, the placeholder is only prompted for if it is true
+    pub(crate) prompt_if: Option<String>,
+    /// The section the placeholder is prompted in, its name is printed as header before the first prompt
+    pub(crate) group: Option<String>,
 }
 
 #[derive(Debug, Clone)]
 pub enum VarInfo {
     Bool { default: Option<bool> },
     String { entry: Box<StringEntry> },
+    Number { entry: Box<NumberEntry> },
+    MultiSelect { entry: Box<MultiSelectEntry> },
+}
+
+impl VarInfo {
+    /// The default as liquid value, if there is one
+    pub(crate) fn default_value(&self) -> Option<Value> {
+        match self {
+            Self::Bool { default } => default.map(|d| Value::Scalar(d.into())),
+            Self::String { entry } => entry.default.clone().map(|d| Value::Scalar(d.into())),
+            Self::Number { entry } => entry.default.map(Number::into_value),
+            Self::MultiSelect { entry } => entry
+                .default
+                .clone()
+                .map(|d| Value::Array(d.into_iter().map(Value::scalar).collect())),
+        }
+    }
+}
+
+/// whole numbers end up as integers, so they render without a trailing `.0`
+pub fn number_into_value(number: f64) -> Value {
+    if number.fract() == 0.0 && number.abs() < i64::MAX as f64 {
+        Value::Scalar((number as i64).into())
+    } else {
+        Value::Scalar(number.into())
+    }
+}
+
+/// The number of a placeholder, whole numbers stay `i64` as `f64` rounds the ones beyond 2^53
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum Number {
+    Integer(i64),
+    Float(f64),
+}
+
+impl Number {
+    /// Parses `input` as `i64` first, and only then as `f64`.
+    /// `NaN` and the infinities are no numbers a template can work with
+    fn parse(input: &str) -> Option<Self> {
+        input.parse::<i64>().map(Self::Integer).ok().or_else(|| {
+            input
+                .parse::<f64>()
+                .ok()
+                .filter(|value| value.is_finite())
+                .map(Self::Float)
+        })
+    }
+
+    pub(crate) fn into_value(self) -> Value {
+        match self {
+            Self::Integer(value) => Value::Scalar(value.into()),
+            Self::Float(value) => number_into_value(value),
+        }
+    }
+
+    fn as_f64(self) -> f64 {
+        match self {
+            Self::Integer(value) => value as f64,
+            Self::Float(value) => value,
+        }
+    }
+}
+
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Self::Integer(a), Self::Integer(b)) => a.partial_cmp(b),
+            _ => self.as_f64().partial_cmp(&other.as_f64()),
+        }
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Integer(value) => value.fmt(f),
+            Self::Float(value) => value.fmt(f),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -25,6 +114,236 @@ pub struct StringEntry {
     pub(crate) default: Option<String>,
     pub(crate) choices: Option<Vec<String>>,
     pub(crate) regex: Option<Regex>,
+    /// Explains the format `regex` expects, shown when a value doesn't match
+    pub(crate) regex_hint: Option<String>,
+    /// A command whose output lines are the choices, in place of the static `choices`
+    pub(crate) choices_command: Option<Vec<String>>,
+    /// The value is composed in the `$EDITOR` of the user, for multi-line content
+    pub(crate) editor: bool,
+    /// Normalizes the input, before it's validated
+    pub(crate) transform: Option<Transform>,
+}
+
+/// How the input of a string placeholder is normalized, see [`StringEntry::transform`]
+#[derive(Debug, Clone)]
+pub enum Transform {
+    /// One of the case conversions of the filters, like `kebab_case`
+    Case(fn(&str) -> String),
+    /// A rhai expression, the input is available as `value`
+    Expression(String),
+}
+
+impl StringEntry {
+    /// Applies the `transform`, if any, to the input of the user
+    pub(crate) fn transform(
+        &self,
+        var_name: &str,
+        value: String,
+    ) -> Result<String, ConversionError> {
+        match &self.transform {
+            None => Ok(value),
+            Some(Transform::Case(convert)) => Ok(convert(&value)),
+            Some(Transform::Expression(expr)) => {
+                let mut scope = rhai::Scope::new();
+                scope.push("value", value);
+                Engine::new()
+                    .eval_expression_with_scope::<Dynamic>(&mut scope, expr)
+                    .map(|value| value.to_string())
+                    .map_err(|e| ConversionError::InvalidExpression {
+                        var_name: var_name.into(),
+                        error: e.to_string(),
+                    })
+            }
+        }
+    }
+
+    /// Checks `value` against the `regex`, if any
+    pub(crate) fn check_regex(&self, var_name: &str, value: &str) -> Result<(), ConversionError> {
+        match &self.regex {
+            Some(regex) if !regex.is_match(value) => Err(self.regex_hint.as_ref().map_or_else(
+                || ConversionError::RegexDoesntMatchField {
+                    var_name: var_name.into(),
+                    field: "value".into(),
+                },
+                |hint| ConversionError::RegexDoesntMatchHint {
+                    var_name: var_name.into(),
+                    value: value.into(),
+                    hint: hint.clone(),
+                },
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl TemplateSlots {
+    /// Runs the `choices_command` of a string placeholder in `dir`, its output lines become the choices.
+    ///
+    /// Just like hooks, the command only runs with `allow_commands`. Otherwise, or if the command fails,
+    /// the static `choices` are kept.
+    pub(crate) fn with_command_choices(&self, dir: &Path, allow_commands: bool) -> Self {
+        let mut slot = self.clone();
+        let entry = match slot.var_info {
+            VarInfo::String { ref mut entry } => entry,
+            _ => return slot,
+        };
+        let command = match entry.choices_command {
+            Some(ref command) => command,
+            None => return slot,
+        };
+        if !allow_commands {
+            warn!(
+                "{} {}",
+                style(format!(
+                    "The choices of `{}` are loaded by a system command, which is not allowed.",
+                    slot.var_name
+                ))
+                .bold(),
+                style("Review the template and use `--allow-commands` to allow it.").yellow()
+            );
+            return slot;
+        }
+
+        match run_choices_command(dir, command) {
+            Ok(choices) => {
+                let choices = choices
+                    .into_iter()
+                    .filter(|choice| entry.check_regex(&slot.var_name, choice).is_ok())
+                    .collect::<Vec<_>>();
+                if !choices.is_empty() {
+                    entry.choices = Some(choices);
+                }
+            }
+            Err(e) => warn!(
+                "{} {}",
+                style(format!(
+                    "Failed to load the choices of `{}`, falling back to its static choices:",
+                    slot.var_name
+                ))
+                .bold(),
+                style(e).yellow()
+            ),
+        }
+        slot
+    }
+}
+
+/// Runs `command` in `dir`, every non-empty line of its output is a choice
+fn run_choices_command(dir: &Path, command: &[String]) -> Result<Vec<String>> {
+    let (program, args) = match command.split_first() {
+        Some(split) => split,
+        None => bail!("the command is empty"),
+    };
+    let output = Command::new(program).args(args).current_dir(dir).output()?;
+    if !output.status.success() {
+        bail!("`{}` returned {}", command.join(" "), output.status);
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+#[derive(Debug, Clone)]
+pub struct NumberEntry {
+    pub(crate) default: Option<Number>,
+    pub(crate) min: Option<Number>,
+    pub(crate) max: Option<Number>,
+}
+
+impl NumberEntry {
+    /// Parses the user input and makes sure it is within `min` and `max`
+    pub(crate) fn parse(&self, var_name: &str, input: &str) -> Result<Number, ConversionError> {
+        let value = Number::parse(input.trim()).ok_or_else(|| ConversionError::NotANumber {
+            var_name: var_name.into(),
+            value: input.into(),
+        })?;
+        self.check_range(var_name, value)?;
+        Ok(value)
+    }
+
+    fn check_range(&self, var_name: &str, value: Number) -> Result<(), ConversionError> {
+        let too_small = self.min.is_some_and(|min| value < min);
+        let too_big = self.max.is_some_and(|max| value > max);
+        if too_small || too_big {
+            return Err(ConversionError::NumberOutOfRange {
+                var_name: var_name.into(),
+                value: value.to_string(),
+                range: self.range(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Human readable representation of the allowed range, like `1..=10`
+    pub(crate) fn range(&self) -> String {
+        format!(
+            "{}..={}",
+            self.min.map(|min| min.to_string()).unwrap_or_default(),
+            self.max.map(|max| max.to_string()).unwrap_or_default()
+        )
+    }
+}
+
+/// A placeholder of `type = "derived"`, computed from other placeholders instead of prompted
+#[derive(Debug, Clone, PartialEq)]
+pub struct DerivedEntry {
+    pub(crate) var_name: String,
+    pub(crate) expr: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct MultiSelectEntry {
+    pub(crate) default: Option<Vec<String>>,
+    pub(crate) choices: Vec<String>,
+}
+
+impl MultiSelectEntry {
+    /// Parses a comma separated list (`a,b`) or a TOML array (`["a", "b"]`)
+    /// and makes sure every selected value is one of the choices
+    pub(crate) fn parse(
+        &self,
+        var_name: &str,
+        input: &str,
+    ) -> Result<Vec<String>, ConversionError> {
+        let input = input.trim();
+        let values = if input.starts_with('[') {
+            toml::from_str::<toml::value::Table>(&format!("values = {}", input))
+                .ok()
+                .and_then(|mut table| table.remove("values"))
+                .and_then(|values| match values {
+                    toml::Value::Array(arr) => arr
+                        .into_iter()
+                        .map(|v| v.as_str().map(String::from))
+                        .collect::<Option<Vec<_>>>(),
+                    _ => None,
+                })
+                .ok_or_else(|| ConversionError::WrongTypeParameter {
+                    var_name: var_name.into(),
+                    parameter: "value".into(),
+                    correct_type: "String Array".into(),
+                })?
+        } else {
+            input
+                .split(',')
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(String::from)
+                .collect()
+        };
+
+        if let Some(invalid) = values.iter().find(|v| !self.choices.contains(v)) {
+            return Err(ConversionError::InvalidChoice {
+                var_name: var_name.into(),
+                value: invalid.clone(),
+                choices: self.choices.clone(),
+            });
+        }
+        Ok(values)
+    }
 }
 
 #[derive(Error, Debug, PartialEq)]
@@ -48,37 +367,107 @@ pub enum ConversionError {
         choices: Vec<String>,
     },
     #[error(
-        "invalid type for variable `{var_name}`: `{value}` possible values are `bool` and `string`"
+        "invalid type for variable `{var_name}`: `{value}` possible values are `bool`, `string`, `number`, `multiselect` and `derived`"
     )]
     InvalidVariableType { var_name: String, value: String },
     #[error("bool type does not support `choices` field")]
     ChoicesOnBool { var_name: String },
     #[error("bool type does not support `regex` field")]
     RegexOnBool { var_name: String },
+    #[error("only string type supports `choices_command`, but `{var_name}` is not a string")]
+    ChoicesCommandOnNonString { var_name: String },
+    #[error("only string type without `choices` supports `editor`, but `{var_name}` is not such")]
+    EditorOnNonString { var_name: String },
+    #[error(
+        "only string type without `choices` supports `transform`, but `{var_name}` is not such"
+    )]
+    TransformOnNonString { var_name: String },
+    #[error("number type does not support `choices` field")]
+    ChoicesOnNumber { var_name: String },
+    #[error("number type does not support `regex` field")]
+    RegexOnNumber { var_name: String },
+    #[error("multiselect type does not support `regex` field")]
+    RegexOnMultiSelect { var_name: String },
+    #[error("multiselect type requires a `choices` field for `{var_name}`")]
+    MissingChoices { var_name: String },
+    #[error("multiselect choice `{choice}` of `{var_name}` can't contain a `,`, as it separates the selected values")]
+    CommaInMultiSelectChoice { var_name: String, choice: String },
+    #[error("`{value}` is not a valid value in choices array `{choices:?}` for `{var_name}`")]
+    InvalidChoice {
+        var_name: String,
+        value: String,
+        choices: Vec<String>,
+    },
+    #[error("only number type supports `min` and `max` fields, but `{var_name}` is not a number")]
+    RangeOnNonNumber { var_name: String },
+    #[error("`min` is greater than `max` for `{var_name}`")]
+    InvalidRange { var_name: String },
+    #[error("`{value}` is not a valid number for `{var_name}`")]
+    NotANumber { var_name: String, value: String },
+    #[error("`{value}` is out of the allowed range `{range}` for `{var_name}`")]
+    NumberOutOfRange {
+        var_name: String,
+        value: String,
+        range: String,
+    },
+    #[error("derived type requires an `expr` field for `{var_name}`")]
+    MissingExpression { var_name: String },
+    #[error("derived type only supports `type` and `expr` fields, but `{field}` is set for `{var_name}`")]
+    FieldOnDerived { var_name: String, field: String },
+    #[error("expression of `{var_name}` failed to evaluate. {error}")]
+    InvalidExpression { var_name: String, error: String },
+    #[error("expression of `{var_name}` references the unknown variable `{reference}`")]
+    MissingReference { var_name: String, reference: String },
+    #[error("derived variables `{var_names:?}` depend on each other in a cycle")]
+    CyclicDerivation { var_names: Vec<String> },
+    #[error("`prompt_if` conditions of `{var_names:?}` depend on each other in a cycle")]
+    CyclicCondition { var_names: Vec<String> },
     #[error("variable `{var_name}` was missing in config file running on silent mode")]
     MissingPlaceholderVariable { var_name: String },
     #[error("field `{field}` of variable `{var_name}` does not match configured regex")]
     RegexDoesntMatchField { var_name: String, field: String },
+    #[error("`{value}` is not a valid value for `{var_name}`: {hint}")]
+    RegexDoesntMatchHint {
+        var_name: String,
+        value: String,
+        hint: String,
+    },
+    #[error("`regex_hint` of `{var_name}` requires a `regex` field")]
+    RegexHintWithoutRegex { var_name: String },
     #[error("regex of `{var_name}` is not a valid regex. {error}")]
     InvalidRegex {
         var_name: String,
         regex: String,
         error: regex::Error,
     },
-    #[error("placeholder `{var_name}` is not valid as you can't override `project-name`, `crate_name`, `crate_type`, `authors` and `os-arch`")]
+    #[error("`default_by_crate_type` of `{var_name}` has the unknown crate type `{crate_type}`, only `bin` and `lib` are supported")]
+    InvalidCrateTypeDefault {
+        var_name: String,
+        crate_type: String,
+    },
+    #[error(
+        "placeholder `{var_name}` is not valid as you can't override {}",
+        reserved_names()
+    )]
     InvalidPlaceholderName { var_name: String },
+    #[error("derived or conditional placeholder `{var_name}` is not valid as it is a keyword of rhai, which evaluates their expressions")]
+    RhaiKeywordName { var_name: String },
 }
 
 #[derive(Debug, Clone, PartialEq)]
 enum SupportedVarValue {
     Bool(bool),
     String(String),
+    Number(Number),
+    MultiSelect(Vec<String>),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum SupportedVarType {
     Bool,
     String,
+    Number,
+    MultiSelect,
 }
 
 const RESERVED_NAMES: [&str; 7] = [
@@ -91,56 +480,477 @@ const RESERVED_NAMES: [&str; 7] = [
     "is_init",
 ];
 
+/// The [`RESERVED_NAMES`] as listed in messages, like "`authors`, `os-arch` and `is_init`"
+fn reserved_names() -> String {
+    let quoted = RESERVED_NAMES.map(|name| format!("`{name}`"));
+    let (last, others) = quoted.split_last().expect("there are reserved names");
+    format!("{} and {last}", others.join(", "))
+}
+
+/// Built-in variables that give way to a placeholder of the same name, as templates declared them before they were built in
+const DECLARABLE_BUILTINS: [&str; 5] = ["username", "email", "template", "now", "project"];
+
+/// Keywords and reserved words of rhai, these can't be used as variables in expressions.
+///
+/// Only derived placeholders and ones with a `prompt_if` are checked against them, all others
+/// are reachable in expressions through the [`VARIABLES_MAP`] anyway.
+const RHAI_KEYWORDS: [&str; 63] = [
+    "true",
+    "false",
+    "let",
+    "const",
+    "if",
+    "else",
+    "switch",
+    "do",
+    "while",
+    "until",
+    "loop",
+    "for",
+    "in",
+    "break",
+    "continue",
+    "return",
+    "throw",
+    "try",
+    "catch",
+    "import",
+    "export",
+    "as",
+    "private",
+    "fn",
+    "Fn",
+    "call",
+    "curry",
+    "this",
+    "is_def_var",
+    "is_def_fn",
+    "is_shared",
+    "global",
+    "print",
+    "debug",
+    "type_of",
+    "eval",
+    "is",
+    "var",
+    "static",
+    "shared",
+    "goto",
+    "exit",
+    "match",
+    "case",
+    "public",
+    "protected",
+    "new",
+    "use",
+    "with",
+    "module",
+    "package",
+    "super",
+    "thread",
+    "spawn",
+    "go",
+    "await",
+    "async",
+    "sync",
+    "yield",
+    "default",
+    "void",
+    "null",
+    "nil",
+];
+
+/// The map holding all variables in rhai expressions, for names that are no identifiers like `variables["project-name"]`
+const VARIABLES_MAP: &str = "variables";
+
+fn check_placeholder_name(key: &str) -> Result<(), ConversionError> {
+    if RESERVED_NAMES.contains(&key) {
+        return Err(ConversionError::InvalidPlaceholderName {
+            var_name: key.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// The name of a placeholder that is part of rhai expressions, a derived or conditional one, must not be a keyword
+fn check_expression_placeholder_name(key: &str) -> Result<(), ConversionError> {
+    if RHAI_KEYWORDS.contains(&key) {
+        return Err(ConversionError::RhaiKeywordName {
+            var_name: key.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Takes the built-in variables out of the `template_object` which the template declares a placeholder for,
+/// top-level or conditional, so the placeholder is asked for instead of being skipped as already known.
+///
+/// Put back the ones no placeholder took with [`restore_builtins`].
+pub fn take_overridden_builtins(template_object: &mut Object, template_config: &Config) -> Object {
+    let declared = template_config
+        .placeholders
+        .iter()
+        .flat_map(|placeholders| placeholders.0.keys())
+        .map(String::as_str)
+        .chain(template_config.conditional_placeholder_names())
+        .collect::<Vec<_>>();
+
+    DECLARABLE_BUILTINS
+        .iter()
+        .filter(|name| declared.contains(name))
+        .filter_map(|name| Some(((*name).into(), template_object.remove(*name)?)))
+        .collect()
+}
+
+/// Puts back the `builtins` of [`take_overridden_builtins`] whose placeholder stayed unset
+pub fn restore_builtins(template_object: &mut Object, builtins: Object) {
+    for (name, value) in builtins {
+        if !template_object.contains_key(name.as_str()) {
+            template_object.insert(name, value);
+        }
+    }
+}
+
 pub fn fill_project_variables<F>(
     mut template_object: Object,
     template_config: &Config,
     value_provider: F,
 ) -> Result<Object>
 where
-    F: Fn(&TemplateSlots) -> Result<Value>,
+    F: Fn(&TemplateSlots, &mut Option<String>) -> Result<Value>,
 {
-    let template_slots = template_config
+    let crate_type = template_object
+        .get("crate_type")
+        .map(|crate_type| crate_type.to_kstr().to_string());
+    let (template_slots, derived_entries) = template_config
         .placeholders
         .as_ref()
-        .map(try_into_template_slots)
-        .unwrap_or_else(|| Ok(Vec::new()))?;
-
-    for slot in template_slots {
-        let key = slot.var_name.clone();
-
-        match template_object.entry(key) {
-            Entry::Occupied(_) => (), // we already have the value from the config file
-            Entry::Vacant(entry) => {
-                // we don't have the file from the config and we can ask for it
-                let value = value_provider(&slot)?;
-                entry.insert(value);
+        .map(|placeholders| try_into_template_slots(placeholders, crate_type.as_deref()))
+        .unwrap_or_else(|| Ok((Vec::new(), Vec::new())))?;
+
+    fill_slots(&mut template_object, template_slots, value_provider)?;
+
+    let derived_entries = derived_entries
+        .into_iter()
+        .filter(|entry| !template_object.contains_key(entry.var_name.as_str()))
+        .collect();
+    fill_derived_variables(&mut template_object, derived_entries)?;
+
+    Ok(template_object)
+}
+
+/// Asks the `value_provider` for all placeholders without a value, but only for the ones whose `prompt_if`
+/// is true. A placeholder that is skipped takes its default, or stays unset.
+///
+/// Like derived placeholders, a condition referencing a placeholder without a value yet is retried after the others.
+/// The group of the last prompt is passed along, so the header of a group is printed once for its placeholders.
+fn fill_slots<F>(
+    template_object: &mut Object,
+    mut pending: Vec<TemplateSlots>,
+    value_provider: F,
+) -> Result<()>
+where
+    F: Fn(&TemplateSlots, &mut Option<String>) -> Result<Value>,
+{
+    let mut skipped = Vec::new();
+    let mut prompted_group = None;
+    while !pending.is_empty() {
+        let pending_names = pending
+            .iter()
+            .map(|slot| slot.var_name.clone())
+            .collect::<Vec<_>>();
+        let mut deferred = Vec::new();
+
+        for slot in pending {
+            if template_object.contains_key(slot.var_name.as_str()) {
+                continue; // we already have the value from the config file
+            }
+            match eval_prompt_if(template_object, &slot, &pending_names, &skipped)? {
+                Some(true) => {
+                    let value = value_provider(&slot, &mut prompted_group)?;
+                    template_object.insert(slot.var_name.into(), value);
+                }
+                Some(false) => match slot.var_info.default_value() {
+                    Some(default) => {
+                        template_object.insert(slot.var_name.into(), default);
+                    }
+                    None => skipped.push(slot.var_name),
+                },
+                None => deferred.push(slot),
             }
         }
+
+        if deferred.len() == pending_names.len() {
+            anyhow::bail!(ConversionError::CyclicCondition {
+                var_names: pending_names,
+            });
+        }
+        pending = deferred;
     }
 
-    Ok(template_object)
+    Ok(())
+}
+
+/// Returns `None` if the condition depends on a placeholder that has no value yet.
+/// Placeholders that have been skipped without default are `()` in the condition.
+fn eval_prompt_if(
+    template_object: &Object,
+    slot: &TemplateSlots,
+    pending_names: &[String],
+    skipped: &[String],
+) -> Result<Option<bool>, ConversionError> {
+    let condition = match slot.prompt_if {
+        Some(ref condition) => condition,
+        None => return Ok(Some(true)),
+    };
+
+    let engine = rhai_engine(template_object, skipped);
+    match engine.eval_expression::<bool>(condition) {
+        Ok(prompt) => Ok(Some(prompt)),
+        Err(e) => match *e {
+            EvalAltResult::ErrorVariableNotFound(ref reference, _)
+                if pending_names.contains(reference) =>
+            {
+                Ok(None)
+            }
+            EvalAltResult::ErrorVariableNotFound(reference, _) => {
+                Err(ConversionError::MissingReference {
+                    var_name: slot.var_name.clone(),
+                    reference,
+                })
+            }
+            e => Err(ConversionError::InvalidExpression {
+                var_name: slot.var_name.clone(),
+                error: e.to_string(),
+            }),
+        },
+    }
+}
+
+/// A rhai engine resolving variables from the `template_object`, the `unset` ones resolve to `()`.
+///
+/// All of them are in the [`VARIABLES_MAP`] as well, unless a variable of that name shadows it.
+pub fn rhai_engine(template_object: &Object, unset: &[String]) -> Engine {
+    let mut engine = Engine::new();
+    #[allow(deprecated)]
+    engine.on_var({
+        let liqobj = template_object.clone();
+        let unset = unset.to_vec();
+        move |name, _, _| {
+            Ok(match liqobj.get(name) {
+                Some(value) => liquid_value_into_dynamic(value),
+                None if unset.iter().any(|unset| unset == name) => Some(Dynamic::UNIT),
+                None if name == VARIABLES_MAP => Some(variables_map(&liqobj, &unset)),
+                None => None,
+            })
+        }
+    });
+    engine
+}
+
+fn variables_map(template_object: &Object, unset: &[String]) -> Dynamic {
+    let mut map: rhai::Map = template_object
+        .iter()
+        .filter_map(|(name, value)| Some((name.as_str().into(), liquid_value_into_dynamic(value)?)))
+        .collect();
+    map.extend(
+        unset
+            .iter()
+            .map(|name| (name.as_str().into(), Dynamic::UNIT)),
+    );
+    Dynamic::from_map(map)
+}
+
+/// Evaluates the `expr` of all derived placeholders with rhai, in dependency order.
+///
+/// An expression referencing a derived placeholder that has not been evaluated yet is retried
+/// after the others, until no more progress can be made, which means there is a cycle.
+fn fill_derived_variables(
+    template_object: &mut Object,
+    mut pending: Vec<DerivedEntry>,
+) -> Result<(), ConversionError> {
+    while !pending.is_empty() {
+        let pending_names = pending
+            .iter()
+            .map(|entry| entry.var_name.clone())
+            .collect::<Vec<_>>();
+        let mut deferred = Vec::new();
+
+        for entry in pending {
+            match eval_derived(template_object, &entry, &pending_names)? {
+                Some(value) => {
+                    template_object.insert(entry.var_name.into(), value);
+                }
+                None => deferred.push(entry),
+            }
+        }
+
+        if deferred.len() == pending_names.len() {
+            return Err(ConversionError::CyclicDerivation {
+                var_names: pending_names,
+            });
+        }
+        pending = deferred;
+    }
+
+    Ok(())
 }
 
-fn try_into_template_slots(
+/// Returns `None` if the expression depends on a derived placeholder that is not evaluated yet
+fn eval_derived(
+    template_object: &Object,
+    entry: &DerivedEntry,
+    pending_names: &[String],
+) -> Result<Option<Value>, ConversionError> {
+    let engine = rhai_engine(template_object, &[]);
+    match engine.eval_expression::<Dynamic>(&entry.expr) {
+        Ok(value) => Ok(Some(dynamic_into_value(value))),
+        Err(e) => match *e {
+            EvalAltResult::ErrorVariableNotFound(ref reference, _)
+                if pending_names.contains(reference) =>
+            {
+                Ok(None)
+            }
+            EvalAltResult::ErrorVariableNotFound(reference, _) => {
+                Err(ConversionError::MissingReference {
+                    var_name: entry.var_name.clone(),
+                    reference,
+                })
+            }
+            e => Err(ConversionError::InvalidExpression {
+                var_name: entry.var_name.clone(),
+                error: e.to_string(),
+            }),
+        },
+    }
+}
+
+/// Converts a liquid value into the matching rhai type, so expressions can do arithmetic,
+/// compare numbers and test membership in arrays. Objects have no rhai counterpart.
+pub fn liquid_value_into_dynamic(value: &Value) -> Option<Dynamic> {
+    match value {
+        Value::Scalar(scalar) => match scalar.type_name() {
+            "boolean" => scalar.to_bool().map(Dynamic::from_bool),
+            "whole number" => scalar.to_integer().map(Dynamic::from_int),
+            "fractional number" => scalar.to_float().map(Dynamic::from_float),
+            _ => Some(scalar.to_kstr().as_str().into()),
+        },
+        Value::Array(values) => Some(Dynamic::from_array(
+            values
+                .iter()
+                .filter_map(liquid_value_into_dynamic)
+                .collect(),
+        )),
+        _ => None,
+    }
+}
+
+fn dynamic_into_value(value: Dynamic) -> Value {
+    let scalar = value
+        .as_bool()
+        .map(Scalar::from)
+        .or_else(|_| value.as_int().map(Scalar::from))
+        .or_else(|_| value.as_float().map(Scalar::from))
+        .unwrap_or_else(|_| value.to_string().into());
+    Value::Scalar(scalar)
+}
+
+/// The defaults of `default_by_crate_type` are taken for the `crate_type`, if it is known
+pub fn try_into_template_slots(
     TemplateSlotsTable(table): &TemplateSlotsTable,
-) -> Result<Vec<TemplateSlots>, ConversionError> {
+    crate_type: Option<&str>,
+) -> Result<(Vec<TemplateSlots>, Vec<DerivedEntry>), ConversionError> {
     let mut slots = Vec::with_capacity(table.len());
+    let mut derived_entries = Vec::new();
     for (key, values) in table.iter() {
-        slots.push(try_key_value_into_slot(key, values)?);
+        match try_key_value_into_derived(key, values)? {
+            Some(derived) => derived_entries.push(derived),
+            None => slots.push((
+                extract_order(key, values.get("order"))?,
+                try_key_value_into_slot(key, values, crate_type)?,
+            )),
+        }
     }
-    Ok(slots)
+
+    // placeholders with an `order` are prompted first, the sort is stable so ties keep their declaration order
+    slots.sort_by_key(|(order, _)| (order.is_none(), *order));
+    let slots = group_slots(slots.into_iter().map(|(_, slot)| slot));
+    Ok((slots, derived_entries))
 }
 
-fn try_key_value_into_slot(
+/// Moves the placeholders of a group right after the first one of that group, so a section is prompted at once
+fn group_slots(slots: impl Iterator<Item = TemplateSlots>) -> Vec<TemplateSlots> {
+    let mut sections: Vec<Vec<TemplateSlots>> = Vec::new();
+    for slot in slots {
+        let section = slot.group.as_ref().and_then(|group| {
+            sections
+                .iter_mut()
+                .find(|section| section[0].group.as_ref() == Some(group))
+        });
+        match section {
+            Some(section) => section.push(slot),
+            None => sections.push(vec![slot]),
+        }
+    }
+    sections.into_iter().flatten().collect()
+}
+
+fn extract_order(
+    var_name: &str,
+    table_entry: Option<&toml::Value>,
+) -> Result<Option<i64>, ConversionError> {
+    match table_entry {
+        None => Ok(None),
+        Some(toml::Value::Integer(order)) => Ok(Some(*order)),
+        Some(_) => Err(ConversionError::WrongTypeParameter {
+            var_name: var_name.into(),
+            parameter: "order".into(),
+            correct_type: "Integer".into(),
+        }),
+    }
+}
+
+/// Returns `None` if the placeholder is not of `type = "derived"`
+fn try_key_value_into_derived(
     key: &str,
     values: &toml::Value,
-) -> Result<TemplateSlots, ConversionError> {
-    if RESERVED_NAMES.contains(&key) {
-        return Err(ConversionError::InvalidPlaceholderName {
-            var_name: key.to_string(),
+) -> Result<Option<DerivedEntry>, ConversionError> {
+    let table = match values.as_table() {
+        Some(table) if table.get("type").and_then(toml::Value::as_str) == Some("derived") => table,
+        _ => return Ok(None),
+    };
+    check_placeholder_name(key)?;
+    check_expression_placeholder_name(key)?;
+    if let Some(field) = table.keys().find(|k| *k != "type" && *k != "expr") {
+        return Err(ConversionError::FieldOnDerived {
+            var_name: key.into(),
+            field: field.clone(),
         });
     }
 
+    match table.get("expr") {
+        Some(toml::Value::String(expr)) => Ok(Some(DerivedEntry {
+            var_name: key.into(),
+            expr: expr.clone(),
+        })),
+        Some(_) => Err(ConversionError::WrongTypeParameter {
+            var_name: key.into(),
+            parameter: "expr".into(),
+            correct_type: "String".into(),
+        }),
+        None => Err(ConversionError::MissingExpression {
+            var_name: key.into(),
+        }),
+    }
+}
+
+fn try_key_value_into_slot(
+    key: &str,
+    values: &toml::Value,
+    crate_type: Option<&str>,
+) -> Result<TemplateSlots, ConversionError> {
+    check_placeholder_name(key)?;
+
     let table = values
         .as_table()
         .ok_or(ConversionError::InvalidPlaceholderFormat {
@@ -149,15 +959,35 @@ fn try_key_value_into_slot(
 
     let var_type = extract_type(key, table.get("type"))?;
     let regex = extract_regex(key, var_type, table.get("regex"))?;
+    let regex_hint = extract_regex_hint(key, regex.as_ref(), table.get("regex_hint"))?;
     let prompt = extract_prompt(key, table.get("prompt"))?;
+    let prompt_if = extract_prompt_if(key, table.get("prompt_if"))?;
+    if prompt_if.is_some() {
+        check_expression_placeholder_name(key)?;
+    }
+    let group = extract_group(key, table.get("group"))?;
     let choices = extract_choices(key, var_type, regex.as_ref(), table.get("choices"))?;
-    let default_choice = extract_default(
-        key,
-        var_type,
-        regex.as_ref(),
-        table.get("default"),
-        choices.as_ref(),
-    )?;
+    let choices_command = extract_choices_command(key, var_type, table.get("choices_command"))?;
+    let (min, max) = extract_range(key, var_type, table.get("min"), table.get("max"))?;
+    let editor = extract_editor(key, var_type, choices.as_ref(), table.get("editor"))?;
+    let transform = extract_transform(key, var_type, choices.as_ref(), table.get("transform"))?;
+    let crate_type_defaults = extract_crate_type_defaults(key, table.get("default_by_crate_type"))?;
+    for default in crate_type_defaults
+        .iter()
+        .flat_map(|defaults| defaults.values())
+    {
+        extract_default(
+            key,
+            var_type,
+            regex.as_ref(),
+            Some(default),
+            choices.as_ref(),
+        )?;
+    }
+    let default = crate_type
+        .and_then(|crate_type| crate_type_defaults?.get(crate_type))
+        .or_else(|| table.get("default"));
+    let default_choice = extract_default(key, var_type, regex.as_ref(), default, choices.as_ref())?;
 
     let var_info = match (var_type, default_choice) {
         (SupportedVarType::Bool, Some(SupportedVarValue::Bool(value))) => VarInfo::Bool {
@@ -168,14 +998,50 @@ fn try_key_value_into_slot(
                 default: Some(value),
                 choices,
                 regex,
+                regex_hint,
+                choices_command,
+                editor,
+                transform,
             }),
         },
+        (SupportedVarType::Number, Some(SupportedVarValue::Number(value))) => {
+            let entry = NumberEntry {
+                default: Some(value),
+                min,
+                max,
+            };
+            entry.check_range(key, value)?;
+            VarInfo::Number {
+                entry: Box::new(entry),
+            }
+        }
         (SupportedVarType::Bool, None) => VarInfo::Bool { default: None },
         (SupportedVarType::String, None) => VarInfo::String {
             entry: Box::new(StringEntry {
                 default: None,
                 choices,
                 regex,
+                regex_hint,
+                choices_command,
+                editor,
+                transform,
+            }),
+        },
+        (SupportedVarType::MultiSelect, default) => VarInfo::MultiSelect {
+            entry: Box::new(MultiSelectEntry {
+                default: match default {
+                    Some(SupportedVarValue::MultiSelect(values)) => Some(values),
+                    _ => None,
+                },
+                // extract_choices makes sure they are present for multiselect
+                choices: choices.unwrap_or_default(),
+            }),
+        },
+        (SupportedVarType::Number, None) => VarInfo::Number {
+            entry: Box::new(NumberEntry {
+                default: None,
+                min,
+                max,
             }),
         },
         _ => unreachable!("It should not have come to this..."),
@@ -184,50 +1050,220 @@ fn try_key_value_into_slot(
         var_name: key.to_string(),
         var_info,
         prompt,
+        prompt_if,
+        group,
     })
 }
 
-fn extract_regex(
+fn extract_editor(
     var_name: &str,
     var_type: SupportedVarType,
+    choices: Option<&Vec<String>>,
     table_entry: Option<&toml::Value>,
-) -> Result<Option<Regex>, ConversionError> {
-    match (var_type, table_entry) {
-        (SupportedVarType::Bool, Some(_)) => Err(ConversionError::RegexOnBool {
+) -> Result<bool, ConversionError> {
+    match table_entry {
+        None | Some(toml::Value::Boolean(false)) => Ok(false),
+        Some(toml::Value::Boolean(true))
+            if var_type == SupportedVarType::String && choices.is_none() =>
+        {
+            Ok(true)
+        }
+        Some(toml::Value::Boolean(true)) => Err(ConversionError::EditorOnNonString {
             var_name: var_name.into(),
         }),
-        (SupportedVarType::String, Some(toml::Value::String(value))) => match Regex::new(value) {
-            Ok(regex) => Ok(Some(regex)),
-            Err(e) => Err(ConversionError::InvalidRegex {
-                var_name: var_name.into(),
-                regex: value.clone(),
-                error: e,
-            }),
-        },
-        (SupportedVarType::String, Some(_)) => Err(ConversionError::WrongTypeParameter {
+        Some(_) => Err(ConversionError::WrongTypeParameter {
             var_name: var_name.into(),
-            parameter: "regex".to_string(),
-            correct_type: "String".to_string(),
+            parameter: "editor".into(),
+            correct_type: "Boolean".into(),
         }),
-        (_, None) => Ok(None),
     }
 }
 
-fn extract_type(
+/// A `transform` is either the name of a case filter, or a rhai expression
+fn extract_transform(
     var_name: &str,
+    var_type: SupportedVarType,
+    choices: Option<&Vec<String>>,
     table_entry: Option<&toml::Value>,
-) -> Result<SupportedVarType, ConversionError> {
+) -> Result<Option<Transform>, ConversionError> {
     match table_entry {
-        None => Ok(SupportedVarType::String),
-        Some(toml::Value::String(value)) if value == "string" => Ok(SupportedVarType::String),
-        Some(toml::Value::String(value)) if value == "bool" => Ok(SupportedVarType::Bool),
-        Some(toml::Value::String(value)) => Err(ConversionError::InvalidVariableType {
-            var_name: var_name.into(),
-            value: value.clone(),
-        }),
+        None => Ok(None),
+        Some(_) if var_type != SupportedVarType::String || choices.is_some() => {
+            Err(ConversionError::TransformOnNonString {
+                var_name: var_name.into(),
+            })
+        }
+        Some(toml::Value::String(transform)) => case_conversion(transform).map_or_else(
+            || {
+                Engine::new()
+                    .compile_expression(transform)
+                    .map(|_| Some(Transform::Expression(transform.clone())))
+                    .map_err(|e| ConversionError::InvalidExpression {
+                        var_name: var_name.into(),
+                        error: e.to_string(),
+                    })
+            },
+            |convert| Ok(Some(Transform::Case(convert))),
+        ),
         Some(_) => Err(ConversionError::WrongTypeParameter {
             var_name: var_name.into(),
-            parameter: "type".to_string(),
+            parameter: "transform".into(),
+            correct_type: "String".into(),
+        }),
+    }
+}
+
+fn extract_choices_command(
+    var_name: &str,
+    var_type: SupportedVarType,
+    table_entry: Option<&toml::Value>,
+) -> Result<Option<Vec<String>>, ConversionError> {
+    let command = match table_entry {
+        None => return Ok(None),
+        Some(_) if var_type != SupportedVarType::String => {
+            return Err(ConversionError::ChoicesCommandOnNonString {
+                var_name: var_name.into(),
+            })
+        }
+        Some(toml::Value::Array(arr)) => arr
+            .iter()
+            .map(|arg| arg.as_str().map(String::from))
+            .collect::<Option<Vec<_>>>()
+            .filter(|command| !command.is_empty()),
+        Some(_) => None,
+    };
+    command
+        .map(Some)
+        .ok_or_else(|| ConversionError::WrongTypeParameter {
+            var_name: var_name.into(),
+            parameter: "choices_command".into(),
+            correct_type: "non-empty String Array".into(),
+        })
+}
+
+fn extract_prompt_if(
+    var_name: &str,
+    table_entry: Option<&toml::Value>,
+) -> Result<Option<String>, ConversionError> {
+    match table_entry {
+        None => Ok(None),
+        Some(toml::Value::String(condition)) => Ok(Some(condition.clone())),
+        Some(_) => Err(ConversionError::WrongTypeParameter {
+            var_name: var_name.into(),
+            parameter: "prompt_if".into(),
+            correct_type: "String".into(),
+        }),
+    }
+}
+
+fn extract_group(
+    var_name: &str,
+    table_entry: Option<&toml::Value>,
+) -> Result<Option<String>, ConversionError> {
+    match table_entry {
+        None => Ok(None),
+        Some(toml::Value::String(group)) => Ok(Some(group.clone())),
+        Some(_) => Err(ConversionError::WrongTypeParameter {
+            var_name: var_name.into(),
+            parameter: "group".into(),
+            correct_type: "String".into(),
+        }),
+    }
+}
+
+fn extract_crate_type_defaults<'a>(
+    var_name: &str,
+    table_entry: Option<&'a toml::Value>,
+) -> Result<Option<&'a toml::value::Table>, ConversionError> {
+    match table_entry {
+        None => Ok(None),
+        Some(toml::Value::Table(defaults)) => {
+            if let Some(crate_type) = defaults.keys().find(|k| *k != "bin" && *k != "lib") {
+                return Err(ConversionError::InvalidCrateTypeDefault {
+                    var_name: var_name.into(),
+                    crate_type: crate_type.clone(),
+                });
+            }
+            Ok(Some(defaults))
+        }
+        Some(_) => Err(ConversionError::WrongTypeParameter {
+            var_name: var_name.into(),
+            parameter: "default_by_crate_type".into(),
+            correct_type: "Table".into(),
+        }),
+    }
+}
+
+fn extract_regex_hint(
+    var_name: &str,
+    regex: Option<&Regex>,
+    table_entry: Option<&toml::Value>,
+) -> Result<Option<String>, ConversionError> {
+    match (regex, table_entry) {
+        (_, None) => Ok(None),
+        (None, Some(_)) => Err(ConversionError::RegexHintWithoutRegex {
+            var_name: var_name.into(),
+        }),
+        (Some(_), Some(toml::Value::String(hint))) => Ok(Some(hint.clone())),
+        (Some(_), Some(_)) => Err(ConversionError::WrongTypeParameter {
+            var_name: var_name.into(),
+            parameter: "regex_hint".to_string(),
+            correct_type: "String".to_string(),
+        }),
+    }
+}
+
+fn extract_regex(
+    var_name: &str,
+    var_type: SupportedVarType,
+    table_entry: Option<&toml::Value>,
+) -> Result<Option<Regex>, ConversionError> {
+    match (var_type, table_entry) {
+        (SupportedVarType::Bool, Some(_)) => Err(ConversionError::RegexOnBool {
+            var_name: var_name.into(),
+        }),
+        (SupportedVarType::Number, Some(_)) => Err(ConversionError::RegexOnNumber {
+            var_name: var_name.into(),
+        }),
+        (SupportedVarType::MultiSelect, Some(_)) => Err(ConversionError::RegexOnMultiSelect {
+            var_name: var_name.into(),
+        }),
+        (SupportedVarType::String, Some(toml::Value::String(value))) => match Regex::new(value) {
+            Ok(regex) => Ok(Some(regex)),
+            Err(e) => Err(ConversionError::InvalidRegex {
+                var_name: var_name.into(),
+                regex: value.clone(),
+                error: e,
+            }),
+        },
+        (SupportedVarType::String, Some(_)) => Err(ConversionError::WrongTypeParameter {
+            var_name: var_name.into(),
+            parameter: "regex".to_string(),
+            correct_type: "String".to_string(),
+        }),
+        (_, None) => Ok(None),
+    }
+}
+
+fn extract_type(
+    var_name: &str,
+    table_entry: Option<&toml::Value>,
+) -> Result<SupportedVarType, ConversionError> {
+    match table_entry {
+        None => Ok(SupportedVarType::String),
+        Some(toml::Value::String(value)) if value == "string" => Ok(SupportedVarType::String),
+        Some(toml::Value::String(value)) if value == "bool" => Ok(SupportedVarType::Bool),
+        Some(toml::Value::String(value)) if value == "number" => Ok(SupportedVarType::Number),
+        Some(toml::Value::String(value)) if value == "multiselect" => {
+            Ok(SupportedVarType::MultiSelect)
+        }
+        Some(toml::Value::String(value)) => Err(ConversionError::InvalidVariableType {
+            var_name: var_name.into(),
+            value: value.clone(),
+        }),
+        Some(_) => Err(ConversionError::WrongTypeParameter {
+            var_name: var_name.into(),
+            parameter: "type".to_string(),
             correct_type: "String".to_string(),
         }),
     }
@@ -264,6 +1300,32 @@ fn extract_default(
         (Some(toml::Value::Boolean(value)), _, SupportedVarType::Bool) => {
             Ok(Some(SupportedVarValue::Bool(*value)))
         }
+        (Some(toml::Value::Integer(value)), _, SupportedVarType::Number) => {
+            Ok(Some(SupportedVarValue::Number(Number::Integer(*value))))
+        }
+        (Some(toml::Value::Float(value)), _, SupportedVarType::Number) => finite(var_name, *value)
+            .map(|value| Some(SupportedVarValue::Number(Number::Float(value)))),
+        (Some(toml::Value::Array(values)), Some(choices), SupportedVarType::MultiSelect) => {
+            let mut defaults = Vec::with_capacity(values.len());
+            for value in values {
+                let value = value
+                    .as_str()
+                    .ok_or_else(|| ConversionError::WrongTypeParameter {
+                        var_name: var_name.into(),
+                        parameter: "default".into(),
+                        correct_type: "String Array".into(),
+                    })?;
+                if !choices.iter().any(|c| c == value) {
+                    return Err(ConversionError::InvalidDefault {
+                        var_name: var_name.into(),
+                        default: value.into(),
+                        choices: choices.clone(),
+                    });
+                }
+                defaults.push(value.to_string());
+            }
+            Ok(Some(SupportedVarValue::MultiSelect(defaults)))
+        }
         (Some(toml::Value::String(value)), None, SupportedVarType::String) => {
             if let Some(reg) = regex {
                 if !reg.is_match(value) {
@@ -305,6 +1367,8 @@ fn extract_default(
             correct_type: match type_name {
                 SupportedVarType::Bool => "bool".to_string(),
                 SupportedVarType::String => "string".to_string(),
+                SupportedVarType::Number => "number".to_string(),
+                SupportedVarType::MultiSelect => "String Array".to_string(),
             },
         }),
     }
@@ -321,12 +1385,23 @@ fn extract_choices(
         (Some(_), SupportedVarType::Bool) => Err(ConversionError::ChoicesOnBool {
             var_name: var_name.into(),
         }),
-        (Some(toml::Value::Array(arr)), SupportedVarType::String) if arr.is_empty() => {
-            Err(ConversionError::EmptyChoices {
-                var_name: var_name.into(),
-            })
-        }
-        (Some(toml::Value::Array(arr)), SupportedVarType::String) => {
+        (None, SupportedVarType::Number) => Ok(None),
+        (Some(_), SupportedVarType::Number) => Err(ConversionError::ChoicesOnNumber {
+            var_name: var_name.into(),
+        }),
+        (None, SupportedVarType::MultiSelect) => Err(ConversionError::MissingChoices {
+            var_name: var_name.into(),
+        }),
+        (
+            Some(toml::Value::Array(arr)),
+            SupportedVarType::String | SupportedVarType::MultiSelect,
+        ) if arr.is_empty() => Err(ConversionError::EmptyChoices {
+            var_name: var_name.into(),
+        }),
+        (
+            Some(toml::Value::Array(arr)),
+            SupportedVarType::String | SupportedVarType::MultiSelect,
+        ) => {
             // Checks if very entry in the array is a String
             let converted = arr
                 .iter()
@@ -348,6 +1423,14 @@ fn extract_choices(
                 .cloned()
                 .map(|v| v.unwrap())
                 .collect::<Vec<_>>();
+            if var_type == SupportedVarType::MultiSelect {
+                if let Some(choice) = strings.iter().find(|v| v.contains(',')) {
+                    return Err(ConversionError::CommaInMultiSelectChoice {
+                        var_name: var_name.into(),
+                        choice: choice.clone(),
+                    });
+                }
+            }
             // check if regex matches every choice
             if let Some(reg) = regex {
                 if strings.iter().any(|v| !reg.is_match(v)) {
@@ -360,12 +1443,73 @@ fn extract_choices(
 
             Ok(Some(strings))
         }
-        (Some(_), SupportedVarType::String) => Err(ConversionError::WrongTypeParameter {
+        (Some(_), SupportedVarType::String | SupportedVarType::MultiSelect) => {
+            Err(ConversionError::WrongTypeParameter {
+                var_name: var_name.into(),
+                parameter: "choices".to_string(),
+                correct_type: "String Array".to_string(),
+            })
+        }
+        (None, SupportedVarType::String) => Ok(None),
+    }
+}
+
+fn extract_range(
+    var_name: &str,
+    var_type: SupportedVarType,
+    min_entry: Option<&toml::Value>,
+    max_entry: Option<&toml::Value>,
+) -> Result<(Option<Number>, Option<Number>), ConversionError> {
+    if var_type != SupportedVarType::Number {
+        return match (min_entry, max_entry) {
+            (None, None) => Ok((None, None)),
+            _ => Err(ConversionError::RangeOnNonNumber {
+                var_name: var_name.into(),
+            }),
+        };
+    }
+
+    let min = extract_number(var_name, "min", min_entry)?;
+    let max = extract_number(var_name, "max", max_entry)?;
+    if let (Some(min), Some(max)) = (min, max) {
+        if min > max {
+            return Err(ConversionError::InvalidRange {
+                var_name: var_name.into(),
+            });
+        }
+    }
+
+    Ok((min, max))
+}
+
+fn extract_number(
+    var_name: &str,
+    parameter: &str,
+    table_entry: Option<&toml::Value>,
+) -> Result<Option<Number>, ConversionError> {
+    match table_entry {
+        None => Ok(None),
+        Some(toml::Value::Integer(value)) => Ok(Some(Number::Integer(*value))),
+        Some(toml::Value::Float(value)) => {
+            finite(var_name, *value).map(|value| Some(Number::Float(value)))
+        }
+        Some(_) => Err(ConversionError::WrongTypeParameter {
             var_name: var_name.into(),
-            parameter: "choices".to_string(),
-            correct_type: "String Array".to_string(),
+            parameter: parameter.into(),
+            correct_type: "number".into(),
         }),
-        (None, SupportedVarType::String) => Ok(None),
+    }
+}
+
+/// TOML allows `nan` and `inf` as floats, they would slip through the range checks
+fn finite(var_name: &str, value: f64) -> Result<f64, ConversionError> {
+    if value.is_finite() {
+        Ok(value)
+    } else {
+        Err(ConversionError::NotANumber {
+            var_name: var_name.into(),
+            value: value.to_string(),
+        })
     }
 }
 
@@ -758,83 +1902,993 @@ mod tests {
     }
 
     #[test]
-    fn bools_cant_have_regex() {
-        let result = extract_regex(
+    fn type_can_be_number() {
+        let result = extract_type("foo", Some(&toml::Value::String("number".into())));
+
+        assert_eq!(result, Ok(SupportedVarType::Number));
+    }
+
+    #[test]
+    fn default_for_number_accepts_integers_and_floats() {
+        let result_int = extract_default(
             "foo",
-            SupportedVarType::Bool,
-            Some(&toml::Value::String("".into())),
+            SupportedVarType::Number,
+            None,
+            Some(&toml::Value::Integer(42)),
+            None,
+        );
+        let result_float = extract_default(
+            "foo",
+            SupportedVarType::Number,
+            None,
+            Some(&toml::Value::Float(0.5)),
+            None,
         );
 
-        assert!(result.is_err())
+        assert_eq!(
+            result_int,
+            Ok(Some(SupportedVarValue::Number(Number::Integer(42))))
+        );
+        assert_eq!(
+            result_float,
+            Ok(Some(SupportedVarValue::Number(Number::Float(0.5))))
+        );
     }
 
     #[test]
-    fn no_regex_is_ok() {
-        let result_bool = extract_regex("foo", SupportedVarType::Bool, None);
-        let result_string = extract_regex("foo", SupportedVarType::String, None);
+    fn multiselect_requires_choices() {
+        let result = extract_choices("foo", SupportedVarType::MultiSelect, None, None);
 
-        assert!(result_bool.is_ok());
-        assert!(result_string.is_ok())
+        assert_eq!(
+            result,
+            Err(ConversionError::MissingChoices {
+                var_name: "foo".into()
+            })
+        );
     }
 
     #[test]
-    fn strings_can_have_regex() {
-        let result = extract_regex(
-            "foo",
-            SupportedVarType::String,
-            Some(&toml::Value::String("^([a-zA-Z][a-zA-Z0-9_-]+)$".into())),
+    fn multiselect_choices_cant_contain_commas() {
+        let choices = toml::Value::Array(vec!["a".into(), "b,c".into()]);
+        let result = extract_choices("foo", SupportedVarType::MultiSelect, None, Some(&choices));
+
+        assert_eq!(
+            result,
+            Err(ConversionError::CommaInMultiSelectChoice {
+                var_name: "foo".into(),
+                choice: "b,c".into()
+            })
         );
 
-        assert!(result.is_ok())
+        let result = extract_choices("foo", SupportedVarType::String, None, Some(&choices));
+        assert!(result.is_ok());
     }
 
     #[test]
-    fn invalid_regex_is_err() {
-        let result = extract_regex(
+    fn default_for_multiselect_must_be_on_choices() {
+        let result = extract_default(
             "foo",
-            SupportedVarType::String,
-            Some(&toml::Value::String("*".into())),
+            SupportedVarType::MultiSelect,
+            None,
+            Some(&toml::Value::Array(vec![toml::Value::String("bar".into())])),
+            Some(&vec!["zoo".to_string(), "far".to_string()]),
         );
 
-        assert!(result.is_err())
+        assert_eq!(
+            result,
+            Err(ConversionError::InvalidDefault {
+                var_name: "foo".into(),
+                default: "bar".into(),
+                choices: vec!["zoo".to_string(), "far".to_string()]
+            })
+        );
     }
 
     #[test]
-    fn block_invalid_key_names() {
-        let result =
-            try_key_value_into_slot("project-name", &toml::Value::Table(Default::default()));
+    fn multiselect_entry_parses_lists_and_arrays() {
+        let entry = MultiSelectEntry {
+            default: None,
+            choices: vec!["serde".into(), "tokio".into(), "clap".into()],
+        };
 
-        assert!(result.is_err());
-        let result = result.err().unwrap();
         assert_eq!(
-            result,
-            ConversionError::InvalidPlaceholderName {
-                var_name: "project-name".into()
-            }
+            entry.parse("foo", "serde, clap"),
+            Ok(vec!["serde".to_string(), "clap".to_string()])
+        );
+        assert_eq!(
+            entry.parse("foo", r#"["tokio", "serde"]"#),
+            Ok(vec!["tokio".to_string(), "serde".to_string()])
+        );
+        assert_eq!(entry.parse("foo", ""), Ok(vec![]));
+        assert_eq!(
+            entry.parse("foo", "serde,log"),
+            Err(ConversionError::InvalidChoice {
+                var_name: "foo".into(),
+                value: "log".into(),
+                choices: vec!["serde".into(), "tokio".into(), "clap".into()],
+            })
         );
+    }
 
-        let result = try_key_value_into_slot("crate_name", &toml::Value::Table(Default::default()));
+    #[test]
+    fn min_and_max_only_for_numbers() {
+        let result = extract_range(
+            "foo",
+            SupportedVarType::String,
+            Some(&toml::Value::Integer(1)),
+            None,
+        );
 
-        assert!(result.is_err());
-        let result = result.err().unwrap();
         assert_eq!(
             result,
-            ConversionError::InvalidPlaceholderName {
-                var_name: "crate_name".into()
-            }
+            Err(ConversionError::RangeOnNonNumber {
+                var_name: "foo".into()
+            })
         );
     }
 
     #[test]
-    fn only_tables_as_placeholder_values() {
-        let result = try_key_value_into_slot("foo", &toml::Value::Integer(Default::default()));
+    fn min_cant_be_greater_than_max() {
+        let result = extract_range(
+            "foo",
+            SupportedVarType::Number,
+            Some(&toml::Value::Integer(10)),
+            Some(&toml::Value::Integer(1)),
+        );
 
-        assert!(result.is_err());
-        let result = result.err().unwrap();
         assert_eq!(
             result,
-            ConversionError::InvalidPlaceholderFormat {
+            Err(ConversionError::InvalidRange {
                 var_name: "foo".into()
+            })
+        );
+    }
+
+    #[test]
+    fn number_entry_validates_range() {
+        let entry = NumberEntry {
+            default: None,
+            min: Some(Number::Integer(1)),
+            max: Some(Number::Integer(10)),
+        };
+
+        assert_eq!(entry.parse("foo", "5"), Ok(Number::Integer(5)));
+        assert_eq!(entry.parse("foo", "5.5"), Ok(Number::Float(5.5)));
+        assert_eq!(
+            entry.parse("foo", "11"),
+            Err(ConversionError::NumberOutOfRange {
+                var_name: "foo".into(),
+                value: "11".into(),
+                range: "1..=10".into(),
+            })
+        );
+        assert_eq!(
+            entry.parse("foo", "ten"),
+            Err(ConversionError::NotANumber {
+                var_name: "foo".into(),
+                value: "ten".into(),
+            })
+        );
+        for input in ["NaN", "inf", "-inf"] {
+            assert_eq!(
+                entry.parse("foo", input),
+                Err(ConversionError::NotANumber {
+                    var_name: "foo".into(),
+                    value: input.into(),
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn whole_numbers_beyond_f64_precision_are_kept() {
+        let entry = NumberEntry {
+            default: None,
+            min: None,
+            max: Some(Number::Integer(9_007_199_254_740_992)),
+        };
+
+        assert_eq!(
+            entry.parse("foo", "9007199254740991"),
+            Ok(Number::Integer(9_007_199_254_740_991))
+        );
+        assert_eq!(
+            entry.parse("foo", "9007199254740993"),
+            Err(ConversionError::NumberOutOfRange {
+                var_name: "foo".into(),
+                value: "9007199254740993".into(),
+                range: "..=9007199254740992".into(),
+            })
+        );
+        assert_eq!(
+            Number::Integer(i64::MAX).into_value(),
+            Value::Scalar(i64::MAX.into())
+        );
+    }
+
+    #[test]
+    fn non_finite_default_is_err() {
+        let value: toml::Value = toml::from_str("default = nan").unwrap();
+        assert_eq!(
+            extract_default(
+                "foo",
+                SupportedVarType::Number,
+                None,
+                value.get("default"),
+                None
+            ),
+            Err(ConversionError::NotANumber {
+                var_name: "foo".into(),
+                value: "NaN".into(),
+            })
+        );
+
+        let value: toml::Value = toml::from_str("max = -inf").unwrap();
+        assert!(extract_range("foo", SupportedVarType::Number, None, value.get("max")).is_err());
+    }
+
+    #[test]
+    fn default_out_of_range_is_err() {
+        let mut table = toml::value::Table::new();
+        table.insert("type".into(), toml::Value::String("number".into()));
+        table.insert("prompt".into(), toml::Value::String("port?".into()));
+        table.insert("default".into(), toml::Value::Integer(80));
+        table.insert("min".into(), toml::Value::Integer(1024));
+
+        let result = try_key_value_into_slot("port", &toml::Value::Table(table), None);
+
+        assert!(matches!(
+            result,
+            Err(ConversionError::NumberOutOfRange { .. })
+        ));
+    }
+
+    #[test]
+    fn bools_cant_have_regex() {
+        let result = extract_regex(
+            "foo",
+            SupportedVarType::Bool,
+            Some(&toml::Value::String("".into())),
+        );
+
+        assert!(result.is_err())
+    }
+
+    #[test]
+    fn no_regex_is_ok() {
+        let result_bool = extract_regex("foo", SupportedVarType::Bool, None);
+        let result_string = extract_regex("foo", SupportedVarType::String, None);
+
+        assert!(result_bool.is_ok());
+        assert!(result_string.is_ok())
+    }
+
+    #[test]
+    fn strings_can_have_regex() {
+        let result = extract_regex(
+            "foo",
+            SupportedVarType::String,
+            Some(&toml::Value::String("^([a-zA-Z][a-zA-Z0-9_-]+)$".into())),
+        );
+
+        assert!(result.is_ok())
+    }
+
+    #[test]
+    fn regex_hint_requires_a_regex() {
+        let hint = toml::Value::String("lowercase only".into());
+        let regex = Regex::new("^[a-z]+$").unwrap();
+
+        assert_eq!(
+            extract_regex_hint("foo", None, Some(&hint)),
+            Err(ConversionError::RegexHintWithoutRegex {
+                var_name: "foo".into()
+            })
+        );
+        assert_eq!(
+            extract_regex_hint("foo", Some(&regex), Some(&hint)),
+            Ok(Some("lowercase only".into()))
+        );
+    }
+
+    #[test]
+    fn invalid_regex_is_err() {
+        let result = extract_regex(
+            "foo",
+            SupportedVarType::String,
+            Some(&toml::Value::String("*".into())),
+        );
+
+        assert!(result.is_err())
+    }
+
+    #[test]
+    fn block_invalid_key_names() {
+        let result = try_key_value_into_slot(
+            "project-name",
+            &toml::Value::Table(Default::default()),
+            None,
+        );
+
+        assert!(result.is_err());
+        let result = result.err().unwrap();
+        assert_eq!(
+            result,
+            ConversionError::InvalidPlaceholderName {
+                var_name: "project-name".into()
+            }
+        );
+
+        let result =
+            try_key_value_into_slot("crate_name", &toml::Value::Table(Default::default()), None);
+
+        assert!(result.is_err());
+        let result = result.err().unwrap();
+        assert_eq!(
+            result,
+            ConversionError::InvalidPlaceholderName {
+                var_name: "crate_name".into()
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_placeholder_name_lists_all_reserved_names() {
+        assert_eq!(
+            ConversionError::InvalidPlaceholderName {
+                var_name: "is_init".into()
+            }
+            .to_string(),
+            "placeholder `is_init` is not valid as you can't override `authors`, `os-arch`, `project-name`, \
+             `crate_name`, `crate_type`, `within_cargo_project` and `is_init`"
+        );
+    }
+
+    #[test]
+    fn builtin_objects_are_no_reserved_names() {
+        let value: toml::Value = toml::from_str(r#"prompt = "When?""#).unwrap();
+        assert!(try_key_value_into_slot("now", &value, None).is_ok());
+        assert!(try_key_value_into_slot("project", &value, None).is_ok());
+    }
+
+    #[test]
+    fn block_rhai_keywords_as_names_of_expression_placeholders() {
+        let value: toml::Value = toml::from_str(
+            r#"type = "string"
+prompt = "?""#,
+        )
+        .unwrap();
+        assert!(try_key_value_into_slot("module", &value, None).is_ok());
+
+        let value: toml::Value = toml::from_str(
+            r#"type = "string"
+prompt = "?"
+prompt_if = "true""#,
+        )
+        .unwrap();
+        assert_eq!(
+            try_key_value_into_slot("module", &value, None).unwrap_err(),
+            ConversionError::RhaiKeywordName {
+                var_name: "module".into()
+            }
+        );
+
+        let value: toml::Value = toml::from_str(r#"type = "derived""#).unwrap();
+        assert_eq!(
+            try_key_value_into_derived("let", &value),
+            Err(ConversionError::RhaiKeywordName {
+                var_name: "let".into()
+            })
+        );
+    }
+
+    #[test]
+    fn only_tables_as_placeholder_values() {
+        let result =
+            try_key_value_into_slot("foo", &toml::Value::Integer(Default::default()), None);
+
+        assert!(result.is_err());
+        let result = result.err().unwrap();
+        assert_eq!(
+            result,
+            ConversionError::InvalidPlaceholderFormat {
+                var_name: "foo".into()
+            }
+        );
+    }
+
+    fn derived(var_name: &str, expr: &str) -> DerivedEntry {
+        DerivedEntry {
+            var_name: var_name.into(),
+            expr: expr.into(),
+        }
+    }
+
+    #[test]
+    fn derived_requires_expr() {
+        let value: toml::Value = toml::from_str(r#"type = "derived""#).unwrap();
+
+        assert_eq!(
+            try_key_value_into_derived("foo", &value),
+            Err(ConversionError::MissingExpression {
+                var_name: "foo".into()
+            })
+        );
+    }
+
+    #[test]
+    fn derived_does_not_support_prompt() {
+        let value: toml::Value = toml::from_str(
+            r#"type = "derived"
+expr = "1"
+prompt = "?""#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            try_key_value_into_derived("foo", &value),
+            Err(ConversionError::FieldOnDerived {
+                var_name: "foo".into(),
+                field: "prompt".into()
+            })
+        );
+    }
+
+    #[test]
+    fn derived_are_evaluated_in_dependency_order() {
+        let mut object = Object::new();
+        object.insert("crate_name".into(), Value::Scalar("my_crate".into()));
+        object.insert("workers".into(), Value::Scalar(4_i64.into()));
+
+        fill_derived_variables(
+            &mut object,
+            vec![
+                derived("core_path", r#"core_module + "::lib""#),
+                derived("core_module", r#"crate_name + "_core""#),
+                derived("threads", "workers * 2"),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            object.get("core_module").unwrap().to_kstr().as_str(),
+            "my_crate_core"
+        );
+        assert_eq!(
+            object.get("core_path").unwrap().to_kstr().as_str(),
+            "my_crate_core::lib"
+        );
+        assert_eq!(object.get("threads").unwrap().to_kstr().as_str(), "8");
+    }
+
+    #[test]
+    fn liquid_values_keep_their_type_in_rhai() {
+        let engine = Engine::new();
+        let mut scope = rhai::Scope::new();
+        scope.push_dynamic(
+            "count",
+            liquid_value_into_dynamic(&Value::Scalar(3_i64.into())).unwrap(),
+        );
+        scope.push_dynamic(
+            "ratio",
+            liquid_value_into_dynamic(&Value::Scalar(0.5_f64.into())).unwrap(),
+        );
+        scope.push_dynamic(
+            "features",
+            liquid_value_into_dynamic(&Value::Array(vec![
+                Value::scalar("serde"),
+                Value::scalar("clap"),
+            ]))
+            .unwrap(),
+        );
+
+        let eval = |expr: &str| {
+            engine
+                .eval_expression_with_scope::<bool>(&mut scope.clone(), expr)
+                .unwrap()
+        };
+        assert!(eval("count > 2"));
+        assert!(eval("ratio < 1.0"));
+        assert!(eval(r#"features.contains("serde")"#));
+        assert!(!eval(r#"features.contains("tokio")"#));
+    }
+
+    #[test]
+    fn derived_reach_names_that_are_no_identifiers_through_the_variables_map() {
+        let mut object = Object::new();
+        object.insert("project-name".into(), Value::Scalar("my-project".into()));
+        object.insert("module".into(), Value::Scalar("core".into()));
+
+        fill_derived_variables(
+            &mut object,
+            vec![derived(
+                "core_path",
+                r#"variables["project-name"] + "::" + variables["module"]"#,
+            )],
+        )
+        .unwrap();
+
+        assert_eq!(
+            object.get("core_path").unwrap().to_kstr().as_str(),
+            "my-project::core"
+        );
+    }
+
+    #[test]
+    fn derived_detects_cycles() {
+        let result = fill_derived_variables(
+            &mut Object::new(),
+            vec![derived("a", "b + 1"), derived("b", "a + 1")],
+        );
+
+        assert_eq!(
+            result,
+            Err(ConversionError::CyclicDerivation {
+                var_names: vec!["a".into(), "b".into()]
+            })
+        );
+    }
+
+    #[test]
+    fn derived_detects_missing_references() {
+        let result = fill_derived_variables(&mut Object::new(), vec![derived("a", "b + 1")]);
+
+        assert_eq!(
+            result,
+            Err(ConversionError::MissingReference {
+                var_name: "a".into(),
+                reference: "b".into()
+            })
+        );
+    }
+
+    fn config(toml: &str) -> Config {
+        Config::try_from(toml.to_string()).unwrap()
+    }
+
+    fn gated_config() -> Config {
+        config(
+            r#"
+            [placeholders]
+            use_db = { type = "bool", prompt = "Use a database?" }
+            db_url = { type = "string", prompt = "Database url?", default = "sqlite://", prompt_if = "use_db" }
+            db_name = { type = "string", prompt = "Database name?", prompt_if = "use_db" }
+            "#,
+        )
+    }
+
+    fn fill_gated(use_db: bool) -> (Object, Vec<String>) {
+        let prompted = std::cell::RefCell::new(Vec::new());
+        let object = fill_project_variables(Object::new(), &gated_config(), |slot, _| {
+            prompted.borrow_mut().push(slot.var_name.clone());
+            Ok(match slot.var_name.as_str() {
+                "use_db" => Value::Scalar(use_db.into()),
+                name => Value::Scalar(format!("prompted {}", name).into()),
+            })
+        })
+        .unwrap();
+        let mut prompted = prompted.into_inner();
+        prompted.sort();
+        (object, prompted)
+    }
+
+    #[test]
+    fn prompt_if_skips_placeholders_when_false() {
+        let (object, prompted) = fill_gated(false);
+
+        assert_eq!(prompted, ["use_db"]);
+        assert_eq!(
+            object.get("db_url").unwrap().to_kstr().as_str(),
+            "sqlite://"
+        );
+        assert!(object.get("db_name").is_none());
+    }
+
+    #[test]
+    fn prompt_if_prompts_placeholders_when_true() {
+        let (object, prompted) = fill_gated(true);
+
+        assert_eq!(prompted, ["db_name", "db_url", "use_db"]);
+        assert_eq!(
+            object.get("db_url").unwrap().to_kstr().as_str(),
+            "prompted db_url"
+        );
+        assert_eq!(
+            object.get("db_name").unwrap().to_kstr().as_str(),
+            "prompted db_name"
+        );
+    }
+
+    #[test]
+    fn prompt_if_detects_cycles() {
+        let config = config(
+            r#"
+            [placeholders]
+            a = { type = "bool", prompt = "A?", prompt_if = "b" }
+            b = { type = "bool", prompt = "B?", prompt_if = "a" }
+            "#,
+        );
+
+        let error = fill_project_variables(Object::new(), &config, |_, _| {
+            Ok(Value::Scalar(true.into()))
+        })
+        .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<ConversionError>(),
+            Some(ConversionError::CyclicCondition { .. })
+        ));
+    }
+
+    #[test]
+    fn prompt_if_must_be_a_string() {
+        let value: toml::Value = toml::from_str(
+            r#"type = "bool"
+prompt = "?"
+prompt_if = true"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            try_key_value_into_slot("foo", &value, None).unwrap_err(),
+            ConversionError::WrongTypeParameter {
+                var_name: "foo".into(),
+                parameter: "prompt_if".into(),
+                correct_type: "String".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn placeholders_are_prompted_by_order_then_declaration() {
+        let config = config(
+            r#"
+            [placeholders]
+            zeta = { type = "string", prompt = "Zeta?" }
+            alpha = { type = "string", prompt = "Alpha?" }
+            second = { type = "string", prompt = "Second?", order = 2 }
+            first = { type = "string", prompt = "First?", order = 1 }
+            also_second = { type = "string", prompt = "Also second?", order = 2 }
+            "#,
+        );
+
+        let prompted = std::cell::RefCell::new(Vec::new());
+        fill_project_variables(Object::new(), &config, |slot, _| {
+            prompted.borrow_mut().push(slot.var_name.clone());
+            Ok(Value::Scalar("value".into()))
+        })
+        .unwrap();
+
+        assert_eq!(
+            prompted.into_inner(),
+            ["first", "second", "also_second", "zeta", "alpha"]
+        );
+    }
+
+    #[test]
+    fn declared_builtin_placeholders_are_still_prompted() {
+        let config = config(
+            r#"
+            [placeholders]
+            username = { type = "string", prompt = "Username?" }
+
+            [conditional.'false'.placeholders]
+            email = { type = "string", prompt = "Email?" }
+            "#,
+        );
+        let mut object = Object::new();
+        object.insert("username".into(), Value::Scalar("git-user".into()));
+        object.insert("email".into(), Value::Scalar("git@example.com".into()));
+
+        let builtins = take_overridden_builtins(&mut object, &config);
+        let prompted = std::cell::RefCell::new(Vec::new());
+        let mut object = fill_project_variables(object, &config, |slot, _| {
+            prompted.borrow_mut().push(slot.var_name.clone());
+            Ok(Value::Scalar("declared".into()))
+        })
+        .unwrap();
+        restore_builtins(&mut object, builtins);
+
+        assert_eq!(prompted.into_inner(), ["username"]);
+        assert_eq!(
+            object.get("username").unwrap().to_kstr().as_str(),
+            "declared"
+        );
+        assert_eq!(
+            object.get("email").unwrap().to_kstr().as_str(),
+            "git@example.com"
+        );
+    }
+
+    #[test]
+    fn declared_template_placeholder_takes_the_place_of_the_builtin_object() {
+        let config = config(
+            r#"
+            [placeholders]
+            template = { type = "string", prompt = "Which template?", choices = ["web", "cli"] }
+            "#,
+        );
+        let mut object = Object::new();
+        object.insert("template".into(), Value::Object(Object::new()));
+
+        let builtins = take_overridden_builtins(&mut object, &config);
+        let mut object =
+            fill_project_variables(object, &config, |_, _| Ok(Value::Scalar("cli".into())))
+                .unwrap();
+        restore_builtins(&mut object, builtins);
+
+        assert_eq!(object.get("template").unwrap().to_kstr().as_str(), "cli");
+    }
+
+    #[test]
+    fn order_must_be_an_integer() {
+        assert_eq!(
+            extract_order("foo", Some(&toml::Value::String("1".into()))).unwrap_err(),
+            ConversionError::WrongTypeParameter {
+                var_name: "foo".into(),
+                parameter: "order".into(),
+                correct_type: "Integer".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn placeholders_of_a_group_are_prompted_together() {
+        let config = config(
+            r#"
+            [placeholders]
+            db_url = { type = "string", prompt = "Url?", group = "Database options" }
+            name = { type = "string", prompt = "Name?" }
+            port = { type = "string", prompt = "Port?", group = "Server options" }
+            db_user = { type = "string", prompt = "User?", group = "Database options" }
+            "#,
+        );
+
+        let prompted = std::cell::RefCell::new(Vec::new());
+        fill_project_variables(Object::new(), &config, |slot, _| {
+            prompted
+                .borrow_mut()
+                .push((slot.var_name.clone(), slot.group.clone()));
+            Ok(Value::Scalar("value".into()))
+        })
+        .unwrap();
+
+        let database = Some("Database options".to_string());
+        assert_eq!(
+            prompted.into_inner(),
+            [
+                ("db_url".to_string(), database.clone()),
+                ("db_user".to_string(), database),
+                ("name".to_string(), None),
+                ("port".to_string(), Some("Server options".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn group_must_be_a_string() {
+        assert_eq!(
+            extract_group("foo", Some(&toml::Value::Integer(1))).unwrap_err(),
+            ConversionError::WrongTypeParameter {
+                var_name: "foo".into(),
+                parameter: "group".into(),
+                correct_type: "String".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn choices_command_is_only_supported_for_strings() {
+        let command = toml::Value::Array(vec![toml::Value::String("ls".into())]);
+
+        assert_eq!(
+            extract_choices_command("foo", SupportedVarType::String, Some(&command)),
+            Ok(Some(vec!["ls".into()]))
+        );
+        assert_eq!(
+            extract_choices_command("foo", SupportedVarType::Bool, Some(&command)),
+            Err(ConversionError::ChoicesCommandOnNonString {
+                var_name: "foo".into()
+            })
+        );
+        assert_eq!(
+            extract_choices_command(
+                "foo",
+                SupportedVarType::String,
+                Some(&toml::Value::Array(vec![]))
+            ),
+            Err(ConversionError::WrongTypeParameter {
+                var_name: "foo".into(),
+                parameter: "choices_command".into(),
+                correct_type: "non-empty String Array".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn choices_command_replaces_the_static_choices_only_if_allowed_and_successful() {
+        if Command::new("sh").arg("-c").arg("true").output().is_err() {
+            return; // no `sh` available, e.g. on windows
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let slot_with_command = |command: &str| {
+            let values = toml::from_str::<toml::Value>(&format!(
+                r#"
+                type = "string"
+                prompt = "?"
+                choices = ["static"]
+                choices_command = ["sh", "-c", "{}"]
+                "#,
+                command
+            ))
+            .unwrap();
+            try_key_value_into_slot("foo", &values, None).unwrap()
+        };
+        let choices = |slot: TemplateSlots| match slot.var_info {
+            VarInfo::String { entry } => entry.choices.unwrap(),
+            _ => unreachable!(),
+        };
+
+        let slot = slot_with_command(r"printf '1.0\\n\\n2.0\\n'");
+        assert_eq!(
+            choices(slot.with_command_choices(dir.path(), true)),
+            ["1.0", "2.0"]
+        );
+        assert_eq!(
+            choices(slot.with_command_choices(dir.path(), false)),
+            ["static"]
+        );
+
+        let slot = slot_with_command("exit 1");
+        assert_eq!(
+            choices(slot.with_command_choices(dir.path(), true)),
+            ["static"]
+        );
+    }
+
+    #[test]
+    fn editor_is_only_supported_for_strings_without_choices() {
+        let enabled = toml::Value::Boolean(true);
+        let choices = vec!["a".to_string()];
+
+        assert_eq!(
+            extract_editor("foo", SupportedVarType::String, None, Some(&enabled)),
+            Ok(true)
+        );
+        assert_eq!(
+            extract_editor("foo", SupportedVarType::String, None, None),
+            Ok(false)
+        );
+        assert_eq!(
+            extract_editor(
+                "foo",
+                SupportedVarType::String,
+                Some(&choices),
+                Some(&enabled)
+            ),
+            Err(ConversionError::EditorOnNonString {
+                var_name: "foo".into()
+            })
+        );
+        assert_eq!(
+            extract_editor("foo", SupportedVarType::Bool, None, Some(&enabled)),
+            Err(ConversionError::EditorOnNonString {
+                var_name: "foo".into()
+            })
+        );
+    }
+
+    #[test]
+    fn transform_is_a_case_filter_or_an_expression() {
+        let transform = |transform: &str, input: &str| {
+            let transform = extract_transform(
+                "foo",
+                SupportedVarType::String,
+                None,
+                Some(&toml::Value::String(transform.into())),
+            )
+            .unwrap();
+            let entry = StringEntry {
+                default: None,
+                choices: None,
+                regex: None,
+                regex_hint: None,
+                choices_command: None,
+                editor: false,
+                transform,
+            };
+            entry.transform("foo", input.into()).unwrap()
+        };
+
+        assert_eq!(transform("kebab", "My Project"), "my-project");
+        assert_eq!(transform("snake_case", "My Project"), "my_project");
+        assert_eq!(transform("value.to_upper()", "my-project"), "MY-PROJECT");
+        assert_eq!(transform(r#"value + "-rs""#, "my-project"), "my-project-rs");
+    }
+
+    #[test]
+    fn transform_must_be_valid() {
+        let transform = |var_type, transform: &str| {
+            extract_transform(
+                "foo",
+                var_type,
+                None,
+                Some(&toml::Value::String(transform.into())),
+            )
+        };
+
+        assert!(matches!(
+            transform(SupportedVarType::String, "value +"),
+            Err(ConversionError::InvalidExpression { .. })
+        ));
+        assert_eq!(
+            transform(SupportedVarType::Number, "kebab").unwrap_err(),
+            ConversionError::TransformOnNonString {
+                var_name: "foo".into()
+            }
+        );
+    }
+
+    #[test]
+    fn default_by_crate_type_wins_for_the_seeded_crate_type() {
+        let config = config(
+            r#"
+            [placeholders]
+            main_file = { type = "string", prompt = "Main file?", default = "main.rs", default_by_crate_type = { lib = "lib.rs" } }
+            "#,
+        );
+        let main_file = |crate_type: &str| {
+            let mut object = Object::new();
+            object.insert(
+                "crate_type".into(),
+                Value::Scalar(crate_type.to_string().into()),
+            );
+            let object = fill_project_variables(object, &config, |slot, _| {
+                Ok(slot.var_info.default_value().unwrap())
+            })
+            .unwrap();
+            object.get("main_file").unwrap().to_kstr().to_string()
+        };
+
+        assert_eq!(main_file("bin"), "main.rs");
+        assert_eq!(main_file("lib"), "lib.rs");
+    }
+
+    #[test]
+    fn default_by_crate_type_only_knows_bin_and_lib() {
+        let values = toml::from_str::<toml::Value>(
+            r#"
+            prompt = "?"
+            default_by_crate_type = { bin = "main.rs", proc-macro = "lib.rs" }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            try_key_value_into_slot("foo", &values, Some("bin")).unwrap_err(),
+            ConversionError::InvalidCrateTypeDefault {
+                var_name: "foo".into(),
+                crate_type: "proc-macro".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn default_by_crate_type_must_match_the_type() {
+        let values = toml::from_str::<toml::Value>(
+            r#"
+            type = "bool"
+            prompt = "?"
+            default_by_crate_type = { lib = "yes" }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            try_key_value_into_slot("foo", &values, Some("bin")).unwrap_err(),
+            ConversionError::WrongTypeParameter {
+                var_name: "foo".into(),
+                parameter: "default".into(),
+                correct_type: "bool".into(),
             }
         );
     }
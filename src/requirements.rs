@@ -0,0 +1,150 @@
+//! Checks of the requirements a template puts on the environment, like `min_rust_version` and `required_commands`
+
+use std::env;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{anyhow, bail, Result};
+use console::style;
+
+use crate::{config::Config, emoji, warn};
+
+/// Fails if the installed `rustc` is older than the `min_rust_version` of the template.
+/// Without a `rustc` to ask, the check is skipped with a warning.
+pub fn check_min_rust_version(template_config: &Config) -> Result<()> {
+    let min_rust_version = match template_config
+        .template
+        .as_ref()
+        .and_then(|template| template.min_rust_version.as_deref())
+    {
+        Some(min_rust_version) => min_rust_version,
+        None => return Ok(()),
+    };
+    let required = parse_rust_version(min_rust_version).ok_or_else(|| {
+        anyhow!(
+            "{} {} {}",
+            emoji::ERROR,
+            style("Invalid `min_rust_version`:").bold().red(),
+            style(format!("`{}` is no version like `1.70`", min_rust_version)).yellow(),
+        )
+    })?;
+
+    let rustc = env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    let installed = Command::new(&rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .split_whitespace()
+                .nth(1)
+                .and_then(parse_rust_version)
+        });
+    match installed {
+        Some(installed) if installed < required => bail!(
+            "{} {} {} {} {}",
+            emoji::ERROR,
+            style("Required Rust version not met. Required:")
+                .bold()
+                .red(),
+            style(min_rust_version).yellow(),
+            style(" was:").bold().red(),
+            style(format!("{}.{}.{}", installed.0, installed.1, installed.2)).yellow(),
+        ),
+        Some(_) => Ok(()),
+        None => {
+            warn!(
+                "{} `{}`",
+                style("Unable to check the Rust version the template requires, without a working")
+                    .bold(),
+                style(rustc.to_string_lossy()).bold()
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Parses a Rust version like `1.70`, `1.70.0` or `1.72.0-nightly` into major, minor and patch.
+/// A pre-release is taken as the release, so a nightly satisfies its own version.
+fn parse_rust_version(version: &str) -> Option<(u64, u64, u64)> {
+    let version = version.split(['-', '+']).next()?;
+    let mut parts = version.split('.').map(|part| part.parse::<u64>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().unwrap_or(Some(0))?;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    parts.next().is_none().then_some((major, minor, patch))
+}
+
+/// Fails with all `required_commands` of the template that are not on `PATH`
+pub fn check_required_commands(template_config: &Config) -> Result<()> {
+    let required_commands = template_config
+        .template
+        .as_ref()
+        .and_then(|template| template.required_commands.as_deref())
+        .unwrap_or_default();
+    let missing: Vec<&str> = required_commands
+        .iter()
+        .map(String::as_str)
+        .filter(|command| !is_on_path(command))
+        .collect();
+
+    if !missing.is_empty() {
+        bail!(
+            "{} {} {}\n{}",
+            emoji::ERROR,
+            style("The template requires commands that are not on `PATH`:")
+                .bold()
+                .red(),
+            style(missing.join(", ")).yellow(),
+            style("Install them, or pass `--ignore-required-commands` to generate anyway.").bold(),
+        );
+    }
+    Ok(())
+}
+
+/// `true` if `command` is a path to a file, or a file in one of the dirs of `PATH`
+pub fn is_on_path(command: &str) -> bool {
+    let command = Path::new(command);
+    if command.components().count() > 1 {
+        return command.is_file();
+    }
+
+    // on windows a command may leave out its extension, like `node` for `node.exe`
+    let extensions: Vec<String> = if cfg!(windows) {
+        env::var("PATHEXT")
+            .unwrap_or_else(|_| ".EXE;.CMD;.BAT;.COM".into())
+            .split(';')
+            .map(String::from)
+            .collect()
+    } else {
+        vec![]
+    };
+    env::var_os("PATH").is_some_and(|paths| {
+        env::split_paths(&paths).any(|dir| {
+            let candidate = dir.join(command);
+            candidate.is_file()
+                || extensions.iter().any(|extension| {
+                    let mut file_name = command.as_os_str().to_owned();
+                    file_name.push(extension);
+                    dir.join(file_name).is_file()
+                })
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_rust_versions() {
+        assert_eq!(parse_rust_version("1.70"), Some((1, 70, 0)));
+        assert_eq!(parse_rust_version("1.70.1"), Some((1, 70, 1)));
+        assert_eq!(parse_rust_version("1.72.0-nightly"), Some((1, 72, 0)));
+        assert_eq!(parse_rust_version("1"), Some((1, 0, 0)));
+        assert_eq!(parse_rust_version("1.x"), None);
+        assert_eq!(parse_rust_version("1.70.0.1"), None);
+        assert_eq!(parse_rust_version(""), None);
+    }
+}
@@ -1,19 +1,23 @@
 use anyhow::{Context, Result};
 use console::style;
-use indicatif::{MultiProgress, ProgressBar};
 use liquid::Parser;
 use liquid_core::{Object, Value};
+use path_absolutize::Absolutize;
+use rayon::prelude::*;
+use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
 use walkdir::{DirEntry, WalkDir};
 
-use crate::config::TemplateConfig;
+use crate::config::{LineEnding, TemplateConfig};
 use crate::filenames::substitute_filename;
 use crate::include_exclude::*;
-use crate::progressbar::spinner;
+use crate::progressbar::Progress;
 use crate::template_filters::*;
-use crate::template_variables::{get_authors, get_os_arch, Authors, CrateType, ProjectName};
-use crate::{emoji, GenerateArgs};
+use crate::template_variables::{get_authors, get_os_arch, Authors, CrateType, Now, ProjectName};
+use crate::user_parsed_input::TemplateLocation;
+use crate::{debug, emoji, log, out, GenerateArgs};
 
 fn engine() -> Parser {
     liquid::ParserBuilder::with_stdlib()
@@ -21,21 +25,81 @@ fn engine() -> Parser {
         .filter(LowerCamelCaseFilterParser)
         .filter(PascalCaseFilterParser)
         .filter(ShoutyKebabCaseFilterParser)
+        .filter(ScreamingSnakeCaseFilterParser)
         .filter(ShoutySnakeCaseFilterParser)
         .filter(SnakeCaseFilterParser)
         .filter(TitleCaseFilterParser)
         .filter(UpperCamelCaseFilterParser)
+        .filter(CapitalizeFirstFilterParser)
+        .filter(SlugifyFilterParser)
+        .filter(PluralizeFilterParser)
+        .filter(SingularizeFilterParser)
+        .filter(Base64EncodeFilterParser)
+        .filter(Base64DecodeFilterParser)
+        .filter(IndentFilterParser)
+        .filter(StableHashRangeFilterParser)
+        .filter(WrapFilterParser)
+        .filter(TomlEscapeFilterParser)
+        .filter(JsonEscapeFilterParser)
+        .filter(ShellEscapeFilterParser)
         .build()
         .expect("can't fail due to no partials support")
 }
 
+/// The branch and ref the template has been generated from, both unknown for path and archive templates
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct TemplateRef {
+    pub branch: Option<String>,
+    /// the pinned tag or revision, otherwise the branch
+    pub reference: Option<String>,
+}
+
+impl TemplateRef {
+    pub fn new(location: &TemplateLocation, branch: &str) -> Self {
+        match location {
+            TemplateLocation::Git(git) => Self {
+                branch: Some(branch.to_string()),
+                reference: Some(
+                    git.tag()
+                        .or_else(|| git.revision())
+                        .unwrap_or(branch)
+                        .to_string(),
+                ),
+            },
+            TemplateLocation::Mercurial(hg) => {
+                let branch = hg.branch().unwrap_or("default").to_string();
+                Self {
+                    branch: Some(branch.clone()),
+                    reference: Some(branch),
+                }
+            }
+            TemplateLocation::GitHubRelease(release) => Self {
+                branch: None,
+                reference: Some(release.tag().to_string()),
+            },
+            TemplateLocation::Path(_) | TemplateLocation::Archive(_) => Self::default(),
+        }
+    }
+
+    /// As `template.branch` and `template.ref`, which are `nil` if unknown
+    fn into_object(self) -> Object {
+        let into_value = |v: Option<String>| v.map_or(Value::Nil, |v| Value::Scalar(v.into()));
+        let mut object = Object::new();
+        object.insert("branch".into(), into_value(self.branch));
+        object.insert("ref".into(), into_value(self.reference));
+        object
+    }
+}
+
 pub fn create_liquid_object(
     args: &GenerateArgs,
     project_dir: &Path,
     name: &ProjectName,
     crate_type: &CrateType,
+    template_ref: TemplateRef,
+    now: &Now,
 ) -> Result<Object> {
-    let authors: Authors = get_authors()?;
+    let authors: Authors = get_authors();
     let os_arch = get_os_arch();
     let project_name = args
         .force
@@ -51,29 +115,78 @@ pub fn create_liquid_object(
     );
     liquid_object.insert("authors".into(), Value::Scalar(authors.author.into()));
     liquid_object.insert("username".into(), Value::Scalar(authors.username.into()));
+    liquid_object.insert("email".into(), Value::Scalar(authors.email.into()));
     liquid_object.insert("os-arch".into(), Value::Scalar(os_arch.into()));
 
+    let mut now_object = Object::new();
+    now_object.insert("year".into(), Value::Scalar(i64::from(now.year).into()));
+    now_object.insert("date".into(), Value::Scalar(now.date.clone().into()));
+    now_object.insert(
+        "timestamp".into(),
+        Value::Scalar(now.timestamp.clone().into()),
+    );
+    liquid_object.insert("now".into(), Value::Object(now_object));
+
     liquid_object.insert(
         "within_cargo_project".into(),
         Value::Scalar(is_within_cargo_project(project_dir).into()),
     );
     liquid_object.insert("is_init".into(), Value::Scalar(args.init.into()));
+    liquid_object.insert("template".into(), Value::Object(template_ref.into_object()));
+    liquid_object.insert(
+        "project".into(),
+        Value::Object(project_dir_object(project_dir)?),
+    );
 
     Ok(liquid_object)
 }
 
+/// `project.dir`, `project.name` and `project.parent_name`, taken from the absolute `project_dir`
+fn project_dir_object(project_dir: &Path) -> Result<Object> {
+    let project_dir = project_dir.absolutize()?;
+    let name_of = |dir: Option<&Path>| {
+        dir.and_then(Path::file_name)
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+
+    let mut project_object = Object::new();
+    project_object.insert(
+        "dir".into(),
+        Value::Scalar(project_dir.display().to_string().into()),
+    );
+    project_object.insert(
+        "name".into(),
+        Value::Scalar(name_of(Some(&project_dir)).into()),
+    );
+    project_object.insert(
+        "parent_name".into(),
+        Value::Scalar(name_of(project_dir.parent()).into()),
+    );
+    Ok(project_object)
+}
+
 fn is_within_cargo_project(project_dir: &Path) -> bool {
     Path::new(project_dir)
         .ancestors()
         .any(|folder| folder.join("Cargo.toml").exists())
 }
 
+/// How many files each render thread gets per chunk, only the contents of one chunk are held at once
+const FILES_PER_RENDER_THREAD: usize = 16;
+
+/// Renders all files of `project_dir` in place.
+///
+/// The contents are rendered on a thread pool of `jobs` threads, as they only depend on the `liquid_object`.
+/// They are rendered in chunks, and everything else, like renaming, collision detection and writing, is done
+/// after each chunk in the order of the files, so the first error is always the same one.
 pub fn walk_dir(
     project_dir: &Path,
     liquid_object: &Object,
     template_config: &mut TemplateConfig,
     hook_files: &[String],
-    mp: &mut MultiProgress,
+    progress: &Progress,
+    jobs: Option<NonZeroUsize>,
 ) -> Result<()> {
     fn is_git_metadata(entry: &DirEntry) -> bool {
         entry
@@ -85,9 +198,12 @@ pub fn walk_dir(
     let engine = engine();
 
     let matcher = Matcher::new(template_config, project_dir, hook_files)?;
-    let spinner_style = spinner();
+    let match_rendered_paths = template_config.match_rendered_paths.unwrap_or(false);
+    let render_extension = template_config.render_extension().to_string();
+    let only_render_extension = template_config.render_extension.is_some();
 
     let mut files_with_errors = Vec::new();
+    let mut rendered_files = RenderedFiles::new(&render_extension);
     let files = WalkDir::new(project_dir)
         .sort_by_file_name()
         .contents_first(true)
@@ -95,79 +211,154 @@ pub fn walk_dir(
         .filter_map(Result::ok)
         .filter(|e| !is_git_metadata(e))
         .filter(|e| e.path() != project_dir)
-        .collect::<Vec<_>>();
+        .map(|entry| {
+            let relative_path = entry.path().strip_prefix(project_dir)?;
+            let match_path = if match_rendered_paths {
+                rendered_output_path(relative_path, &engine, liquid_object, &render_extension)?
+            } else {
+                relative_path.to_path_buf()
+            };
+            let rule = matcher
+                .matched_rule(&match_path)
+                .map(|rule| format!(", matched by `{}`", rule))
+                .unwrap_or_default();
+            let should_include = matcher.should_include(&match_path);
+            if matches!(should_include, ShouldInclude::Include) && entry.file_type().is_file() {
+                if let Some(raw_rule) = matcher.matched_raw_rule(&match_path) {
+                    let rule = format!(", matched by raw `{}`", raw_rule);
+                    return Ok((entry, ShouldInclude::Exclude, rule));
+                }
+                if only_render_extension
+                    && !entry
+                        .file_name()
+                        .to_string_lossy()
+                        .ends_with(render_extension.as_str())
+                {
+                    let rule = format!(", as it doesn't end with `{}`", render_extension);
+                    return Ok((entry, ShouldInclude::Exclude, rule));
+                }
+            }
+            Ok((entry, should_include, rule))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs.map_or(0, NonZeroUsize::get))
+        .build()?;
+    let chunk_size = pool.current_num_threads() * FILES_PER_RENDER_THREAD;
     let total = files.len().to_string();
-    for (progress, entry) in files.into_iter().enumerate() {
-        let pb = mp.add(ProgressBar::new(50));
-        pb.set_style(spinner_style.clone());
-        pb.set_prefix(format!(
-            "[{:width$}/{}]",
-            progress + 1,
-            total,
-            width = total.len()
-        ));
-
-        let filename = entry.path();
-        let relative_path = filename.strip_prefix(project_dir)?;
-        let f = relative_path.display();
-        pb.set_message(format!("Processing: {}", f));
-
-        // todo(refactor): as parameter
-        let verbose = false;
-
-        match matcher.should_include(relative_path) {
-            ShouldInclude::Include => {
-                if entry.file_type().is_file() {
-                    match template_process_file(liquid_object, &engine, filename) {
-                        Err(e) => {
-                            if verbose {
-                                files_with_errors.push((filename.display().to_string(), e.clone()));
-                            }
+    let verbose = log::is_verbose();
+
+    for (chunk_index, chunk) in files.chunks(chunk_size).enumerate() {
+        let bars = chunk
+            .iter()
+            .enumerate()
+            .map(|(index, (entry, _, _))| {
+                let pb = progress.add(format!(
+                    "[{:width$}/{}]",
+                    chunk_index * chunk_size + index + 1,
+                    total,
+                    width = total.len()
+                ));
+                let relative_path = entry.path().strip_prefix(project_dir)?;
+                pb.set_message(format!("Processing: {}", relative_path.display()));
+                Ok(pb)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let contents = pool.install(|| {
+            chunk
+                .par_iter()
+                .zip(&bars)
+                .map(|((entry, should_include, _), pb)| {
+                    let content = (matches!(should_include, ShouldInclude::Include)
+                        && entry.file_type().is_file())
+                    .then(|| template_process_file(liquid_object, &engine, entry.path()));
+                    pb.inc(25);
+                    content
+                })
+                .collect::<Vec<_>>()
+        });
+
+        for (((entry, should_include, rule), content), pb) in chunk.iter().zip(contents).zip(bars) {
+            let filename = entry.path();
+            let relative_path = filename.strip_prefix(project_dir)?;
+            let f = relative_path.display();
+
+            match should_include {
+                ShouldInclude::Include => match content {
+                    Some(Err(e)) => {
+                        rendered_files.insert(filename, filename)?;
+                        debug!("Copied verbatim, as it failed to render: {}{}", f, rule);
+                        if verbose {
+                            files_with_errors.push((filename.display().to_string(), e));
                         }
-                        Ok(new_contents) => {
-                            let new_filename =
-                                substitute_filename(filename, &engine, liquid_object)
-                                    .with_context(|| {
-                                        format!(
-                                            "{} {} `{}`",
-                                            emoji::ERROR,
-                                            style("Error templating a filename").bold().red(),
-                                            style(filename.display()).bold()
-                                        )
-                                    })?;
-                            pb.inc(25);
-                            let relative_path = new_filename.strip_prefix(project_dir)?;
-                            let f = relative_path.display();
-                            fs::create_dir_all(new_filename.parent().unwrap()).unwrap();
-                            fs::write(new_filename.as_path(), new_contents).with_context(|| {
-                                format!(
-                                    "{} {} `{}`",
-                                    emoji::ERROR,
-                                    style("Error writing rendered file.").bold().red(),
-                                    style(new_filename.display()).bold()
-                                )
-                            })?;
-                            pb.inc(50);
-                            pb.finish_with_message(format!("Done: {}", f));
+                    }
+                    Some(Ok(new_contents)) => {
+                        let new_contents = normalize_line_endings(
+                            new_contents,
+                            template_config.normalize_line_endings,
+                        );
+                        let new_filename = substitute_filename(
+                            filename,
+                            &engine,
+                            liquid_object,
+                            &render_extension,
+                        )
+                        .with_context(|| {
+                            format!(
+                                "{} {} `{}`",
+                                emoji::ERROR,
+                                style("Error templating a filename").bold().red(),
+                                style(filename.display()).bold()
+                            )
+                        })?;
+                        rendered_files.insert(filename, &new_filename)?;
+                        let relative_path = new_filename.strip_prefix(project_dir)?;
+                        let f = relative_path.display();
+                        fs::create_dir_all(new_filename.parent().unwrap()).unwrap();
+                        fs::write(new_filename.as_path(), new_contents).with_context(|| {
+                            format!(
+                                "{} {} `{}`",
+                                emoji::ERROR,
+                                style("Error writing rendered file.").bold().red(),
+                                style(new_filename.display()).bold()
+                            )
+                        })?;
+                        if filename != new_filename {
+                            fs::remove_file(filename)?;
+                        }
+                        pb.inc(50);
+                        debug!("Rendered: {}{}", f, rule);
+                        pb.finish_with_message(format!("Done: {}", f));
+                    }
+                    None => {
+                        let new_filename = substitute_filename(
+                            filename,
+                            &engine,
+                            liquid_object,
+                            &render_extension,
+                        )?;
+                        let relative_path = new_filename.strip_prefix(project_dir)?;
+                        let f = relative_path.display();
+                        pb.inc(50);
+                        if filename != new_filename {
+                            fs::remove_dir_all(filename)?;
                         }
+                        pb.inc(50);
+                        pb.finish_with_message(format!("Done: {}", f));
                     }
-                } else {
-                    let new_filename = substitute_filename(filename, &engine, liquid_object)?;
-                    let relative_path = new_filename.strip_prefix(project_dir)?;
-                    let f = relative_path.display();
-                    pb.inc(50);
-                    if filename != new_filename {
-                        fs::remove_dir_all(filename)?;
+                },
+                ShouldInclude::Exclude => {
+                    if entry.file_type().is_file() {
+                        rendered_files.insert(filename, filename)?;
+                        debug!("Copied verbatim, excluded from rendering: {}{}", f, rule);
                     }
-                    pb.inc(50);
-                    pb.finish_with_message(format!("Done: {}", f));
+                    pb.finish_with_message(format!("Skipped: {}", f));
+                }
+                ShouldInclude::Ignore => {
+                    debug!("Ignored, as it is a hook: {}", f);
+                    pb.finish_with_message(format!("Ignored: {}", f));
                 }
-            }
-            ShouldInclude::Exclude => {
-                pb.finish_with_message(format!("Skipped: {}", f));
-            }
-            ShouldInclude::Ignore => {
-                pb.finish_with_message(format!("Ignored: {}", f));
             }
         }
     }
@@ -179,6 +370,76 @@ pub fn walk_dir(
     Ok(())
 }
 
+/// The path `relative_path` ends up at in the generated project, with its name rendered and without
+/// the `render_extension`
+fn rendered_output_path(
+    relative_path: &Path,
+    engine: &Parser,
+    liquid_object: &Object,
+    render_extension: &str,
+) -> Result<PathBuf> {
+    let rendered = substitute_filename(relative_path, engine, liquid_object, render_extension)?;
+    let rendered_name = rendered.to_string_lossy();
+    Ok(PathBuf::from(
+        rendered_name
+            .strip_suffix(render_extension)
+            .unwrap_or(&rendered_name),
+    ))
+}
+
+/// Keeps track of the final file names, to detect templates that end up at the same path.
+///
+/// Like `copy_dir_all` does, the render extension is not part of the final name.
+struct RenderedFiles<'a> {
+    render_extension: &'a str,
+    files: HashMap<PathBuf, PathBuf>,
+}
+
+impl<'a> RenderedFiles<'a> {
+    fn new(render_extension: &'a str) -> Self {
+        Self {
+            render_extension,
+            files: HashMap::new(),
+        }
+    }
+
+    fn insert(&mut self, template_path: &Path, rendered_path: &Path) -> Result<()> {
+        let rendered_name = rendered_path.to_string_lossy();
+        let final_path = PathBuf::from(
+            rendered_name
+                .strip_suffix(self.render_extension)
+                .unwrap_or(&rendered_name),
+        );
+
+        match self.files.get(&final_path) {
+            Some(other) if other != template_path => anyhow::bail!(
+                "{} {} `{}` {} `{}` {} `{}`",
+                emoji::ERROR,
+                style("Filename collision:").bold().red(),
+                style(other.display()).bold(),
+                style("and").bold().red(),
+                style(template_path.display()).bold(),
+                style("both render to").bold().red(),
+                style(final_path.display()).bold(),
+            ),
+            _ => {
+                self.files.insert(final_path, template_path.to_path_buf());
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Only files that could be rendered end up here, binary files are never touched.
+fn normalize_line_endings(content: String, line_ending: Option<LineEnding>) -> String {
+    match line_ending {
+        None => content,
+        Some(line_ending) => content
+            .replace("\r\n", "\n")
+            .replace('\n', line_ending.as_str()),
+    }
+}
+
 fn template_process_file(
     context: &Object,
     parser: &Parser,
@@ -251,5 +512,50 @@ fn print_files_with_errors_warning(files_with_errors: Vec<(String, liquid_core::
         "Learn more: https://github.com/cargo-generate/cargo-generate#include--exclude.\n\n";
     let hint = style("Consider adding these files to a `cargo-generate.toml` in the template repo to skip substitution on these files.").bold();
 
-    println!("{}\n{}\n\n{}", msg, hint, read_more);
+    out!("{}\n{}\n\n{}", msg, hint, read_more);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_describe_the_project_dir() {
+        let project_dir = std::env::current_dir().unwrap().join("parent/my-project");
+        let object = project_dir_object(Path::new("parent/my-project")).unwrap();
+
+        assert_eq!(
+            object.get("dir"),
+            Some(&Value::Scalar(project_dir.display().to_string().into()))
+        );
+        assert_eq!(
+            object.get("name"),
+            Some(&Value::Scalar("my-project".into()))
+        );
+        assert_eq!(
+            object.get("parent_name"),
+            Some(&Value::Scalar("parent".into()))
+        );
+    }
+
+    #[test]
+    fn should_keep_line_endings_by_default() {
+        let content = "a\r\nb\nc".to_string();
+
+        assert_eq!(normalize_line_endings(content.clone(), None), content);
+    }
+
+    #[test]
+    fn should_normalize_mixed_line_endings() {
+        let content = "a\r\nb\nc\n".to_string();
+
+        assert_eq!(
+            normalize_line_endings(content.clone(), Some(LineEnding::Lf)),
+            "a\nb\nc\n"
+        );
+        assert_eq!(
+            normalize_line_endings(content, Some(LineEnding::Crlf)),
+            "a\r\nb\r\nc\r\n"
+        );
+    }
 }
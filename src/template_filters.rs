@@ -1,10 +1,13 @@
+// the `FilterParameters` derive generates `&Option<&Expression>`, which can't be allowed per struct
+#![allow(clippy::ref_option_ref)]
+
 use anyhow::Result;
 use heck::{
-    ToKebabCase, ToLowerCamelCase, ToPascalCase, ToShoutyKebabCase, ToShoutySnakeCase, ToSnakeCase,
-    ToTitleCase, ToUpperCamelCase,
+    ToKebabCase, ToLowerCamelCase, ToPascalCase, ToShoutyKebabCase, ToSnakeCase, ToUpperCamelCase,
 };
-use liquid_core::{Filter, ParseFilter, Runtime, ValueView};
-use liquid_derive::FilterReflection;
+use liquid_core::parser::FilterParameters as _;
+use liquid_core::{Expression, Filter, ParseFilter, Runtime, ValueView};
+use liquid_derive::{FilterParameters, FilterReflection, FromFilterParameters};
 
 macro_rules! create_case_filter {
     ($name:literal, $kebab_name:ident, $expr:expr) => {
@@ -45,9 +48,896 @@ create_case_filter!("lower_camel_case", LowerCamelCase, |i: String| i
 create_case_filter!("pascal_case", PascalCase, |i: String| i.to_pascal_case());
 create_case_filter!("shouty_kebab_case", ShoutyKebabCase, |i: String| i
     .to_shouty_kebab_case());
-create_case_filter!("shouty_snake_case", ShoutySnakeCase, |i: String| i
-    .to_shouty_snake_case());
+create_case_filter!("shouty_snake_case", ShoutySnakeCase, |i: String| {
+    shouty_snake_case(&i)
+});
+create_case_filter!("screaming_snake_case", ScreamingSnakeCase, |i: String| {
+    shouty_snake_case(&i)
+});
 create_case_filter!("snake_case", SnakeCase, |i: String| i.to_snake_case());
-create_case_filter!("title_case", TitleCase, |i: String| i.to_title_case());
+create_case_filter!("title_case", TitleCase, |i: String| title_case(&i));
+create_case_filter!("capitalize_first", CapitalizeFirst, |i: String| {
+    capitalize_first(&i)
+});
 create_case_filter!("upper_camel_case", UpperCamelCase, |i: String| i
     .to_upper_camel_case());
+create_case_filter!("slugify", Slugify, |i: String| slugify(&i));
+create_case_filter!("pluralize", Pluralize, |i: String| {
+    inflect_last_word(&i, pluralize)
+});
+create_case_filter!("singularize", Singularize, |i: String| {
+    inflect_last_word(&i, singularize)
+});
+
+/// The conversion of the case filter `name`, where the `_case` suffix may be left out, like `kebab`
+pub fn case_conversion(name: &str) -> Option<fn(&str) -> String> {
+    let conversion: fn(&str) -> String = match name.strip_suffix("_case").unwrap_or(name) {
+        "kebab" => |i: &str| i.to_kebab_case(),
+        "lower_camel" => |i: &str| i.to_lower_camel_case(),
+        "pascal" | "upper_camel" => |i: &str| i.to_pascal_case(),
+        "shouty_kebab" => |i: &str| i.to_shouty_kebab_case(),
+        "shouty_snake" | "screaming_snake" => shouty_snake_case,
+        "snake" => |i: &str| i.to_snake_case(),
+        "title" => title_case,
+        "capitalize_first" => capitalize_first,
+        "slugify" => slugify,
+        _ => return None,
+    };
+    Some(conversion)
+}
+
+/// Like heck's `to_shouty_snake_case`, but letters outside of ASCII are kept instead of being
+/// treated as separators, so `äpfel-und-birnen` becomes `ÄPFEL_UND_BIRNEN`
+fn shouty_snake_case(input: &str) -> String {
+    split_words(input)
+        .iter()
+        .map(|word| word.to_uppercase())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// Splits at every non alphanumeric character, between a lowercase letter or digit and an
+/// uppercase letter, and before the last letter of an uppercase run that continues lowercase
+fn split_words(input: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    for part in input.split(|c: char| !c.is_alphanumeric()) {
+        let chars: Vec<(usize, char)> = part.char_indices().collect();
+        let mut start = 0;
+        let mut after_lower = false;
+        for (n, &(i, c)) in chars.iter().enumerate() {
+            if c.is_uppercase() {
+                let prev_upper = n > 0 && chars[n - 1].1.is_uppercase();
+                let next_lower = matches!(chars.get(n + 1), Some((_, next)) if next.is_lowercase());
+                if i > start && (after_lower || (prev_upper && next_lower)) {
+                    words.push(&part[start..i]);
+                    start = i;
+                }
+                after_lower = false;
+            } else if c.is_lowercase() {
+                after_lower = true;
+            }
+        }
+        if start < part.len() {
+            words.push(&part[start..]);
+        }
+    }
+    words
+}
+
+/// Upcases only the very first character, the rest is kept as is
+fn capitalize_first(input: &str) -> String {
+    let mut chars = input.chars();
+    chars.next().map_or_else(String::new, |first| {
+        first.to_uppercase().chain(chars).collect()
+    })
+}
+
+/// Splits words like heck's title case does, but all uppercase words like `HTTP` are kept as acronyms
+fn title_case(input: &str) -> String {
+    split_words(input)
+        .iter()
+        .map(|word| {
+            if word.chars().any(char::is_lowercase) {
+                capitalize_first(&word.to_lowercase())
+            } else {
+                word.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Lowercase ASCII with runs of other characters replaced by a single `-`, accented characters are transliterated
+fn slugify(input: &str) -> String {
+    let ascii = deunicode::deunicode(input).to_lowercase();
+    ascii
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Words that are the same in singular and plural
+const UNCOUNTABLE: &[&str] = &[
+    "equipment",
+    "fish",
+    "information",
+    "metadata",
+    "news",
+    "rice",
+    "series",
+    "sheep",
+    "species",
+];
+
+/// Singular and plural of words that don't follow the rules below
+const IRREGULAR: &[(&str, &str)] = &[
+    ("alias", "aliases"),
+    ("canvas", "canvases"),
+    ("child", "children"),
+    ("cookie", "cookies"),
+    ("criterion", "criteria"),
+    ("datum", "data"),
+    ("foot", "feet"),
+    ("gas", "gases"),
+    ("goose", "geese"),
+    ("half", "halves"),
+    ("index", "indices"),
+    ("knife", "knives"),
+    ("leaf", "leaves"),
+    ("life", "lives"),
+    ("man", "men"),
+    ("matrix", "matrices"),
+    ("mouse", "mice"),
+    ("movie", "movies"),
+    ("ox", "oxen"),
+    ("person", "people"),
+    ("quiz", "quizzes"),
+    ("shelf", "shelves"),
+    ("tooth", "teeth"),
+    ("vertex", "vertices"),
+    ("wife", "wives"),
+    ("wolf", "wolves"),
+    ("woman", "women"),
+];
+
+/// Suffix rules of regular words, the first matching suffix is replaced
+const PLURAL_RULES: &[(&str, &str)] = &[
+    ("sis", "ses"),
+    ("ss", "sses"),
+    ("us", "uses"),
+    ("s", "ses"),
+    ("sh", "shes"),
+    ("ch", "ches"),
+    ("x", "xes"),
+    ("z", "zes"),
+    ("ay", "ays"),
+    ("ey", "eys"),
+    ("oy", "oys"),
+    ("uy", "uys"),
+    ("y", "ies"),
+    ("", "s"),
+];
+
+const SINGULAR_RULES: &[(&str, &str)] = &[
+    ("sses", "ss"),
+    ("ss", "ss"),
+    ("us", "us"),
+    ("is", "is"),
+    ("lyses", "lysis"),
+    ("buses", "bus"),
+    ("puses", "pus"),
+    ("ruses", "rus"),
+    ("tuses", "tus"),
+    ("ies", "y"),
+    ("shes", "sh"),
+    ("ches", "ch"),
+    ("xes", "x"),
+    ("s", ""),
+];
+
+fn apply_rules(word: &str, rules: &[(&str, &str)]) -> String {
+    rules
+        .iter()
+        .find_map(|(suffix, replacement)| {
+            word.strip_suffix(suffix)
+                .map(|stem| format!("{}{}", stem, replacement))
+        })
+        .unwrap_or_else(|| word.to_string())
+}
+
+/// Plural of a lowercase `word`, a word that is plural already is kept
+fn pluralize(word: &str) -> String {
+    if word.is_empty() || UNCOUNTABLE.contains(&word) {
+        return word.to_string();
+    }
+    if let Some((_, plural)) = IRREGULAR.iter().find(|(singular, _)| *singular == word) {
+        return (*plural).to_string();
+    }
+    if IRREGULAR.iter().any(|(_, plural)| *plural == word) {
+        return word.to_string();
+    }
+
+    let singular = apply_rules(word, SINGULAR_RULES);
+    if singular != word && apply_rules(&singular, PLURAL_RULES) == word {
+        return word.to_string();
+    }
+    apply_rules(word, PLURAL_RULES)
+}
+
+/// Singular of a lowercase `word`, a word that is singular already is kept
+fn singularize(word: &str) -> String {
+    if UNCOUNTABLE.contains(&word) || IRREGULAR.iter().any(|(singular, _)| *singular == word) {
+        return word.to_string();
+    }
+    if let Some((singular, _)) = IRREGULAR.iter().find(|(_, plural)| *plural == word) {
+        return (*singular).to_string();
+    }
+    apply_rules(word, SINGULAR_RULES)
+}
+
+/// Inflects the last word of an identifier, like `user_account`, `UserAccount` or `USER_ACCOUNT`,
+/// keeping the case of the unchanged part of the word, while a changed ending follows a shouty word
+fn inflect_last_word(input: &str, inflect: fn(&str) -> String) -> String {
+    let mut start = 0;
+    let mut prev: Option<char> = None;
+    for (i, c) in input.char_indices() {
+        let hump = matches!(prev, Some(p) if p.is_lowercase() || p.is_numeric());
+        if c == '_' || c == '-' || c.is_whitespace() {
+            start = i + c.len_utf8();
+        } else if c.is_uppercase() && hump {
+            start = i;
+        }
+        prev = Some(c);
+    }
+
+    let (prefix, word) = input.split_at(start);
+    let lower = word.to_lowercase();
+    let inflected = inflect(&lower);
+    let unchanged = lower
+        .chars()
+        .zip(inflected.chars())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let shouty = word.chars().filter(|c| c.is_alphabetic()).count() > 1
+        && !word.chars().any(char::is_lowercase);
+
+    let kept: String = word.chars().take(unchanged).collect();
+    let ending: String = inflected.chars().skip(unchanged).collect();
+    let ending = if shouty {
+        ending.to_uppercase()
+    } else {
+        ending
+    };
+    format!("{}{}{}", prefix, kept, ending)
+}
+
+macro_rules! create_fallible_filter {
+    ($name:literal, $kebab_name:ident, $description:literal, $expr:expr) => {
+        paste::paste! {
+            #[derive(Clone, ParseFilter, FilterReflection)]
+            #[filter(
+                name = $name,
+                description = $description,
+                parsed([<$kebab_name Filter>])
+            )]
+            pub struct [<$kebab_name Filter Parser>];
+
+            #[derive(Debug, Default, liquid_derive::Display_filter)]
+            #[name = $name]
+            struct [<$kebab_name Filter>];
+
+            impl Filter for [<$kebab_name Filter>] {
+                fn evaluate(
+                    &self,
+                    input: &dyn ValueView,
+                    _runtime: &dyn Runtime,
+                ) -> Result<liquid_core::model::Value, liquid_core::error::Error> {
+                    let input = input
+                        .as_scalar()
+                        .ok_or_else(|| liquid_core::error::Error::with_msg("String expected"))?;
+
+                    let input = $expr(input.into_string().to_string())?;
+                    Ok(liquid_core::model::Value::scalar(input))
+                }
+            }
+        }
+    };
+}
+
+// liquid values are always valid UTF-8, so encoding works on the raw bytes of the string,
+// while decoding fails for content that is not UTF-8 instead of silently mangling it
+create_fallible_filter!(
+    "base64_encode",
+    Base64Encode,
+    "Encode text as base64",
+    |i: String| -> Result<String, liquid_core::error::Error> { Ok(base64::encode(i.as_bytes())) }
+);
+create_fallible_filter!(
+    "base64_decode",
+    Base64Decode,
+    "Decode base64 to text",
+    |i: String| -> Result<String, liquid_core::error::Error> {
+        let bytes = base64::decode(i.trim()).map_err(|e| {
+            liquid_core::error::Error::with_msg(format!("Invalid base64 input: {}", e))
+        })?;
+        String::from_utf8(bytes).map_err(|_| {
+            liquid_core::error::Error::with_msg("Decoded base64 is not valid UTF-8 text")
+        })
+    }
+);
+
+// the escape filters produce a complete, quoted value, so templates write `key = {{ x | toml_escape }}`
+create_fallible_filter!(
+    "toml_escape",
+    TomlEscape,
+    "Quote text as a TOML string",
+    |i: String| -> Result<String, liquid_core::error::Error> { Ok(toml_escape(&i)) }
+);
+create_fallible_filter!(
+    "json_escape",
+    JsonEscape,
+    "Quote text as a JSON string",
+    |i: String| -> Result<String, liquid_core::error::Error> {
+        serde_json::to_string(&i).map_err(|e| {
+            liquid_core::error::Error::with_msg(format!("Cannot escape as JSON: {}", e))
+        })
+    }
+);
+create_fallible_filter!(
+    "shell_escape",
+    ShellEscape,
+    "Quote text as a single POSIX shell word",
+    |i: String| -> Result<String, liquid_core::error::Error> { Ok(shell_escape(&i)) }
+);
+
+/// Quotes `input` as a TOML basic string, escaping quotes, backslashes and control chars.
+fn toml_escape(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len() + 2);
+    escaped.push('"');
+    for c in input.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '\u{8}' => escaped.push_str("\\b"),
+            '\u{c}' => escaped.push_str("\\f"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04X}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// Quotes `input` in single quotes, where nothing is special but the single quote itself.
+fn shell_escape(input: &str) -> String {
+    format!("'{}'", input.replace('\'', r"'\''"))
+}
+
+#[derive(Debug, FilterParameters)]
+struct IndentArgs {
+    #[parameter(
+        description = "The number of spaces to indent by.",
+        arg_type = "integer"
+    )]
+    width: Expression,
+    #[parameter(
+        description = "Whether to indent the first line as well.",
+        arg_type = "bool",
+        mode = "keyword"
+    )]
+    first: Option<Expression>,
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "indent",
+    description = "Indent every line but the first by a number of spaces",
+    parameters(IndentArgs),
+    parsed(IndentFilter)
+)]
+pub struct IndentFilterParser;
+
+#[derive(Debug, FromFilterParameters, liquid_derive::Display_filter)]
+#[name = "indent"]
+struct IndentFilter {
+    #[parameters]
+    args: IndentArgs,
+}
+
+impl Filter for IndentFilter {
+    fn evaluate(
+        &self,
+        input: &dyn ValueView,
+        runtime: &dyn Runtime,
+    ) -> Result<liquid_core::model::Value, liquid_core::error::Error> {
+        let args = self.args.evaluate(runtime)?;
+        let width = usize::try_from(args.width)
+            .map_err(|_| liquid_core::error::Error::with_msg("Width must not be negative"))?;
+        let input = input
+            .as_scalar()
+            .ok_or_else(|| liquid_core::error::Error::with_msg("String expected"))?;
+
+        let input = indent(&input.into_string(), width, args.first.unwrap_or(false));
+        Ok(liquid_core::model::Value::scalar(input))
+    }
+}
+
+/// Prefixes the lines of `input` with `width` spaces, the first one only if `first` is set.
+/// Blank lines, like the one after a trailing newline, are kept as they are, and so are `\r\n` line endings.
+fn indent(input: &str, width: usize, first: bool) -> String {
+    let prefix = " ".repeat(width);
+    input
+        .split_inclusive('\n')
+        .enumerate()
+        .map(|(i, line)| {
+            let blank = line.trim_end_matches(['\r', '\n']).is_empty();
+            if blank || (i == 0 && !first) {
+                line.to_string()
+            } else {
+                format!("{}{}", prefix, line)
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, FilterParameters)]
+struct WrapArgs {
+    #[parameter(
+        description = "The column width to wrap at, including the prefix.",
+        arg_type = "integer"
+    )]
+    width: Expression,
+    #[parameter(
+        description = "The prefix of each line, like `// `.",
+        arg_type = "str",
+        mode = "keyword"
+    )]
+    prefix: Option<Expression>,
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "wrap",
+    description = "Wrap the words of each paragraph at a column width",
+    parameters(WrapArgs),
+    parsed(WrapFilter)
+)]
+pub struct WrapFilterParser;
+
+#[derive(Debug, FromFilterParameters, liquid_derive::Display_filter)]
+#[name = "wrap"]
+struct WrapFilter {
+    #[parameters]
+    args: WrapArgs,
+}
+
+impl Filter for WrapFilter {
+    fn evaluate(
+        &self,
+        input: &dyn ValueView,
+        runtime: &dyn Runtime,
+    ) -> Result<liquid_core::model::Value, liquid_core::error::Error> {
+        let args = self.args.evaluate(runtime)?;
+        let width = usize::try_from(args.width)
+            .ok()
+            .filter(|width| *width > 0)
+            .ok_or_else(|| liquid_core::error::Error::with_msg("Width must be positive"))?;
+        let input = input
+            .as_scalar()
+            .ok_or_else(|| liquid_core::error::Error::with_msg("String expected"))?;
+        let prefix = args.prefix.as_deref().unwrap_or("");
+
+        let input = wrap(&input.into_string(), width, prefix);
+        Ok(liquid_core::model::Value::scalar(input))
+    }
+}
+
+/// Fills the words of each paragraph of `input` into lines of at most `width` chars, `prefix` included.
+/// Paragraphs are separated by blank lines, which are kept. A word longer than the width gets a line of its own.
+fn wrap(input: &str, width: usize, prefix: &str) -> String {
+    let text_width = width.saturating_sub(prefix.chars().count()).max(1);
+    let mut lines = vec![];
+    let mut line = String::new();
+    let mut line_width = 0;
+    for input_line in input.lines() {
+        if input_line.trim().is_empty() {
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            lines.push(String::new());
+            continue;
+        }
+        for word in input_line.split_whitespace() {
+            let word_width = word.chars().count();
+            if !line.is_empty() && line_width + 1 + word_width > text_width {
+                lines.push(std::mem::take(&mut line));
+            }
+            if line.is_empty() {
+                line_width = word_width;
+            } else {
+                line.push(' ');
+                line_width += 1 + word_width;
+            }
+            line.push_str(word);
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+
+    let mut wrapped = lines
+        .iter()
+        .map(|line| {
+            if line.is_empty() {
+                prefix.trim_end().to_string()
+            } else {
+                format!("{}{}", prefix, line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    if input.ends_with('\n') {
+        wrapped.push('\n');
+    }
+    wrapped
+}
+
+#[derive(Debug, FilterParameters)]
+struct StableHashRangeArgs {
+    #[parameter(description = "The lower bound, inclusive.", arg_type = "integer")]
+    min: Expression,
+    #[parameter(description = "The upper bound, inclusive.", arg_type = "integer")]
+    max: Expression,
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "stable_hash_range",
+    description = "Maps the input to a stable number within a range, like a port",
+    parameters(StableHashRangeArgs),
+    parsed(StableHashRangeFilter)
+)]
+pub struct StableHashRangeFilterParser;
+
+#[derive(Debug, FromFilterParameters, liquid_derive::Display_filter)]
+#[name = "stable_hash_range"]
+struct StableHashRangeFilter {
+    #[parameters]
+    args: StableHashRangeArgs,
+}
+
+impl Filter for StableHashRangeFilter {
+    fn evaluate(
+        &self,
+        input: &dyn ValueView,
+        runtime: &dyn Runtime,
+    ) -> Result<liquid_core::model::Value, liquid_core::error::Error> {
+        let args = self.args.evaluate(runtime)?;
+        if args.min > args.max {
+            return Err(liquid_core::error::Error::with_msg(
+                "Min must not be greater than max",
+            ));
+        }
+        let input = input
+            .as_scalar()
+            .ok_or_else(|| liquid_core::error::Error::with_msg("String expected"))?;
+
+        let value = stable_hash_range(&input.into_string(), args.min, args.max);
+        Ok(liquid_core::model::Value::scalar(value))
+    }
+}
+
+/// Maps `input` to a number within `min..=max` by its FNV-1a hash, which unlike the hasher of std
+/// is guaranteed to stay the same across Rust versions and platforms. It is not cryptographic.
+fn stable_hash_range(input: &str, min: i64, max: i64) -> i64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+    let hash = input.bytes().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    });
+    let span = i128::from(max) - i128::from(min) + 1;
+    let offset = i128::from(hash) % span;
+    // `offset` is below `span`, so the sum is within `min..=max`
+    (i128::from(min) + offset) as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(filter: &str, input: &str) -> String {
+        let parser = liquid::ParserBuilder::with_stdlib()
+            .filter(ShoutySnakeCaseFilterParser)
+            .filter(ScreamingSnakeCaseFilterParser)
+            .filter(TitleCaseFilterParser)
+            .filter(CapitalizeFirstFilterParser)
+            .filter(SlugifyFilterParser)
+            .filter(PluralizeFilterParser)
+            .filter(SingularizeFilterParser)
+            .filter(Base64EncodeFilterParser)
+            .filter(Base64DecodeFilterParser)
+            .filter(IndentFilterParser)
+            .filter(StableHashRangeFilterParser)
+            .filter(WrapFilterParser)
+            .filter(TomlEscapeFilterParser)
+            .filter(JsonEscapeFilterParser)
+            .filter(ShellEscapeFilterParser)
+            .build()
+            .unwrap();
+        let mut globals = liquid::Object::new();
+        globals.insert(
+            "input".into(),
+            liquid_core::Value::scalar(input.to_string()),
+        );
+
+        parser
+            .parse(&format!("{{{{ input | {} }}}}", filter))
+            .unwrap()
+            .render(&globals)
+            .unwrap()
+    }
+
+    #[test]
+    fn should_convert_to_shouty_snake_case() {
+        for filter in ["shouty_snake_case", "screaming_snake_case"] {
+            assert_eq!(render(filter, "my-project"), "MY_PROJECT");
+            assert_eq!(render(filter, "my_project"), "MY_PROJECT");
+            assert_eq!(render(filter, "MyProject"), "MY_PROJECT");
+            assert_eq!(render(filter, "my--project__name"), "MY_PROJECT_NAME");
+            assert_eq!(render(filter, "HTTPServer"), "HTTP_SERVER");
+        }
+    }
+
+    #[test]
+    fn should_convert_unicode_and_numbers_to_shouty_snake_case() {
+        assert_eq!(
+            render("screaming_snake_case", "äpfel-und-birnen"),
+            "ÄPFEL_UND_BIRNEN"
+        );
+        assert_eq!(render("screaming_snake_case", "straßeÖl"), "STRASSE_ÖL");
+        assert_eq!(
+            render("screaming_snake_case", "abc123Def456"),
+            "ABC123_DEF456"
+        );
+        assert_eq!(
+            render("screaming_snake_case", "FIELD_NAME11"),
+            "FIELD_NAME11"
+        );
+    }
+
+    #[test]
+    fn should_convert_to_title_case_keeping_acronyms() {
+        assert_eq!(render("title_case", "my-project"), "My Project");
+        assert_eq!(render("title_case", "my_project"), "My Project");
+        assert_eq!(render("title_case", "my--project__name"), "My Project Name");
+        assert_eq!(render("title_case", "HTTP-server"), "HTTP Server");
+        assert_eq!(render("title_case", "my_API_client"), "My API Client");
+        assert_eq!(render("title_case", "myProject"), "My Project");
+        assert_eq!(render("title_case", "MyHttpClient"), "My Http Client");
+        assert_eq!(
+            render("title_case", "Already Title Case"),
+            "Already Title Case"
+        );
+        assert_eq!(render("title_case", "äpfel-und-birnen"), "Äpfel Und Birnen");
+    }
+
+    #[test]
+    fn should_capitalize_only_the_first_character() {
+        assert_eq!(render("capitalize_first", "my-project"), "My-project");
+        assert_eq!(render("capitalize_first", "my_project"), "My_project");
+        assert_eq!(render("capitalize_first", "hTTP-server"), "HTTP-server");
+        assert_eq!(render("capitalize_first", "Already done"), "Already done");
+        assert_eq!(render("capitalize_first", "äpfel"), "Äpfel");
+        assert_eq!(render("capitalize_first", ""), "");
+    }
+
+    #[test]
+    fn should_slugify_unicode_text() {
+        assert_eq!(render("slugify", "Café Déjà Vu"), "cafe-deja-vu");
+        assert_eq!(render("slugify", "  Hello, World!  "), "hello-world");
+        assert_eq!(render("slugify", "my__project--name"), "my-project-name");
+        assert_eq!(render("slugify", "Straße 42"), "strasse-42");
+        assert_eq!(render("slugify", "--already-a-slug--"), "already-a-slug");
+        assert_eq!(render("slugify", "!!!"), "");
+    }
+
+    #[test]
+    fn should_inflect_regular_words() {
+        for (singular, plural) in [
+            ("user", "users"),
+            ("city", "cities"),
+            ("key", "keys"),
+            ("box", "boxes"),
+            ("branch", "branches"),
+            ("class", "classes"),
+            ("status", "statuses"),
+            ("analysis", "analyses"),
+        ] {
+            assert_eq!(render("pluralize", singular), plural);
+            assert_eq!(render("singularize", plural), singular);
+        }
+    }
+
+    #[test]
+    fn should_inflect_irregular_and_uncountable_words() {
+        for (singular, plural) in [
+            ("person", "people"),
+            ("index", "indices"),
+            ("child", "children"),
+            ("datum", "data"),
+            ("sheep", "sheep"),
+            ("series", "series"),
+        ] {
+            assert_eq!(render("pluralize", singular), plural);
+            assert_eq!(render("singularize", plural), singular);
+        }
+    }
+
+    #[test]
+    fn should_keep_words_already_inflected() {
+        assert_eq!(render("pluralize", "users"), "users");
+        assert_eq!(render("pluralize", "cities"), "cities");
+        assert_eq!(render("pluralize", "people"), "people");
+        assert_eq!(render("pluralize", "indices"), "indices");
+        assert_eq!(render("singularize", "user"), "user");
+        assert_eq!(render("singularize", "status"), "status");
+        assert_eq!(render("singularize", "person"), "person");
+    }
+
+    #[test]
+    fn should_inflect_the_last_word_of_identifiers() {
+        assert_eq!(render("pluralize", "user_account"), "user_accounts");
+        assert_eq!(render("pluralize", "user-category"), "user-categories");
+        assert_eq!(render("pluralize", "UserAccount"), "UserAccounts");
+        assert_eq!(render("pluralize", "AdminPerson"), "AdminPeople");
+        assert_eq!(render("pluralize", "USER_INDEX"), "USER_INDICES");
+        assert_eq!(render("singularize", "UserAccounts"), "UserAccount");
+        assert_eq!(render("singularize", "api_people"), "api_person");
+        assert_eq!(render("pluralize", ""), "");
+    }
+
+    #[test]
+    fn should_encode_known_base64_vectors() {
+        // test vectors of RFC 4648
+        for (input, expected) in [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(render("base64_encode", input), expected);
+            assert_eq!(render("base64_decode", expected), input);
+        }
+    }
+
+    #[test]
+    fn should_round_trip_multibyte_text_through_base64() {
+        assert_eq!(render("base64_encode", "äpfel"), "w6RwZmVs");
+        assert_eq!(
+            render("base64_decode", &render("base64_encode", "äpfel")),
+            "äpfel"
+        );
+    }
+
+    #[test]
+    fn should_fail_to_decode_invalid_or_binary_base64() {
+        let parser = liquid::ParserBuilder::with_stdlib()
+            .filter(Base64DecodeFilterParser)
+            .build()
+            .unwrap();
+        let template = parser.parse("{{ input | base64_decode }}").unwrap();
+
+        for input in ["not base64!", "/w=="] {
+            let mut globals = liquid::Object::new();
+            globals.insert("input".into(), liquid_core::Value::scalar(input));
+            assert!(template.render(&globals).is_err());
+        }
+    }
+
+    #[test]
+    fn should_indent_all_but_the_first_line() {
+        assert_eq!(
+            render("indent: 4", "a:\n  b: 1\nc: 2"),
+            "a:\n      b: 1\n    c: 2"
+        );
+        assert_eq!(render("indent: 2, first: true", "a\nb"), "  a\n  b");
+        assert_eq!(render("indent: 0, first: true", "a\nb"), "a\nb");
+        assert_eq!(render("indent: 2", "single"), "single");
+        assert_eq!(render("indent: 2", ""), "");
+    }
+
+    #[test]
+    fn should_not_indent_blank_lines_and_trailing_newlines() {
+        assert_eq!(render("indent: 2, first: true", "a\n\nb\n"), "  a\n\n  b\n");
+        assert_eq!(
+            render("indent: 2, first: true", "a\r\n\r\nb\r\n"),
+            "  a\r\n\r\n  b\r\n"
+        );
+        assert_eq!(render("indent: 4", "a\r\nb\r\nc"), "a\r\n    b\r\n    c");
+    }
+
+    #[test]
+    fn should_wrap_words_at_the_width() {
+        assert_eq!(
+            render("wrap: 10", "the quick brown fox jumps"),
+            "the quick\nbrown fox\njumps"
+        );
+        assert_eq!(render("wrap: 80", "short"), "short");
+        assert_eq!(render("wrap: 10", ""), "");
+    }
+
+    #[test]
+    fn should_wrap_with_a_prefix_and_keep_paragraphs() {
+        assert_eq!(
+            render(
+                r#"wrap: 12, prefix: "// ""#,
+                "one two three\nfour\n\nfive\n"
+            ),
+            "// one two\n// three\n// four\n//\n// five\n"
+        );
+    }
+
+    #[test]
+    fn should_put_words_longer_than_the_width_on_a_line_of_their_own() {
+        assert_eq!(render("wrap: 5", "a verylongword b"), "a\nverylongword\nb");
+        assert_eq!(render(r#"wrap: 2, prefix: "// ""#, "a b"), "// a\n// b");
+    }
+
+    #[test]
+    fn should_map_to_a_stable_number_within_the_range() {
+        let port = render("stable_hash_range: 3000, 3999", "my-project");
+        assert_eq!(port, render("stable_hash_range: 3000, 3999", "my-project"));
+        assert_eq!(
+            port,
+            stable_hash_range("my-project", 3000, 3999).to_string()
+        );
+        // pinned, so a change of the hash would be noticed as it changes generated projects
+        assert_eq!(port, "3659");
+        assert_ne!(
+            stable_hash_range("my-project", 3000, 3999),
+            stable_hash_range("other-project", 3000, 3999)
+        );
+
+        for name in ["", "a", "my-project", "äpfel", "a-much-longer-project-name"] {
+            let value = stable_hash_range(name, 3000, 3999);
+            assert!((3000..=3999).contains(&value), "{} out of range", value);
+        }
+        assert_eq!(stable_hash_range("my-project", 7, 7), 7);
+        assert!((i64::MIN..=i64::MAX).contains(&stable_hash_range("x", i64::MIN, i64::MAX)));
+    }
+
+    #[test]
+    fn should_quote_text_as_a_toml_string() {
+        let input = "say \"hi\"\nC:\\temp\t\u{1}";
+        let rendered = render("toml_escape", input);
+        assert_eq!(rendered, r#""say \"hi\"\nC:\\temp\t\u0001""#);
+
+        let parsed: toml::Value = toml::from_str(&format!("v = {}", rendered)).unwrap();
+        assert_eq!(parsed["v"].as_str(), Some(input));
+    }
+
+    #[test]
+    fn should_quote_text_as_a_json_string() {
+        let input = "say \"hi\"\nC:\\temp";
+        let rendered = render("json_escape", input);
+        assert_eq!(rendered, r#""say \"hi\"\nC:\\temp""#);
+
+        let parsed: String = serde_json::from_str(&rendered).unwrap();
+        assert_eq!(parsed, input);
+    }
+
+    #[test]
+    fn should_quote_text_as_a_shell_word() {
+        assert_eq!(render("shell_escape", "it's"), r"'it'\''s'");
+        assert_eq!(render("shell_escape", "a \\ b\n$HOME"), "'a \\ b\n$HOME'");
+        assert_eq!(render("shell_escape", ""), "''");
+    }
+}
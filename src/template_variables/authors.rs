@@ -1,22 +1,29 @@
-use anyhow::Result;
-use git2::{Config as GitConfig, Repository as GitRepository};
 use std::env;
 
+use crate::git;
+
+/// Stands in for the name when it can't be determined, e.g. on CI without any git config
+const UNKNOWN_NAME: &str = "Your Name";
+
 pub struct Authors {
     pub author: String,
     pub username: String,
+    /// empty if unknown
+    pub email: String,
 }
 
 /// Taken from cargo and thus (c) 2020 Cargo Developers
 ///
 /// cf. <https://github.com/rust-lang/cargo/blob/2d5c2381e4e50484bf281fc1bfe19743aa9eb37a/src/cargo/ops/cargo_new.rs#L769-L851>
-pub fn get_authors() -> Result<Authors> {
+///
+/// Unlike cargo it doesn't fail without a name, but falls back to a placeholder name.
+pub fn get_authors() -> Authors {
     fn get_environment_variable(variables: &[&str]) -> Option<String> {
         variables.iter().filter_map(|var| env::var(var).ok()).next()
     }
 
-    fn discover_author() -> Result<(String, Option<String>)> {
-        let git_config = find_real_git_config();
+    fn discover_author() -> (String, Option<String>) {
+        let git_config = git::user_config();
         let git_config = git_config.as_ref();
 
         let name_variables = [
@@ -31,16 +38,9 @@ pub fn get_authors() -> Result<Authors> {
             .or_else(|| git_config.and_then(|g| g.get_string("user.name").ok()))
             .or_else(|| get_environment_variable(&name_variables[3..]));
 
-        let name = match name {
-            Some(name) => name,
-            None => {
-                let username_var = if cfg!(windows) { "USERNAME" } else { "USER" };
-                anyhow::bail!(
-                    "could not determine the current user, please set ${}",
-                    username_var
-                )
-            }
-        };
+        let name = name
+            .filter(|name| !name.trim().is_empty())
+            .unwrap_or_else(|| UNKNOWN_NAME.to_string());
         let email_variables = [
             "CARGO_EMAIL",
             "GIT_AUTHOR_EMAIL",
@@ -64,29 +64,19 @@ pub fn get_authors() -> Result<Authors> {
             s.to_string()
         });
 
-        Ok((name, email))
+        (name, email)
     }
 
-    fn find_real_git_config() -> Option<GitConfig> {
-        match env::current_dir() {
-            Ok(cwd) => GitRepository::discover(cwd)
-                .and_then(|repo| repo.config())
-                .or_else(|_| GitConfig::open_default())
-                .ok(),
-            Err(_) => GitConfig::open_default().ok(),
-        }
-    }
-
-    let author = match discover_author()? {
-        (name, Some(email)) => Authors {
+    match discover_author() {
+        (name, Some(email)) if !email.is_empty() => Authors {
             author: format!("{} <{}>", name, email),
             username: name,
+            email,
         },
-        (name, None) => Authors {
+        (name, _) => Authors {
             author: name.clone(),
             username: name,
+            email: String::new(),
         },
-    };
-
-    Ok(author)
+    }
 }
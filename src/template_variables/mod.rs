@@ -1,98 +1,196 @@
 mod authors;
 mod crate_type;
+mod now;
 mod os_arch;
 mod project_name;
 
-use crate::{emoji, GenerateArgs};
+use crate::{emoji, log, out, GenerateArgs};
 
 use anyhow::Result;
 use console::style;
 use regex::Regex;
 use serde::Deserialize;
-use std::{collections::HashMap, fmt::Display, fs, path::Path};
+use std::{collections::HashMap, ffi::OsString, fmt::Display, fs, path::Path};
 use toml::Value;
 
 pub use authors::{get_authors, Authors};
 pub use crate_type::CrateType;
-pub use os_arch::get_os_arch;
+pub use now::{get_now, Now};
+pub use os_arch::{get_os_arch, get_platform_variables};
 pub use project_name::ProjectName;
 
-fn load_env_template_values() -> Result<HashMap<String, toml::Value>> {
+/// Prefix of env vars that provide the value of a single placeholder, like `CARGO_GENERATE_VALUE_FOO`
+const ENV_VALUE_PREFIX: &str = "CARGO_GENERATE_VALUE_";
+
+fn load_env_template_values_file() -> Result<HashMap<String, toml::Value>> {
     //FIXME: use this variable to be in sync with args
-    let mut values = std::env::var("CARGO_GENERATE_TEMPLATE_VALUES_FILE")
+    std::env::var("CARGO_GENERATE_TEMPLATE_VALUES_FILE")
         .ok()
         .map_or(Ok(Default::default()), |path| {
             read_template_values_file(Path::new(&path))
-        })?;
-
-    values.extend(std::env::vars().filter_map(|(key, value)| {
-        key.strip_prefix("CARGO_GENERATE_VALUE_")
-            .map(|key| (key.to_lowercase(), Value::from(value)))
-    }));
+        })
+}
 
-    Ok(values)
+/// Collects all `CARGO_GENERATE_VALUE_<NAME>` vars as placeholder `<name>`.
+/// Vars whose name or value is not valid unicode are skipped.
+fn read_template_values_from_env(
+    vars: impl Iterator<Item = (OsString, OsString)>,
+) -> HashMap<String, toml::Value> {
+    vars.filter_map(|(key, value)| {
+        let key = key.into_string().ok()?;
+        let name = key.strip_prefix(ENV_VALUE_PREFIX)?;
+        let value = value.into_string().ok()?;
+        Some((name.to_lowercase(), Value::from(value)))
+    })
+    .collect()
 }
 
-fn load_args_template_values(args: &GenerateArgs) -> Result<HashMap<String, toml::Value>> {
-    let mut values = args
-        .template_values_file
+fn load_args_template_values_file(args: &GenerateArgs) -> Result<HashMap<String, toml::Value>> {
+    args.template_values_file
         .as_ref()
         .map(Path::new)
         .map_or(Ok(Default::default()), |path| {
             read_template_values_file(path)
-        })?;
-
-    values.extend(read_template_values_from_definitions(&args.define)?);
-    Ok(values)
+        })
 }
 
+/// Loads all provided template values, in order of increasing precedence:
+/// values files, `CARGO_GENERATE_VALUE_*` env vars, `--define-from-json` and `--define` args.
 pub fn load_env_and_args_template_values(
     args: &GenerateArgs,
 ) -> Result<HashMap<String, toml::Value>> {
-    let mut template_variables = load_env_template_values()?;
-    template_variables.extend(load_args_template_values(args)?);
+    let mut template_variables = load_env_template_values_file()?;
+    template_variables.extend(load_args_template_values_file(args)?);
+    template_variables.extend(read_template_values_from_env(std::env::vars_os()));
+    if let Some(ref path) = args.define_from_json {
+        template_variables.extend(read_template_values_from_json(path)?);
+    }
+    template_variables.extend(read_template_values_from_definitions(&args.define)?);
     Ok(template_variables)
 }
 
+/// Reads a flat JSON object, only scalars are supported as values
+fn read_template_values_from_json(path: &Path) -> Result<HashMap<String, Value>> {
+    let json_error = |e: &dyn Display| {
+        anyhow::anyhow!(
+            "{} {} {}",
+            emoji::ERROR,
+            style(format!("JSON Values Error in `{}`:", path.display()))
+                .bold()
+                .red(),
+            style(e).bold().red(),
+        )
+    };
+    let contents = fs::read_to_string(path).map_err(|e| json_error(&e))?;
+    let object = match serde_json::from_str(&contents).map_err(|e| json_error(&e))? {
+        serde_json::Value::Object(object) => object,
+        _ => return Err(json_error(&"expected a JSON object of key/value pairs")),
+    };
+
+    object
+        .into_iter()
+        .map(|(key, value)| {
+            let value = match value {
+                serde_json::Value::String(s) => Value::String(s),
+                serde_json::Value::Bool(b) => Value::Boolean(b),
+                serde_json::Value::Number(n) => n.as_i64().map_or_else(
+                    || Value::Float(n.as_f64().unwrap_or_default()),
+                    Value::Integer,
+                ),
+                serde_json::Value::Null
+                | serde_json::Value::Array(_)
+                | serde_json::Value::Object(_) => {
+                    return Err(json_error(&format!(
+                        "the value of `{}` must be a string, bool or number",
+                        key
+                    )))
+                }
+            };
+            Ok((key, value))
+        })
+        .collect()
+}
+
+/// Fails if the file is missing, or is not TOML with a `[values]` table
 fn read_template_values_file(path: &Path) -> Result<HashMap<String, Value>> {
-    match fs::read_to_string(path) {
-        Ok(ref contents) => toml::from_str::<TemplateValuesToml>(contents)
-            .map(|v| v.values)
-            .map_err(|e| e.into()),
-        Err(e) => anyhow::bail!(
+    let values_file_error = |e: &dyn Display| {
+        anyhow::anyhow!(
             "{} {} {}",
             emoji::ERROR,
-            style("Values File Error:").bold().red(),
+            style(format!("Values File Error in `{}`:", path.display()))
+                .bold()
+                .red(),
             style(e).bold().red(),
-        ),
-    }
+        )
+    };
+    let contents = fs::read_to_string(path).map_err(|e| values_file_error(&e))?;
+    toml::from_str::<TemplateValuesToml>(&contents)
+        .map(|v| v.values)
+        .map_err(|e| values_file_error(&e))
 }
 
 fn read_template_values_from_definitions<S: AsRef<str> + Display>(
     definitions: &[S],
 ) -> Result<HashMap<String, toml::Value>> {
-    let mut values = HashMap::with_capacity(definitions.len());
-    let key_value_regex = Regex::new(r"^([a-zA-Z]+[a-zA-Z0-9\-_]*)\s*=\s*(.+)$").unwrap();
-
-    definitions.iter().try_fold(
-        &mut values,
-        |template_values, definition| match key_value_regex.captures(definition.as_ref()) {
-            Some(cap) => {
-                let key = cap.get(1).unwrap().as_str().to_string();
-                let value = cap.get(2).unwrap().as_str().to_string();
-                println!("{} => '{}'", key, value);
-                template_values.insert(key, Value::from(value));
-                Ok(template_values)
+    Ok(split_definitions(definitions)?
+        .into_iter()
+        .map(|(key, value)| {
+            if !log::is_quiet() {
+                out!("{} => '{}'", key, value);
             }
-            None => Err(anyhow::anyhow!(
-                "{} {} {}",
-                emoji::ERROR,
-                style("Failed to parse value:").bold().red(),
-                style(definition).bold().red(),
-            )),
-        },
-    )?;
-    Ok(values)
+            let value = parse_scalar(&value);
+            (key, value)
+        })
+        .collect())
+}
+
+/// The `--define` values as they have been written, for the ones that [`parse_scalar`] coerces
+/// into a bool or number. A string placeholder takes these, so `-d version=0.10` stays `0.10`.
+pub fn raw_definitions<S: AsRef<str> + Display>(
+    definitions: &[S],
+) -> Result<HashMap<String, String>> {
+    Ok(split_definitions(definitions)?
+        .into_iter()
+        .filter(|(_, value)| !parse_scalar(value).is_str())
+        .collect())
+}
+
+/// Splits each `key=value` definition, in order
+fn split_definitions<S: AsRef<str> + Display>(definitions: &[S]) -> Result<Vec<(String, String)>> {
+    let key_value_regex = Regex::new(r"^([a-zA-Z]+[a-zA-Z0-9\-_\.]*)\s*=\s*(.+)$").unwrap();
+
+    definitions
+        .iter()
+        .map(
+            |definition| match key_value_regex.captures(definition.as_ref()) {
+                Some(cap) => Ok((
+                    cap.get(1).unwrap().as_str().to_string(),
+                    cap.get(2).unwrap().as_str().to_string(),
+                )),
+                None => Err(anyhow::anyhow!(
+                    "{} {} {}",
+                    emoji::ERROR,
+                    style("Failed to parse value:").bold().red(),
+                    style(definition).bold().red(),
+                )),
+            },
+        )
+        .collect()
+}
+
+/// parses a value as TOML scalar, so `true` becomes a bool and `3` an integer,
+/// anything else is taken as plain string
+fn parse_scalar(value: &str) -> Value {
+    toml::from_str::<toml::value::Table>(&format!("value = {}", value))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .filter(|v| {
+            matches!(
+                v,
+                Value::Boolean(_) | Value::Integer(_) | Value::Float(_) | Value::String(_)
+            )
+        })
+        .unwrap_or_else(|| Value::from(value))
 }
 
 #[derive(Deserialize, Debug, PartialEq)]
@@ -102,7 +200,98 @@ struct TemplateValuesToml {
 
 #[cfg(test)]
 mod test {
-    use super::read_template_values_from_definitions;
+    use super::{
+        raw_definitions, read_template_values_file, read_template_values_from_definitions,
+        read_template_values_from_env, read_template_values_from_json,
+    };
+    use std::{ffi::OsString, fs};
+
+    #[test]
+    fn values_files_need_a_values_table() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("values.toml");
+
+        fs::write(&path, "[values]\nmy_value = \"foo\"").unwrap();
+        let values = read_template_values_file(&path).unwrap();
+        assert_eq!(values["my_value"].as_str(), Some("foo"));
+
+        fs::write(&path, "my_value = \"foo\"").unwrap();
+        assert!(read_template_values_file(&path).is_err());
+
+        fs::write(&path, "[values\nmy_value = ").unwrap();
+        let error = read_template_values_file(&path).unwrap_err();
+        assert!(error.to_string().contains("values.toml"));
+    }
+
+    #[test]
+    fn json_values_are_coerced_to_toml_scalars() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("values.json");
+        fs::write(
+            &path,
+            r#"{"name": "foo", "use_serde": true, "port": 8080, "ratio": 0.5}"#,
+        )
+        .unwrap();
+
+        let values = read_template_values_from_json(&path).unwrap();
+        assert_eq!(values["name"].as_str(), Some("foo"));
+        assert_eq!(values["use_serde"].as_bool(), Some(true));
+        assert_eq!(values["port"].as_integer(), Some(8080));
+        assert_eq!(values["ratio"].as_float(), Some(0.5));
+    }
+
+    #[test]
+    fn json_values_must_be_a_flat_object() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("values.json");
+
+        for invalid in [
+            r#"{"nested": {"a": 1}}"#,
+            r#"{"list": [1, 2]}"#,
+            r#"{"nothing": null}"#,
+            r#"["not", "an", "object"]"#,
+            "not json",
+        ] {
+            fs::write(&path, invalid).unwrap();
+            let error = read_template_values_from_json(&path).unwrap_err();
+            assert!(error.to_string().contains("values.json"), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn missing_values_files_are_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let error = read_template_values_file(&dir.path().join("missing.toml")).unwrap_err();
+        assert!(error.to_string().contains("missing.toml"));
+    }
+
+    #[test]
+    fn env_values_are_lowercased_without_prefix() {
+        let vars = vec![
+            ("CARGO_GENERATE_VALUE_MY_VALUE".into(), "foo".into()),
+            ("OTHER_VAR".into(), "bar".into()),
+        ];
+        let values = read_template_values_from_env(vars.into_iter());
+        assert_eq!(values.len(), 1);
+        assert_eq!(values["my_value"].as_str(), Some("foo"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn env_values_that_are_not_unicode_are_skipped() {
+        use std::os::unix::ffi::OsStringExt;
+
+        let vars = vec![
+            (
+                OsString::from("CARGO_GENERATE_VALUE_BROKEN"),
+                OsString::from_vec(vec![0x66, 0x6f, 0x80]),
+            ),
+            ("CARGO_GENERATE_VALUE_FINE".into(), "fine".into()),
+        ];
+        let values = read_template_values_from_env(vars.into_iter());
+        assert!(!values.contains_key("broken"));
+        assert_eq!(values["fine"].as_str(), Some("fine"));
+    }
 
     #[test]
     fn names_must_start_with_word_char() {
@@ -123,13 +312,23 @@ mod test {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn raw_definitions_keep_the_text_of_coerced_values() {
+        let definitions = vec!["ver=0.10", "name=foo", "quoted=\"1.0\"", "flag=true"];
+        let raw = raw_definitions(&definitions).unwrap();
+
+        assert_eq!(raw.len(), 2);
+        assert_eq!(raw.get("ver").map(String::as_str), Some("0.10"));
+        assert_eq!(raw.get("flag").map(String::as_str), Some("true"));
+    }
+
     #[test]
     fn names_may_contain_digits() {
         let definitions = vec!["my0123456789key=42"];
         let result = read_template_values_from_definitions(&definitions).unwrap();
 
-        let val = result["my0123456789key"].as_str().unwrap();
-        assert_eq!(val, "42");
+        let val = result["my0123456789key"].as_integer().unwrap();
+        assert_eq!(val, 42);
     }
 
     #[test]
@@ -137,8 +336,8 @@ mod test {
         let definitions = vec!["my-key=42"];
         let result = read_template_values_from_definitions(&definitions).unwrap();
 
-        let val = result["my-key"].as_str().unwrap();
-        assert_eq!(val, "42");
+        let val = result["my-key"].as_integer().unwrap();
+        assert_eq!(val, 42);
     }
 
     #[test]
@@ -146,8 +345,17 @@ mod test {
         let definitions = vec!["my_key=42"];
         let result = read_template_values_from_definitions(&definitions).unwrap();
 
-        let val = result["my_key"].as_str().unwrap();
-        assert_eq!(val, "42");
+        let val = result["my_key"].as_integer().unwrap();
+        assert_eq!(val, 42);
+    }
+
+    #[test]
+    fn names_may_contain_dot() {
+        let definitions = vec!["now.year=2020"];
+        let result = read_template_values_from_definitions(&definitions).unwrap();
+
+        let val = result["now.year"].as_integer().unwrap();
+        assert_eq!(val, 2020);
     }
 
     #[test]
@@ -162,7 +370,25 @@ mod test {
         let definitions = vec!["key   =      42"];
         let result = read_template_values_from_definitions(&definitions).unwrap();
 
-        let val = result["key"].as_str().unwrap();
-        assert_eq!(val, "42");
+        let val = result["key"].as_integer().unwrap();
+        assert_eq!(val, 42);
+    }
+
+    #[test]
+    fn values_are_parsed_as_toml_scalars() {
+        let definitions = vec![
+            "use_serde=true",
+            "ratio=0.5",
+            "name=foo bar",
+            r#"quoted="42""#,
+            "list=[1, 2]",
+        ];
+        let result = read_template_values_from_definitions(&definitions).unwrap();
+
+        assert_eq!(result["use_serde"].as_bool(), Some(true));
+        assert_eq!(result["ratio"].as_float(), Some(0.5));
+        assert_eq!(result["name"].as_str(), Some("foo bar"));
+        assert_eq!(result["quoted"].as_str(), Some("42"));
+        assert_eq!(result["list"].as_str(), Some("[1, 2]"));
     }
 }
@@ -0,0 +1,18 @@
+use chrono::{Datelike, Local, SecondsFormat};
+
+/// Represents the point in time the generation started,
+/// it's evaluated once so that all files see the very same values.
+pub struct Now {
+    pub year: i32,
+    pub date: String,
+    pub timestamp: String,
+}
+
+pub fn get_now() -> Now {
+    let now = Local::now();
+    Now {
+        year: now.year(),
+        date: now.format("%Y-%m-%d").to_string(),
+        timestamp: now.to_rfc3339_opts(SecondsFormat::Secs, false),
+    }
+}
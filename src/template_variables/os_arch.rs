@@ -5,3 +5,8 @@ pub type OsArch = String;
 pub fn get_os_arch() -> OsArch {
     format!("{}-{}", env::consts::OS, env::consts::ARCH)
 }
+
+/// Platform values every `[conditional]` expression can refer to, e.g. `os == "windows"`.
+pub const fn get_platform_variables() -> [(&'static str, &'static str); 2] {
+    [("os", env::consts::OS), ("arch", env::consts::ARCH)]
+}
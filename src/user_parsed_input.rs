@@ -2,20 +2,30 @@
 
 use std::{
     collections::HashMap,
+    fmt,
     path::{Path, PathBuf},
 };
 
+use anyhow::{bail, Result};
 use console::style;
 use regex::Regex;
 
-use crate::{app_config::AppConfig, warn, GenerateArgs};
+use crate::{
+    app_config::AppConfig, archive::is_archive, emoji, hg::strip_hg_prefix, warn, GenerateArgs,
+};
 
 // Contains parsed information from user.
 pub struct UserParsedInput {
     // from where clone or copy template?
     template_location: TemplateLocation,
+    // more templates from repeated --git, overlaid on template_location in order
+    overlay_locations: Vec<TemplateLocation>,
+    // the description of the favorite the template has been chosen by
+    favorite_description: Option<String>,
     // if template_location contains many templates user already specified one
     subfolder: Option<String>,
+    // the subfolder of a favorite or a git shorthand, used if the user didn't specify one
+    default_subfolder: Option<String>,
     // all values that user defined through:
     // 1. envirnoment variables
     // 2. configuration file
@@ -35,17 +45,23 @@ impl UserParsedInput {
     ) -> Self {
         Self {
             template_location: template_location.into(),
+            overlay_locations: vec![],
+            favorite_description: None,
             subfolder: subfolder.map(|s| s.as_ref().to_owned()),
+            default_subfolder: None,
             template_values: default_values,
         }
     }
 
     /// Try create `UserParsedInput` reading in order [`AppConfig`] and [`Args`]
     ///
+    /// # Errors
+    /// Fails if a favorite uses an environment variable that is not set
+    ///
     /// # Panics
     /// This function assume that Args and AppConfig are verfied eariler and are logicly correct
     /// For example if both `--git` and `--path` are set this function will panic
-    pub fn try_from_args_and_config(app_config: &AppConfig, args: &GenerateArgs) -> Self {
+    pub fn try_from_args_and_config(app_config: &AppConfig, args: &GenerateArgs) -> Result<Self> {
         let mut default_values = app_config.values.clone().unwrap_or_default();
         let ssh_identity = app_config
             .defaults
@@ -55,28 +71,84 @@ impl UserParsedInput {
 
         // --git
         if let Some(git_url) = args.template_path.git() {
-            let git_user_in = GitUserInput::new(
-                git_url,
+            let template_location = git_location(
+                git_url.as_ref(),
                 args.template_path.branch(),
-                ssh_identity,
+                args.template_path.tag(),
+                args.template_path.rev(),
+                ssh_identity.clone(),
                 args.force_git_init,
             );
-            return Self::new(git_user_in, args.template_path.subfolder(), default_values);
+            let mut user_parsed_input = Self::new(
+                template_location,
+                args.template_path.subfolder(),
+                default_values,
+            );
+            // the pinning args belong to the first `--git`, the others are taken as they are
+            user_parsed_input.overlay_locations = args
+                .template_path
+                .git_overlays()
+                .iter()
+                .map(|git_url| {
+                    git_location::<&str, &str, &str>(
+                        git_url,
+                        None,
+                        None,
+                        None,
+                        ssh_identity.clone(),
+                        args.force_git_init,
+                    )
+                })
+                .collect();
+            return Ok(user_parsed_input);
+        }
+
+        // --hg
+        if let Some(hg_url) = args.template_path.hg() {
+            let hg_url = hg_url.as_ref();
+            return Ok(Self::new(
+                HgUserInput::new(
+                    strip_hg_prefix(hg_url).unwrap_or(hg_url),
+                    args.template_path.branch(),
+                ),
+                args.template_path.subfolder(),
+                default_values,
+            ));
         }
 
         // --path
         if let Some(path) = args.template_path.path() {
-            return Self::new(
+            return Ok(Self::new(
                 path.as_ref(),
                 args.template_path.subfolder(),
                 default_values,
-            );
+            ));
+        }
+
+        // --archive
+        if let Some(archive) = args.template_path.archive() {
+            return Ok(Self::new(
+                TemplateLocation::Archive(archive.as_ref().to_owned()),
+                args.template_path.subfolder(),
+                default_values,
+            ));
+        }
+
+        // --github-release
+        if let Some(release) = args.template_path.github_release() {
+            let asset = args.template_path.asset().map_or("", AsRef::as_ref);
+            return Ok(Self::new(
+                GitHubReleaseUserInput::parse(release.as_ref(), asset)?,
+                args.template_path.subfolder(),
+                default_values,
+            ));
         }
 
         // check if favorite is favorite configuration
         let fav_name = args.template_path.any_path();
 
         if let Some(fav_cfg) = app_config.get_favorite_cfg(fav_name) {
+            let fav_cfg = fav_cfg.expand_env_vars(fav_name)?;
             assert!(fav_cfg.git.is_none() || fav_cfg.path.is_none());
 
             let temp_location = fav_cfg.git.as_ref().map_or_else(
@@ -87,9 +159,14 @@ impl UserParsedInput {
                         .branch()
                         .map(|s| s.as_ref().to_owned())
                         .or_else(|| fav_cfg.branch.clone());
+                    if let Some(hg_url) = strip_hg_prefix(git_url) {
+                        return TemplateLocation::from(HgUserInput::new(hg_url, branch.as_ref()));
+                    }
                     let git_user_input = GitUserInput::new(
                         git_url,
                         branch.as_ref(),
+                        args.template_path.tag(),
+                        args.template_path.rev(),
                         ssh_identity,
                         args.force_git_init,
                     );
@@ -102,30 +179,43 @@ impl UserParsedInput {
                 default_values.extend(fav_default_values.clone());
             }
 
-            return Self::new(
+            let mut user_parsed_input = Self::new(
                 temp_location,
-                args.template_path
-                    .subfolder()
-                    .map(|s| s.as_ref().to_owned())
-                    .or_else(|| fav_cfg.subfolder.clone()),
+                args.template_path.subfolder(),
                 default_values,
             );
+            user_parsed_input.default_subfolder = fav_cfg.subfolder.clone();
+            user_parsed_input.favorite_description = fav_cfg.description.clone();
+            return Ok(user_parsed_input);
         }
 
         // there is no specified favorite in configuration
         // this part try to guess what user wanted in order:
 
-        // 1. look for abbrevations like gh:, gl: etc.
-        let temp_location = abbreviated_git_url_to_full_remote(&fav_name).map(|git_url| {
-            let git_user_in = GitUserInput::with_git_url_and_args(&git_url, args);
-            TemplateLocation::from(git_user_in)
+        // 1. look for Mercurial urls like hg+https://hg.example.com/template
+        let temp_location = strip_hg_prefix(fav_name).map(|hg_url| {
+            TemplateLocation::from(HgUserInput::new(hg_url, args.template_path.branch()))
+        });
+
+        // 2. look for archives like template.tar.gz or https://example.com/template.zip
+        let temp_location = temp_location.or_else(|| {
+            is_archive(fav_name).then(|| TemplateLocation::Archive(fav_name.to_owned()))
         });
 
-        // 2. check if template directory exist
+        // 3. look for abbrevations like gh:, gl: etc. also with subfolder and branch like gh:owner/repo//subdir@branch
+        let shorthand = parse_git_shorthand(fav_name);
+        let temp_location = temp_location.or_else(|| {
+            shorthand.as_ref().map(|shorthand| {
+                let git_user_in = GitUserInput::with_shorthand_and_args(shorthand, args);
+                TemplateLocation::from(git_user_in)
+            })
+        });
+
+        // 4. check if template directory exist
         let temp_location =
             temp_location.or_else(|| local_path(fav_name).map(TemplateLocation::from));
 
-        // 3. check if the input is in form org/repo<> (map to github)
+        // 5. check if the input is in form org/repo<> (map to github)
         let temp_location = temp_location.or_else(|| {
             abbreviated_github(fav_name).map(|git_url| {
                 let git_user_in = GitUserInput::with_git_url_and_args(&git_url, args);
@@ -133,11 +223,13 @@ impl UserParsedInput {
             })
         });
 
-        // 4. assume user wanted use --git
+        // 6. assume user wanted use --git
         let temp_location = temp_location.unwrap_or_else(|| {
             let git_user_in = GitUserInput::new(
                 &fav_name,
                 args.template_path.branch(),
+                args.template_path.tag(),
+                args.template_path.rev(),
                 ssh_identity,
                 args.force_git_init,
             );
@@ -149,9 +241,21 @@ impl UserParsedInput {
             TemplateLocation::Git(git_user_input) => {
                 format!("git repository: {}", style(git_user_input.url()).bold())
             }
+            TemplateLocation::Mercurial(hg_user_input) => {
+                format!(
+                    "Mercurial repository: {}",
+                    style(hg_user_input.url()).bold()
+                )
+            }
             TemplateLocation::Path(path) => {
                 format!("local path: {}", style(path.display()).bold())
             }
+            TemplateLocation::Archive(archive) => {
+                format!("archive: {}", style(archive).bold())
+            }
+            TemplateLocation::GitHubRelease(release) => {
+                format!("GitHub release: {}", style(release).bold())
+            }
         };
         warn!(
             "Favorite `{}` not found in config, using it as a {}",
@@ -159,21 +263,41 @@ impl UserParsedInput {
             location_msg
         );
 
-        Self::new(
+        let mut user_parsed_input = Self::new(
             temp_location,
-            args.template_path
-                .subfolder()
-                .map(|s| s.as_ref().to_owned()),
+            args.template_path.subfolder(),
             default_values,
-        )
+        );
+        user_parsed_input.default_subfolder = shorthand.and_then(|shorthand| shorthand.subfolder);
+        Ok(user_parsed_input)
     }
 
     pub const fn location(&self) -> &TemplateLocation {
         &self.template_location
     }
 
+    pub const fn location_mut(&mut self) -> &mut TemplateLocation {
+        &mut self.template_location
+    }
+
+    /// The templates to overlay on [`Self::location`], in order
+    pub fn overlay_locations(&self) -> &[TemplateLocation] {
+        &self.overlay_locations
+    }
+
     pub fn subfolder(&self) -> Option<&str> {
-        self.subfolder.as_deref()
+        self.subfolder
+            .as_deref()
+            .or(self.default_subfolder.as_deref())
+    }
+
+    pub fn set_subfolder<T: AsRef<str>>(&mut self, subfolder: Option<T>) {
+        self.subfolder = subfolder.map(|s| s.as_ref().to_owned());
+    }
+
+    /// The description of the favorite, the fallback for a template without `description`
+    pub fn favorite_description(&self) -> Option<&str> {
+        self.favorite_description.as_deref()
     }
 
     pub const fn template_values(&self) -> &HashMap<String, toml::Value> {
@@ -185,6 +309,31 @@ impl UserParsedInput {
     }
 }
 
+/// The location of a `--git` value, which can also be a Mercurial url or an archive
+fn git_location<T1, T2, T3>(
+    git_url: &str,
+    branch: Option<&T1>,
+    tag: Option<&T2>,
+    revision: Option<&T3>,
+    identity: Option<PathBuf>,
+    force_init: bool,
+) -> TemplateLocation
+where
+    T1: AsRef<str>,
+    T2: AsRef<str>,
+    T3: AsRef<str>,
+{
+    if let Some(hg_url) = strip_hg_prefix(git_url) {
+        return TemplateLocation::from(HgUserInput::new(hg_url, branch));
+    }
+    if is_archive(git_url) {
+        return TemplateLocation::Archive(git_url.to_owned());
+    }
+    TemplateLocation::from(GitUserInput::new(
+        &git_url, branch, tag, revision, identity, force_init,
+    ))
+}
+
 // favorite can be in form with abbrevation what means that input is git repositoru
 pub fn abbreviated_git_url_to_full_remote(git: impl AsRef<str>) -> Option<String> {
     let git = git.as_ref();
@@ -200,6 +349,49 @@ pub fn abbreviated_git_url_to_full_remote(git: impl AsRef<str>) -> Option<String
     }
 }
 
+/// A git abbreviation with an optional subfolder and branch, like `gh:owner/repo//path/to/subdir@branch`
+#[derive(Debug, PartialEq)]
+pub struct GitShorthand {
+    url: String,
+    subfolder: Option<String>,
+    branch: Option<String>,
+}
+
+/// Parses `gh:`, `gl:` and `bb:` abbreviations, `None` if `input` is no such abbreviation of a repo
+pub fn parse_git_shorthand(input: &str) -> Option<GitShorthand> {
+    let (scheme, rest) = input.split_once(':')?;
+    if !matches!(scheme, "gh" | "gl" | "bb") {
+        return None;
+    }
+
+    let (rest, branch) = match rest.rsplit_once('@') {
+        Some((rest, branch)) if !branch.is_empty() => (rest, Some(branch.to_owned())),
+        Some(_) => return None,
+        None => (rest, None),
+    };
+    let (repo, subfolder) = match rest.split_once("//") {
+        Some((repo, subfolder)) => {
+            let subfolder = subfolder.trim_matches('/');
+            if subfolder.is_empty() {
+                return None;
+            }
+            (repo, Some(subfolder.to_owned()))
+        }
+        None => (rest, None),
+    };
+    let repo = repo.trim_end_matches('/');
+    let (owner, name) = repo.split_once('/')?;
+    if owner.is_empty() || name.is_empty() || repo.contains(char::is_whitespace) {
+        return None;
+    }
+
+    Some(GitShorthand {
+        url: abbreviated_git_url_to_full_remote(format!("{}:{}", scheme, repo))?,
+        subfolder,
+        branch,
+    })
+}
+
 // favorite can be in form of org/repo what should be parsed as github.com
 pub fn abbreviated_github(fav: &str) -> Option<String> {
     let org_repo_regex = Regex::new(r"^[a-zA-Z0-9_]+/[a-zA-Z0-9_%-]+$").unwrap();
@@ -217,24 +409,32 @@ pub fn local_path(fav: &str) -> Option<PathBuf> {
 pub struct GitUserInput {
     url: String,
     branch: Option<String>,
+    tag: Option<String>,
+    revision: Option<String>,
     identity: Option<PathBuf>,
     _force_init: bool,
 }
 
 impl GitUserInput {
-    fn new<T1, T2>(
+    fn new<T1, T2, T3, T4>(
         url: &T1,
         branch: Option<&T2>,
+        tag: Option<&T3>,
+        revision: Option<&T4>,
         identity: Option<PathBuf>,
         force_init: bool,
     ) -> Self
     where
         T1: AsRef<str>,
         T2: AsRef<str>,
+        T3: AsRef<str>,
+        T4: AsRef<str>,
     {
         Self {
             url: url.as_ref().to_owned(),
             branch: branch.map(|s| s.as_ref().to_owned()),
+            tag: tag.map(|s| s.as_ref().to_owned()),
+            revision: revision.map(|s| s.as_ref().to_owned()),
             identity,
             _force_init: force_init,
         }
@@ -248,6 +448,29 @@ impl GitUserInput {
         Self::new(
             url,
             args.template_path.branch(),
+            args.template_path.tag(),
+            args.template_path.rev(),
+            args.ssh_identity.clone(),
+            args.force_git_init,
+        )
+    }
+
+    /// A branch of the shorthand is only used if no branch, tag or revision is given by the args
+    fn with_shorthand_and_args(shorthand: &GitShorthand, args: &GenerateArgs) -> Self {
+        let template_path = &args.template_path;
+        let pinned = template_path.branch().is_some()
+            || template_path.tag().is_some()
+            || template_path.rev().is_some();
+        let branch = template_path
+            .branch()
+            .map(|s| s.as_ref().to_owned())
+            .or_else(|| shorthand.branch.clone().filter(|_| !pinned));
+
+        Self::new(
+            &shorthand.url,
+            branch.as_ref(),
+            template_path.tag(),
+            template_path.rev(),
             args.ssh_identity.clone(),
             args.force_git_init,
         )
@@ -261,15 +484,139 @@ impl GitUserInput {
         self.branch.as_deref()
     }
 
+    pub fn tag(&self) -> Option<&str> {
+        self.tag.as_deref()
+    }
+
+    pub fn revision(&self) -> Option<&str> {
+        self.revision.as_deref()
+    }
+
     pub fn identity(&self) -> Option<&Path> {
         self.identity.as_deref()
     }
+
+    pub fn set_branch(&mut self, branch: String) {
+        self.branch = Some(branch);
+    }
+}
+
+// Template should be cloned with hg
+pub struct HgUserInput {
+    url: String,
+    branch: Option<String>,
+}
+
+impl HgUserInput {
+    fn new<T>(url: &str, branch: Option<&T>) -> Self
+    where
+        T: AsRef<str>,
+    {
+        Self {
+            url: url.to_owned(),
+            branch: branch.map(|s| s.as_ref().to_owned()),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        self.url.as_ref()
+    }
+
+    pub fn branch(&self) -> Option<&str> {
+        self.branch.as_deref()
+    }
+}
+
+// Template should be downloaded as asset of a GitHub release
+pub struct GitHubReleaseUserInput {
+    repo: String,
+    tag: String,
+    asset: String,
+}
+
+impl GitHubReleaseUserInput {
+    /// Parses `release` like `owner/repo@v1.2.3`
+    pub fn parse(release: &str, asset: &str) -> Result<Self> {
+        let is_repo = |repo: &str| {
+            let parts: Vec<&str> = repo.split('/').collect();
+            parts.len() == 2 && parts.iter().all(|part| !part.is_empty())
+        };
+        match release.split_once('@') {
+            Some((repo, tag)) if is_repo(repo) && !tag.is_empty() => Ok(Self {
+                repo: repo.to_owned(),
+                tag: tag.to_owned(),
+                asset: asset.to_owned(),
+            }),
+            _ => bail!(
+                "{} {} {}",
+                emoji::ERROR,
+                style("Invalid GitHub release:").bold().red(),
+                style(format!("`{}` is not like `owner/repo@tag`", release)).yellow(),
+            ),
+        }
+    }
+
+    /// The repository, as `owner/repo`
+    pub fn repo(&self) -> &str {
+        &self.repo
+    }
+
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    pub fn asset(&self) -> &str {
+        &self.asset
+    }
+}
+
+impl fmt::Display for GitHubReleaseUserInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{} ({})", self.repo, self.tag, self.asset)
+    }
 }
 
-// Distinguish between plain copy and clone
+// Distinguish between plain copy, clone and archive extraction
 pub enum TemplateLocation {
     Git(GitUserInput),
+    Mercurial(HgUserInput),
     Path(PathBuf),
+    // local path or http(s) url of a `.tar.gz`, `.tar` or `.zip` file
+    Archive(String),
+    // an archive attached to a GitHub release
+    GitHubRelease(GitHubReleaseUserInput),
+}
+
+impl TemplateLocation {
+    /// The location of the base template in `[template] extends`, a path is relative to `relative_to`,
+    /// the dir of the `cargo-generate.toml` declaring it
+    pub fn from_extends(extends: &str, relative_to: &Path, identity: Option<PathBuf>) -> Self {
+        let local = relative_to.join(extends);
+        if local.is_dir() {
+            return Self::Path(local);
+        }
+        if let Some(hg_url) = strip_hg_prefix(extends) {
+            return HgUserInput::new(hg_url, None::<&String>).into();
+        }
+        if is_archive(extends) {
+            let archive = if local.is_file() {
+                local.display().to_string()
+            } else {
+                extends.to_owned()
+            };
+            return Self::Archive(archive);
+        }
+        let url = abbreviated_git_url_to_full_remote(extends).unwrap_or_else(|| extends.to_owned());
+        GitUserInput::new(
+            &url,
+            None::<&String>,
+            None::<&String>,
+            None::<&String>,
+            identity,
+            false,
+        )
+        .into()
+    }
 }
 
 impl From<GitUserInput> for TemplateLocation {
@@ -278,6 +625,18 @@ impl From<GitUserInput> for TemplateLocation {
     }
 }
 
+impl From<GitHubReleaseUserInput> for TemplateLocation {
+    fn from(source: GitHubReleaseUserInput) -> Self {
+        Self::GitHubRelease(source)
+    }
+}
+
+impl From<HgUserInput> for TemplateLocation {
+    fn from(source: HgUserInput) -> Self {
+        Self::Mercurial(source)
+    }
+}
+
 impl<T> From<T> for TemplateLocation
 where
     T: AsRef<Path>,
@@ -316,4 +675,134 @@ mod tests {
         );
         assert!(&abbreviated_github("path/to/a/sth").is_none());
     }
+
+    #[test]
+    fn should_parse_shorthands_with_subfolder_and_branch() {
+        assert_eq!(
+            parse_git_shorthand("gh:owner/repo//path/to/subdir@branch"),
+            Some(GitShorthand {
+                url: "https://github.com/owner/repo.git".into(),
+                subfolder: Some("path/to/subdir".into()),
+                branch: Some("branch".into()),
+            })
+        );
+        assert_eq!(
+            parse_git_shorthand("gl:group/repo@feature/x"),
+            Some(GitShorthand {
+                url: "https://gitlab.com/group/repo.git".into(),
+                subfolder: None,
+                branch: Some("feature/x".into()),
+            })
+        );
+        assert_eq!(
+            parse_git_shorthand("gh:owner/repo//templates/cli/"),
+            Some(GitShorthand {
+                url: "https://github.com/owner/repo.git".into(),
+                subfolder: Some("templates/cli".into()),
+                branch: None,
+            })
+        );
+        assert_eq!(
+            parse_git_shorthand("gh:owner/repo"),
+            Some(GitShorthand {
+                url: "https://github.com/owner/repo.git".into(),
+                subfolder: None,
+                branch: None,
+            })
+        );
+    }
+
+    #[test]
+    fn should_not_parse_other_inputs_as_shorthand() {
+        assert_eq!(parse_git_shorthand("owner/repo"), None);
+        assert_eq!(parse_git_shorthand("https://github.com/owner/repo"), None);
+        assert_eq!(parse_git_shorthand("gh:repo"), None);
+        assert_eq!(parse_git_shorthand("gh:owner/repo@"), None);
+        assert_eq!(parse_git_shorthand("gh:owner/repo//"), None);
+    }
+
+    #[test]
+    fn should_take_subfolder_and_branch_from_the_shorthand() {
+        let parse = |cli_args: &[&str]| {
+            let crate::Cli::Generate(args) = <crate::Cli as clap::Parser>::parse_from(
+                ["cargo", "generate"].iter().chain(cli_args),
+            );
+            UserParsedInput::try_from_args_and_config(&AppConfig::default(), &args).unwrap()
+        };
+
+        let input = parse(&["gh:owner/repo//path/to/subdir@branch"]);
+        assert_eq!(input.subfolder(), Some("path/to/subdir"));
+        match input.location() {
+            TemplateLocation::Git(git) => {
+                assert_eq!(git.url(), "https://github.com/owner/repo.git");
+                assert_eq!(git.branch(), Some("branch"));
+            }
+            _ => panic!("expected a git location"),
+        }
+
+        // explicit flags win over the shorthand
+        let input = parse(&["gh:owner/repo//subdir@branch", "--tag", "v1.0.0"]);
+        match input.location() {
+            TemplateLocation::Git(git) => {
+                assert_eq!(git.branch(), None);
+                assert_eq!(git.tag(), Some("v1.0.0"));
+            }
+            _ => panic!("expected a git location"),
+        }
+    }
+
+    #[test]
+    fn should_take_subfolder_and_values_from_the_favorite() {
+        let app_config: AppConfig = toml::from_str(
+            r#"
+            [favorites.service]
+            git = "https://github.com/owner/services.git"
+            subfolder = "services/api"
+            values = { port = 8080 }
+            "#,
+        )
+        .unwrap();
+        let parse = |cli_args: &[&str]| {
+            let crate::Cli::Generate(args) = <crate::Cli as clap::Parser>::parse_from(
+                ["cargo", "generate", "service"].iter().chain(cli_args),
+            );
+            UserParsedInput::try_from_args_and_config(&app_config, &args).unwrap()
+        };
+
+        let input = parse(&[]);
+        assert_eq!(input.subfolder(), Some("services/api"));
+        assert_eq!(
+            input.template_values().get("port"),
+            Some(&toml::Value::Integer(8080))
+        );
+
+        // a subfolder of the command line wins over the one of the favorite
+        assert_eq!(
+            parse(&["services/worker"]).subfolder(),
+            Some("services/worker")
+        );
+        assert_eq!(
+            parse(&["--subfolder", "services/worker"]).subfolder(),
+            Some("services/worker")
+        );
+    }
+
+    #[test]
+    fn should_parse_github_releases() {
+        let release =
+            GitHubReleaseUserInput::parse("owner/repo@v1.2.3", "template.tar.gz").unwrap();
+        assert_eq!(release.repo(), "owner/repo");
+        assert_eq!(release.tag(), "v1.2.3");
+        assert_eq!(release.asset(), "template.tar.gz");
+
+        for invalid in [
+            "owner/repo",
+            "repo@v1",
+            "owner/repo@",
+            "a/b/c@v1",
+            "/repo@v1",
+        ] {
+            assert!(GitHubReleaseUserInput::parse(invalid, "template.tar.gz").is_err());
+        }
+    }
 }
@@ -78,6 +78,75 @@ version = "0.1.0"
     assert_eq!(0, references);
 }
 
+#[test]
+fn it_leaves_out_the_files_a_path_template_ignores() {
+    let template = tmp_dir()
+        .file("Cargo.toml", r#"name = "{{project-name}}""#)
+        .file(".gitignore", "target/\n")
+        .file("target/debug/app", "binary")
+        .build();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--path")
+        .arg(template.path())
+        .current_dir(dir.path())
+        .assert()
+        .success();
+    binary()
+        .arg("generate")
+        .arg("--name")
+        .arg("all-files")
+        .arg("--path")
+        .arg(template.path())
+        .arg("--respect-gitignore=false")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert!(dir.exists("foobar-project/.gitignore"));
+    assert!(!dir.exists("foobar-project/target"));
+    assert!(dir.exists("all-files/target/debug/app"));
+}
+
+#[test]
+fn it_describes_the_project_dir_in_placeholders() {
+    let template = tmp_dir()
+        .file(
+            "info.txt",
+            "{{project.name}} in {{project.parent_name}} at {{project.dir}}",
+        )
+        .init_git()
+        .build();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    // the current dir of the binary has its symlinks resolved, like `/tmp` on macOS
+    let base_dir = fs::canonicalize(dir.path()).unwrap();
+    let parent_name = base_dir.file_name().unwrap().to_string_lossy();
+    let project_dir = base_dir.join("foobar-project");
+    assert_eq!(
+        dir.read("foobar-project/info.txt"),
+        format!(
+            "foobar-project in {} at {}",
+            parent_name,
+            project_dir.display()
+        )
+    );
+}
+
 #[test]
 fn it_substitutes_projectname_in_cargo_toml() {
     let template = tmp_dir()
@@ -424,6 +493,79 @@ version = "0.1.0"
     Ok(())
 }
 
+#[test]
+fn it_creates_the_missing_parents_of_the_destination() {
+    let template = tmp_dir().init_default_template().build();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--name")
+        .arg("my-proj")
+        .arg("--destination")
+        .arg(dir.path().join("deep/nested/destination"))
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert!(dir
+        .read("deep/nested/destination/my-proj/Cargo.toml")
+        .contains("my-proj"));
+}
+
+#[test]
+fn it_updates_only_the_changed_files_of_an_existing_project() {
+    let template = tmp_dir()
+        .file(
+            "Cargo.toml",
+            r#"[package]
+name = "{{project-name}}"
+version = "0.1.0"
+"#,
+        )
+        .file("README.md", "# {{project-name}}")
+        .file("src/main.rs", "fn main() {}")
+        .init_git()
+        .build();
+    let dir = tmp_dir().build();
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--name")
+        .arg("my-proj")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+    std::fs::write(
+        dir.path().join("my-proj/Cargo.toml"),
+        "[package]\nname = \"renamed\"\n",
+    )
+    .unwrap();
+    std::fs::remove_file(dir.path().join("my-proj/README.md")).unwrap();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--name")
+        .arg("my-proj")
+        .arg("--update")
+        .arg("--overwrite")
+        .arg("always")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("1 added, 1 modified, 1 unchanged").from_utf8())
+        .stdout(predicates::str::contains("Project updated").from_utf8());
+
+    assert!(dir.read("my-proj/Cargo.toml").contains("my-proj"));
+    assert!(dir.exists("my-proj/README.md"));
+}
+
 #[test]
 fn it_refuses_to_overwrite_files() -> anyhow::Result<()> {
     let template = tmp_dir()
@@ -496,6 +638,70 @@ version = "0.1.0"
         .contains("foobar_project"));
 }
 
+#[test]
+fn it_keeps_the_directory_name_but_converts_the_project_name_with_no_rename() {
+    let template = tmp_dir()
+        .file(
+            "README.md",
+            r#"# {{project-name}}
+"#,
+        )
+        .init_git()
+        .build();
+
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--name")
+        .arg("My_Service")
+        .arg("--no-rename")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(
+            predicates::str::contains("Renaming project")
+                .not()
+                .from_utf8(),
+        )
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert_eq!(dir.read("My_Service/README.md"), "# my-service\n");
+}
+
+#[test]
+fn it_uses_the_directory_name_independent_of_the_project_name() {
+    let template = tmp_dir()
+        .file(
+            "README.md",
+            r#"# {{project-name}}
+"#,
+        )
+        .init_git()
+        .build();
+
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--name")
+        .arg("My Service")
+        .arg("--force")
+        .arg("--directory-name")
+        .arg("my-service")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert!(!dir.exists("My Service"));
+    assert_eq!(dir.read("my-service/README.md"), "# My Service\n");
+}
+
 #[test]
 fn it_removes_files_listed_in_genignore() {
     let template = tmp_dir()
@@ -816,6 +1022,44 @@ version = "0.1.0"
         .contains("*JUST A SUBMODULE*"));
 }
 
+#[test]
+fn it_loads_nested_submodules_with_recurse_submodules() {
+    let nested = tmp_dir()
+        .file("README.md", "*A NESTED SUBMODULE*")
+        .init_git()
+        .build();
+    let nested_url = url::Url::from_file_path(nested.path()).unwrap();
+    let submodule = tmp_dir()
+        .file("README.md", "*JUST A SUBMODULE*")
+        .init_git()
+        .add_submodule("./nested/", nested_url.as_str())
+        .build();
+    let submodule_url = url::Url::from_file_path(submodule.path()).unwrap();
+    let template = tmp_dir()
+        .file("README.md", "{{project-name}}")
+        .init_git()
+        .add_submodule("./submodule/", submodule_url.as_str())
+        .build();
+
+    let dir = tmp_dir().build();
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--recurse-submodules")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert!(dir
+        .read("foobar-project/submodule/nested/README.md")
+        .contains("*A NESTED SUBMODULE*"));
+    assert!(!dir.exists("foobar-project/submodule/nested/.git"));
+}
+
 #[test]
 fn it_allows_relative_paths() {
     let template = tmp_dir()
@@ -974,6 +1218,54 @@ exclude = ["excluded"]
         .contains("foobar-project"));
 }
 
+#[test]
+fn it_copies_raw_files_without_rendering() {
+    let template = tmp_dir()
+        .file(
+            "cargo-generate.toml",
+            r#"[template]
+include = ["Cargo.toml", "src/*"]
+raw = ["src/{templates,vendor}/*"]
+"#,
+        )
+        .file("Cargo.toml", "name = \"{{project-name}}\"")
+        .file("src/main.rs", "// {{project-name}}")
+        .file(
+            "src/templates/page.html",
+            "<h1>{{ 1 | plus: 1 }} {{project-name}}</h1>",
+        )
+        .file("src/vendor/macros.hbs", "{{#each items}}{{this}}{{/each}}")
+        .init_git()
+        .build();
+
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert_eq!(
+        dir.read("foobar-project/Cargo.toml"),
+        "name = \"foobar-project\""
+    );
+    assert_eq!(dir.read("foobar-project/src/main.rs"), "// foobar-project");
+    assert_eq!(
+        dir.read("foobar-project/src/templates/page.html"),
+        "<h1>{{ 1 | plus: 1 }} {{project-name}}</h1>"
+    );
+    assert_eq!(
+        dir.read("foobar-project/src/vendor/macros.hbs"),
+        "{{#each items}}{{this}}{{/each}}"
+    );
+}
+
 #[test]
 fn it_warns_on_include_and_exclude_in_config() {
     let template = tmp_dir()
@@ -1434,3 +1726,1119 @@ fn error_message_for_invalid_repo_or_user() {
                 .from_utf8(),
         );
 }
+
+#[test]
+fn it_exits_with_code_2_if_the_template_is_not_found() {
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--path")
+        .arg(dir.path().join("no-such-template"))
+        .arg("--name")
+        .arg("foobar-project")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .code(2);
+
+    assert!(!dir.exists("foobar-project"));
+}
+
+#[test]
+fn it_only_reports_files_on_dry_run() {
+    let template = tmp_dir().init_default_template().build();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--branch")
+        .arg("main")
+        .arg("--dry-run")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(
+            predicates::str::contains("Cargo.toml")
+                .and(predicates::str::contains("project-name = foobar-project"))
+                .from_utf8(),
+        );
+
+    assert!(!dir.exists("foobar-project"));
+}
+
+#[test]
+fn it_fails_dry_run_on_collisions() {
+    let template = tmp_dir().init_default_template().build();
+    let dir = tmp_dir().file("Cargo.toml", "").build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--branch")
+        .arg("main")
+        .arg("--init")
+        .arg("--dry-run")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .code(4)
+        .stderr(predicates::str::contains("File already exists").from_utf8());
+
+    assert_eq!(dir.read("Cargo.toml"), "");
+}
+
+#[test]
+fn it_overwrites_existing_files_with_overwrite_always() {
+    let template = tmp_dir()
+        .file("Cargo.toml", "name = \"{{project-name}}\"")
+        .file("README.md", "fresh")
+        .init_git()
+        .build();
+    let dir = tmp_dir()
+        .file("Cargo.toml", "")
+        .file("src/lib.rs", "// mine")
+        .build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--init")
+        .arg("--overwrite")
+        .arg("always")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert_eq!(dir.read("Cargo.toml"), "name = \"foobar-project\"");
+    assert_eq!(dir.read("README.md"), "fresh");
+    assert_eq!(dir.read("src/lib.rs"), "// mine");
+}
+
+#[test]
+fn it_merges_a_template_into_an_existing_project_on_init() {
+    let template = tmp_dir()
+        .file("rustfmt.toml", "edition = \"2021\"")
+        .file(".github/workflows/ci.yml", "name: {{project-name}}")
+        .init_git()
+        .build();
+    let dir = tmp_dir()
+        .file("Cargo.toml", "[package]\nname = \"existing\"")
+        .file("src/lib.rs", "// mine")
+        .init_git()
+        .build();
+    let head_before = dir.read(".git/HEAD");
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--name")
+        .arg("existing")
+        .arg("--init")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert_eq!(dir.read("rustfmt.toml"), "edition = \"2021\"");
+    assert_eq!(dir.read(".github/workflows/ci.yml"), "name: existing");
+    assert_eq!(dir.read("Cargo.toml"), "[package]\nname = \"existing\"");
+    assert_eq!(dir.read("src/lib.rs"), "// mine");
+    assert_eq!(dir.read(".git/HEAD"), head_before);
+}
+
+#[test]
+fn it_overwrites_conflicts_and_merges_the_rest_on_init_with_overwrite_always() {
+    let template = tmp_dir()
+        .file("Cargo.toml", "[package]\nname = \"{{project-name}}\"")
+        .file("src/main.rs", "fn main() {}")
+        .init_git()
+        .build();
+    let dir = tmp_dir()
+        .file("Cargo.toml", "[package]\nname = \"existing\"")
+        .file("src/lib.rs", "// mine")
+        .init_git()
+        .build();
+    let head_before = dir.read(".git/HEAD");
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--name")
+        .arg("replaced")
+        .arg("--init")
+        .arg("--overwrite")
+        .arg("always")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert_eq!(dir.read("Cargo.toml"), "[package]\nname = \"replaced\"");
+    assert_eq!(dir.read("src/main.rs"), "fn main() {}");
+    assert_eq!(dir.read("src/lib.rs"), "// mine");
+    assert_eq!(dir.read(".git/HEAD"), head_before);
+}
+
+#[test]
+fn it_rejects_unknown_overwrite_policies() {
+    let template = tmp_dir().init_default_template().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--overwrite")
+        .arg("sometimes")
+        .assert()
+        .failure()
+        .stderr(
+            predicates::str::contains("Must be one of 'never', 'always' or 'prompt'").from_utf8(),
+        );
+}
+
+#[test]
+fn it_provides_the_generation_time_and_allows_to_override_it() {
+    let template = tmp_dir()
+        .file(
+            "LICENSE",
+            "Copyright {{now.year}}, created {{now.date}} at {{now.timestamp}}",
+        )
+        .init_git()
+        .build();
+
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("-d")
+        .arg("now.year=2020")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    let license = dir.read("foobar-project/LICENSE");
+    assert!(license.starts_with("Copyright 2020, created "));
+    assert!(!license.contains("{{now"));
+}
+
+#[test]
+fn it_prints_a_json_summary() {
+    let template = tmp_dir().init_default_template().build();
+    let dir = tmp_dir().build();
+
+    let output = binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--branch")
+        .arg("main")
+        .arg("--output-format")
+        .arg("json")
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        summary["project_dir"],
+        dir.path().join("foobar-project").display().to_string()
+    );
+    assert_eq!(summary["variables"]["project-name"], "foobar-project");
+    assert_eq!(summary["git_initialized"], true);
+    assert!(summary["files"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|file| file.as_str().unwrap().ends_with("Cargo.toml")));
+}
+
+#[test]
+fn it_refuses_to_fetch_remote_templates_offline() {
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg("https://github.com/cargo-generate/wasm-pack-template")
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--offline")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(
+            predicates::str::contains(
+                "refusing to fetch https://github.com/cargo-generate/wasm-pack-template",
+            )
+            .from_utf8(),
+        );
+}
+
+#[test]
+fn it_generates_from_local_templates_offline() {
+    let template = tmp_dir().init_default_template().build();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--branch")
+        .arg("main")
+        .arg("--offline")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert!(dir.exists("foobar-project/Cargo.toml"));
+}
+
+#[test]
+fn it_refuses_to_fetch_remote_submodules_of_local_templates_offline() {
+    let submodule = tmp_dir()
+        .file("README.md", "*JUST A SUBMODULE*")
+        .init_git()
+        .build();
+
+    let submodule_url = url::Url::from_file_path(submodule.path()).unwrap();
+    let template = tmp_dir()
+        .init_default_template()
+        .add_submodule("./submodule/", submodule_url.as_str())
+        .build();
+
+    Command::new("git")
+        .args(["config", "--file", ".gitmodules", "submodule.submodule.url"])
+        .arg("https://example.invalid/submodule.git")
+        .current_dir(template.path())
+        .assert()
+        .success();
+    Command::new("git")
+        .args(["commit", "--all", "--message", "remote submodule"])
+        .current_dir(template.path())
+        .assert()
+        .success();
+
+    let dir = tmp_dir().build();
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--branch")
+        .arg("main")
+        .arg("--offline")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(
+            predicates::str::contains(
+                "Offline mode: refusing to fetch https://example.invalid/submodule.git",
+            )
+            .from_utf8(),
+        );
+
+    assert!(!dir.exists("foobar-project"));
+}
+
+#[test]
+fn it_logs_plain_progress_lines_without_a_terminal() {
+    let template = tmp_dir().init_default_template().build();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--path")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("] Done: Cargo.toml").from_utf8());
+}
+
+#[test]
+fn it_hides_the_progress_with_no_progress() {
+    let template = tmp_dir().init_default_template().build();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--path")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--no-progress")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(
+            predicates::str::contains("Done: Cargo.toml")
+                .not()
+                .from_utf8(),
+        );
+
+    assert!(dir.exists("foobar-project/Cargo.toml"));
+}
+
+#[test]
+fn it_renders_the_same_with_a_single_job() {
+    let template = tmp_dir()
+        .file("a.txt", "{{project-name}} a")
+        .file("b/{{project-name}}.txt", "{{project-name}} b")
+        .file("c.txt", "{{project-name}} c")
+        .init_git()
+        .build();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--path")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--jobs")
+        .arg("1")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert_eq!(dir.read("foobar-project/a.txt"), "foobar-project a");
+    assert_eq!(
+        dir.read("foobar-project/b/foobar-project.txt"),
+        "foobar-project b"
+    );
+    assert_eq!(dir.read("foobar-project/c.txt"), "foobar-project c");
+}
+
+#[test]
+fn it_rejects_zero_jobs() {
+    let template = tmp_dir().init_default_template().build();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--path")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--jobs")
+        .arg("0")
+        .current_dir(dir.path())
+        .assert()
+        .failure();
+
+    assert!(!dir.exists("foobar-project"));
+}
+
+#[test]
+fn it_infers_the_project_name_from_the_destination() {
+    let template = tmp_dir().init_default_template().build();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--destination")
+        .arg(dir.path().join("my-app"))
+        .arg("--silent")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert!(dir.read("my-app/Cargo.toml").contains(r#"name = "my-app""#));
+    assert!(!dir.exists("my-app/my-app"));
+}
+
+#[test]
+fn it_requires_a_name_in_silent_mode_if_none_can_be_inferred() {
+    let template = tmp_dir().init_default_template().build();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--silent")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("project name was not set").from_utf8());
+}
+
+#[test]
+fn it_infers_the_project_name_from_the_current_dir_on_init() {
+    let template = tmp_dir().init_default_template().build();
+    let dir = tmp_dir().build();
+    let project_dir = dir.path().join("my-init-app");
+    fs::create_dir(&project_dir).unwrap();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--init")
+        .arg("--silent")
+        .current_dir(&project_dir)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert!(dir
+        .read("my-init-app/Cargo.toml")
+        .contains(r#"name = "my-init-app""#));
+}
+
+#[test]
+fn it_keeps_a_non_empty_destination_as_parent_dir_without_a_name() {
+    let template = tmp_dir().init_default_template().build();
+    let dir = tmp_dir().file("projects/existing/README.md", "").build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--destination")
+        .arg(dir.path().join("projects"))
+        .arg("--silent")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("project name was not set").from_utf8());
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--destination")
+        .arg(dir.path().join("projects"))
+        .arg("--name")
+        .arg("my-app")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert!(dir.exists("projects/existing/README.md"));
+    assert!(dir
+        .read("projects/my-app/Cargo.toml")
+        .contains(r#"name = "my-app""#));
+}
+
+#[test]
+fn it_generates_multiple_subfolders_into_sibling_dirs() {
+    let template = tmp_dir()
+        .file(
+            "crates/api/Cargo.toml",
+            r#"[package]
+name = "{{project-name}}-api"
+description = "{{summary}}"
+"#,
+        )
+        .file(
+            "crates/cli/Cargo.toml",
+            r#"[package]
+name = "{{project-name}}-cli"
+description = "{{summary}}"
+"#,
+        )
+        .init_git()
+        .build();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--subfolder")
+        .arg("crates/api")
+        .arg("--subfolder")
+        .arg("crates/cli")
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--define")
+        .arg("summary=Shared")
+        .arg("--silent")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    let api = dir.read("foobar-project/api/Cargo.toml");
+    assert!(api.contains(r#"name = "foobar-project-api""#));
+    assert!(api.contains(r#"description = "Shared""#));
+    let cli = dir.read("foobar-project/cli/Cargo.toml");
+    assert!(cli.contains(r#"name = "foobar-project-cli""#));
+    assert!(cli.contains(r#"description = "Shared""#));
+}
+
+#[test]
+fn it_refuses_subfolders_that_collide() {
+    let template = tmp_dir()
+        .file("a/app/Cargo.toml", "")
+        .file("b/app/Cargo.toml", "")
+        .init_git()
+        .build();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--subfolder")
+        .arg("a/app")
+        .arg("--subfolder")
+        .arg("b/app")
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--silent")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(
+            predicates::str::contains("multiple subfolders would be generated into `app`")
+                .from_utf8(),
+        );
+
+    assert!(!dir.exists("foobar-project"));
+}
+
+#[test]
+fn it_takes_a_second_positional_as_subfolder_if_it_exists() {
+    let template = tmp_dir()
+        .file("Cargo.toml", r#"name = "{{project-name}}-root""#)
+        .file("app/Cargo.toml", r#"name = "{{project-name}}-app""#)
+        .init_git()
+        .build();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg(template.path())
+        .arg("app")
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--silent")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert!(dir
+        .read("foobar-project/Cargo.toml")
+        .contains(r#"name = "foobar-project-app""#));
+}
+
+#[test]
+fn it_takes_a_second_positional_as_project_name_if_no_such_subfolder_exists() {
+    let template = tmp_dir()
+        .file("Cargo.toml", r#"name = "{{project-name}}-root""#)
+        .file("app/Cargo.toml", r#"name = "{{project-name}}-app""#)
+        .init_git()
+        .build();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg(template.path())
+        .arg("foobar-project")
+        .arg("--silent")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert!(dir
+        .read("foobar-project/Cargo.toml")
+        .contains(r#"name = "foobar-project-root""#));
+}
+
+#[test]
+fn it_prints_the_resolved_template_dir() {
+    let template = tmp_dir()
+        .file("README.md", "")
+        .file("inner/Cargo.toml", "")
+        .file("inner/cargo-generate.toml", "")
+        .init_git()
+        .build();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--print-template-dir")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Template dir: inner").from_utf8())
+        .stdout(predicates::str::contains("Config file: inner/cargo-generate.toml").from_utf8());
+
+    assert!(fs::read_dir(dir.path()).unwrap().next().is_none());
+}
+
+#[test]
+fn it_refuses_templates_with_missing_required_commands() {
+    let template = tmp_dir()
+        .file(
+            "cargo-generate.toml",
+            r#"[template]
+required_commands = ["cargo-generate-missing-one", "cargo-generate-missing-two"]
+"#,
+        )
+        .init_default_template()
+        .build();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(
+            predicates::str::contains("cargo-generate-missing-one, cargo-generate-missing-two")
+                .from_utf8(),
+        );
+    assert!(!dir.exists("foobar-project"));
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--ignore-required-commands")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+    assert!(dir.exists("foobar-project/Cargo.toml"));
+}
+
+#[test]
+fn it_refuses_templates_requiring_a_newer_rust_version() {
+    let template = tmp_dir()
+        .file(
+            "cargo-generate.toml",
+            r#"[template]
+min_rust_version = "999.0"
+"#,
+        )
+        .init_default_template()
+        .build();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Required Rust version not met").from_utf8());
+    assert!(!dir.exists("foobar-project"));
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--ignore-rust-version")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+    assert!(dir.exists("foobar-project/Cargo.toml"));
+}
+
+#[test]
+fn it_writes_diagnostics_in_the_selected_log_format() {
+    let template = tmp_dir().init_default_template().build();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--path")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--log-format")
+        .arg("json")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(r#"{"level":"info","message":"Done!"#).from_utf8());
+
+    binary()
+        .arg("generate")
+        .arg("--path")
+        .arg(template.path())
+        .arg("--name")
+        .arg("plain-project")
+        .env("CARGO_GENERATE_LOG_FORMAT", "plain")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("info: Done!").from_utf8())
+        .stdout(predicates::str::contains("\u{1b}[").not().from_utf8());
+}
+
+#[test]
+fn it_only_prints_warnings_and_errors_with_quiet() {
+    let template = tmp_dir().init_default_template().build();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--path")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--quiet")
+        .arg("-d")
+        .arg("license=MIT")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Basedir").not().from_utf8())
+        .stdout(predicates::str::contains("license =>").not().from_utf8())
+        .stdout(predicates::str::contains("Done!").not().from_utf8())
+        .stdout(
+            predicates::str::contains("Initializing a fresh git repository")
+                .not()
+                .from_utf8(),
+        );
+
+    assert!(dir.exists("foobar-project/Cargo.toml"));
+
+    binary()
+        .arg("generate")
+        .arg("--path")
+        .arg(template.path())
+        .arg("--name")
+        .arg("dry-project")
+        .arg("--quiet")
+        .arg("--dry-run")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(
+            predicates::str::contains("Resolved placeholders")
+                .not()
+                .from_utf8(),
+        );
+}
+
+#[test]
+fn it_explains_skipped_files_with_verbose() {
+    let template = tmp_dir()
+        .file(
+            "cargo-generate.toml",
+            r#"[template]
+exclude = ["*.txt"]
+"#,
+        )
+        .file("notes.txt", "{{project-name}}")
+        .file("README.md", "{{project-name}}")
+        .build();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--path")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--verbose")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(
+            predicates::str::contains(
+                "Copied verbatim, excluded from rendering: notes.txt, matched by `*.txt`",
+            )
+            .from_utf8(),
+        )
+        .stdout(predicates::str::contains("Rendered: README.md").from_utf8());
+
+    assert_eq!(
+        dir.read("foobar-project/notes.txt"),
+        "{{project-name}}".to_string()
+    );
+}
+
+#[test]
+fn it_writes_the_project_as_tar_to_stdout() {
+    let template = tmp_dir().init_default_template().build();
+    let dir = tmp_dir().build();
+
+    let output = binary()
+        .arg("generate")
+        .arg("--path")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--to-stdout-tar")
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Generating template"));
+    let mut archive = tar::Archive::new(output.stdout.as_slice());
+    let mut cargo_toml = archive
+        .entries()
+        .unwrap()
+        .map(Result::unwrap)
+        .find(|entry| {
+            entry.path().unwrap().as_ref() == std::path::Path::new("foobar-project/Cargo.toml")
+        })
+        .expect("Cargo.toml in the archive");
+    let mut content = String::new();
+    std::io::Read::read_to_string(&mut cargo_toml, &mut content).unwrap();
+    assert!(content.contains(r#"name = "foobar-project""#));
+    assert!(!dir.exists("foobar-project"));
+}
+
+#[test]
+fn it_layers_multiple_git_templates_in_order() {
+    let base = tmp_dir()
+        .init_default_template()
+        .file(
+            "cargo-generate.toml",
+            r#"[placeholders]
+license = { type = "string", prompt = "License?" }
+"#,
+        )
+        .file("README.md", "base {{license}}")
+        .file("LICENSE", "{{license}}")
+        .init_git()
+        .build();
+    let overlay = tmp_dir()
+        .file(
+            "cargo-generate.toml",
+            r#"[placeholders]
+ci = { type = "string", prompt = "CI?" }
+"#,
+        )
+        .file("README.md", "overlay {{license}} {{ci}}")
+        .file("ci.yml", "runs-on: {{ci}}")
+        .init_git()
+        .build();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(base.path())
+        .arg("--git")
+        .arg(overlay.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--silent")
+        .arg("-d")
+        .arg("license=MIT")
+        .arg("-d")
+        .arg("ci=ubuntu")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert!(dir
+        .read("foobar-project/Cargo.toml")
+        .contains(r#"name = "foobar-project""#));
+    assert_eq!(dir.read("foobar-project/LICENSE"), "MIT");
+    assert_eq!(dir.read("foobar-project/README.md"), "overlay MIT ubuntu");
+    assert_eq!(dir.read("foobar-project/ci.yml"), "runs-on: ubuntu");
+}
+
+#[test]
+fn it_formats_the_project_with_fmt() {
+    let template = tmp_dir()
+        .init_default_template()
+        .file("src/main.rs", "fn main(){println!(\"{{project-name}}\");}")
+        .build();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--path")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--fmt")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert_eq!(
+        dir.read("foobar-project/src/main.rs"),
+        "fn main() {\n    println!(\"foobar-project\");\n}\n"
+    );
+}
+
+#[test]
+fn it_skips_fmt_for_projects_without_cargo_toml() {
+    let template = tmp_dir().file("README.md", "{{project-name}}").build();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--path")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--fmt")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("cargo fmt").not().from_utf8());
+
+    assert_eq!(dir.read("foobar-project/README.md"), "foobar-project");
+}
+
+#[test]
+fn it_prints_the_name_and_description_of_the_template() {
+    let template = tmp_dir()
+        .init_default_template()
+        .file(
+            "cargo-generate.toml",
+            r#"[template]
+name = "Axum Service"
+description = "A web service with axum"
+"#,
+        )
+        .build();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--path")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(
+            predicates::str::contains("Generating 'Axum Service'")
+                .and(predicates::str::contains("A web service with axum"))
+                .from_utf8(),
+        );
+}
+
+#[test]
+fn it_prints_shell_completions() {
+    binary()
+        .arg("generate")
+        .arg("completions")
+        .arg("bash")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("--allow-commands").from_utf8())
+        .stdout(predicates::str::contains("--verify-checksums").from_utf8());
+}
+
+#[test]
+fn it_checks_a_template_without_generating() {
+    let template = tmp_dir()
+        .file(
+            "cargo-generate.toml",
+            r#"[placeholders]
+license = { type = "string", prompt = "License?", choices = ["MIT", "Apache-2.0"], default = "MIT" }
+
+[hooks]
+pre = ["pre.rhai"]
+"#,
+        )
+        .file("pre.rhai", r#"variable::set("license_file", "LICENSE");"#)
+        .file("Cargo.toml", "name = \"{{project-name}}\"")
+        .init_git()
+        .build();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("check")
+        .arg("--git")
+        .arg(template.path())
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done").from_utf8());
+
+    assert!(fs::read_dir(dir.path()).unwrap().next().is_none());
+}
+
+#[test]
+fn it_fails_the_check_of_a_broken_template() {
+    let template = tmp_dir()
+        .file(
+            "cargo-generate.toml",
+            r#"[placeholders]
+license = { type = "string", prompt = "License?", choices = [] }
+
+[hooks]
+post = ["post.rhai"]
+"#,
+        )
+        .file("post.rhai", "if true {")
+        .init_git()
+        .build();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("check")
+        .arg("--git")
+        .arg(template.path())
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stdout(
+            predicates::str::contains("`license`")
+                .and(predicates::str::contains("`post.rhai` doesn't compile"))
+                .from_utf8(),
+        )
+        .stderr(predicates::str::contains("2 problem(s) found").from_utf8());
+}
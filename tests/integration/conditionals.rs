@@ -91,3 +91,85 @@ ignore = ["included"]
         .read("foobar-project/excluded2")
         .contains("{{should-not-process}}"));
 }
+
+#[test]
+fn it_can_use_numbers_and_arrays_in_conditions() {
+    let template = tmp_dir()
+        .file(
+            "cargo-generate.toml",
+            r#"
+[placeholders]
+count = { type = "number", prompt = "How many?" }
+features = { type = "multiselect", prompt = "Features?", choices = ["serde", "clap"] }
+
+[conditional.'count > 2']
+ignore = ["few"]
+
+[conditional.'!features.contains("serde")']
+ignore = ["serde.rs"]
+"#,
+        )
+        .file("few", "only a few")
+        .file("serde.rs", "use serde;")
+        .init_git()
+        .build();
+
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("-d")
+        .arg("count=3")
+        .arg("-d")
+        .arg("features=serde")
+        .current_dir(&dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert!(!dir.exists("foobar-project/few"));
+    assert!(dir.exists("foobar-project/serde.rs"));
+}
+
+#[test]
+fn it_can_conditionally_ignore_files_per_platform() {
+    let template = tmp_dir()
+        .file(
+            "cargo-generate.toml",
+            format!(
+                r#"
+[conditional.'os == "{}" && arch == "{}"']
+ignore = ["this-platform"]
+
+[conditional.'os == "not-an-os"']
+ignore = ["other-platform"]
+"#,
+                std::env::consts::OS,
+                std::env::consts::ARCH
+            ),
+        )
+        .file("this-platform", "{{project-name}}")
+        .file("other-platform", "{{project-name}}")
+        .init_git()
+        .build();
+
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .current_dir(&dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert!(!dir.exists("foobar-project/this-platform"));
+    assert!(dir.exists("foobar-project/other-platform"));
+}
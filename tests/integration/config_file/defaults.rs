@@ -52,3 +52,78 @@ fn it_uses_ssh_identity_from_defaults_config() {
         .read("foo/Cargo.toml")
         .contains(r#"name = "foo""#));
 }
+
+#[test]
+fn it_caches_git_templates_if_enabled_in_defaults_config() {
+    let working_dir = tmp_dir().build();
+    let cache_dir = tmp_dir().build();
+    let config_dir = tmp_dir()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [defaults]
+                cache_templates = true
+            "#},
+        )
+        .build();
+    let some_template = create_template("some-template");
+
+    for name in ["foo", "bar"] {
+        binary()
+            .arg("generate")
+            .arg("--config")
+            .arg(config_dir.path().join("cargo-generate.toml"))
+            .arg("--name")
+            .arg(name)
+            .arg("--git")
+            .arg(some_template.path())
+            .env("CARGO_GENERATE_CACHE_DIR", cache_dir.path())
+            .current_dir(working_dir.path())
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("Done!").from_utf8());
+    }
+
+    let entries = std::fs::read_dir(cache_dir.path())
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(entries.len(), 1);
+    assert!(entries[0].path().join(".git").is_dir());
+    assert!(working_dir
+        .read("bar/Cargo.toml")
+        .contains(r#"name = "bar""#));
+}
+
+#[test]
+fn it_bypasses_the_template_cache_with_no_cache() {
+    let working_dir = tmp_dir().build();
+    let cache_dir = tmp_dir().build();
+    let config_dir = tmp_dir()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [defaults]
+                cache_templates = true
+            "#},
+        )
+        .build();
+    let some_template = create_template("some-template");
+
+    binary()
+        .arg("generate")
+        .arg("--config")
+        .arg(config_dir.path().join("cargo-generate.toml"))
+        .arg("--name")
+        .arg("foo")
+        .arg("--git")
+        .arg(some_template.path())
+        .arg("--no-cache")
+        .env("CARGO_GENERATE_CACHE_DIR", cache_dir.path())
+        .current_dir(working_dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(std::fs::read_dir(cache_dir.path()).unwrap().count(), 0);
+    assert!(working_dir.exists("foo/Cargo.toml"));
+}
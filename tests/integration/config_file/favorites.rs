@@ -297,3 +297,41 @@ fn favorites_default_value_can_be_overridden_by_environment() {
         .read("my-project/Cargo.toml")
         .contains(r#"description = "Overridden value""#));
 }
+
+#[test]
+fn it_requires_a_template_or_favorite_in_silent_mode() {
+    let favorite_template = create_template("favorite-template");
+    let (_config, config_path) = create_favorite_config("test", &favorite_template);
+    let working_dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--config")
+        .arg(config_path)
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--silent")
+        .current_dir(working_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("No template or favorite given").from_utf8());
+
+    assert!(!working_dir.exists("foobar-project"));
+}
+
+#[test]
+fn it_fails_without_template_if_there_are_no_favorites_to_choose_from() {
+    let config = tmp_dir().file("cargo-generate", "").build();
+    let working_dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--config")
+        .arg(config.path().join("cargo-generate"))
+        .arg("--name")
+        .arg("foobar-project")
+        .current_dir(working_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("no favorites defined").from_utf8());
+}
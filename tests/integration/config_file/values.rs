@@ -171,7 +171,6 @@ fn it_accepts_template_values_file_via_flag() {
         .arg("--template-values-file")
         .arg(template.path().join("my-values.toml"))
         .current_dir(&dir.path())
-        .env("CARGO_GENERATE_VALUE_MY_VALUE", "env-def-value")
         .assert()
         .success()
         .stdout(predicates::str::contains("Done!").from_utf8());
@@ -180,6 +179,239 @@ fn it_accepts_template_values_file_via_flag() {
     assert!(random_toml.contains("value = \"file-value\""));
 }
 
+#[test]
+fn it_accepts_arrays_and_tables_from_values_file() {
+    let template = tmp_dir()
+        .file(
+            "my-values.toml",
+            indoc! {r#"
+                [values]
+                features = ["serde", "tokio"]
+                limits = { workers = 4, ratio = 0.5 }
+            "#},
+        )
+        .file(
+            "random.toml",
+            indoc! {r#"
+                features = [{% for feature in features %}"{{feature}}",{% endfor %}]
+                workers = {{limits.workers}}
+                ratio = {{limits.ratio}}
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--template-values-file")
+        .arg(template.path().join("my-values.toml"))
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    let random_toml = dir.read("foobar-project/random.toml");
+    assert!(random_toml.contains(r#"features = ["serde","tokio",]"#));
+    assert!(random_toml.contains("workers = 4"));
+    assert!(random_toml.contains("ratio = 0.5"));
+}
+
+#[test]
+fn it_prefers_values_file_flag_over_environment_values_file() {
+    let template = tmp_dir()
+        .file(
+            "env-values.toml",
+            indoc! {r#"
+                [values]
+                my_value = "env-file-value"
+            "#},
+        )
+        .file(
+            "flag-values.toml",
+            indoc! {r#"
+                [values]
+                my_value = "flag-file-value"
+            "#},
+        )
+        .file(
+            "random.toml",
+            indoc! {r#"
+                value = "{{my_value}}"
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--values-file")
+        .arg(template.path().join("flag-values.toml"))
+        .env(
+            "CARGO_GENERATE_TEMPLATE_VALUES_FILE",
+            template.path().join("env-values.toml"),
+        )
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert!(dir
+        .read("foobar-project/random.toml")
+        .contains("value = \"flag-file-value\""));
+}
+
+#[test]
+fn it_fails_for_a_values_file_without_values_table() {
+    let template = tmp_dir()
+        .file("my-values.toml", "my_value = \"file-value\"")
+        .file("random.toml", "value = \"{{my_value}}\"")
+        .init_git()
+        .build();
+
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--values-file")
+        .arg(template.path().join("my-values.toml"))
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Values File Error").from_utf8());
+
+    assert!(!dir.exists("foobar-project"));
+}
+
+#[test]
+fn it_prefers_individual_values_from_environment_over_values_file() {
+    let template = tmp_dir()
+        .file(
+            "my-values.toml",
+            indoc! {r#"
+                [values]
+                my_value = "file-value"
+            "#},
+        )
+        .file(
+            "random.toml",
+            indoc! {r#"
+                value = "{{my_value}}"
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--template-values-file")
+        .arg(template.path().join("my-values.toml"))
+        .current_dir(dir.path())
+        .env("CARGO_GENERATE_VALUE_MY_VALUE", "env-def-value")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    let random_toml = dbg!(dir.read("foobar-project/random.toml"));
+    assert!(random_toml.contains("value = \"env-def-value\""));
+}
+
+#[test]
+fn it_prefers_defines_over_individual_values_from_environment() {
+    let template = tmp_dir()
+        .file(
+            "random.toml",
+            indoc! {r#"
+                value = "{{my_value}}"
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--define")
+        .arg("my_value=def-value")
+        .current_dir(dir.path())
+        .env("CARGO_GENERATE_VALUE_MY_VALUE", "env-def-value")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    let random_toml = dbg!(dir.read("foobar-project/random.toml"));
+    assert!(random_toml.contains("value = \"def-value\""));
+}
+
+#[test]
+fn it_accepts_values_from_json_with_defines_taking_precedence() {
+    let template = tmp_dir()
+        .file(
+            "random.toml",
+            indoc! {r#"
+                name = "{{my_name}}"
+                value = "{{my_value}}"
+                {% if use_serde %}serde = true{% endif %}
+            "#},
+        )
+        .init_git()
+        .build();
+    let values = tmp_dir()
+        .file(
+            "values.json",
+            r#"{"my_name": "from-json", "my_value": "from-json", "use_serde": true}"#,
+        )
+        .build();
+
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--define-from-json")
+        .arg(values.path().join("values.json"))
+        .arg("-d")
+        .arg("my_value=from-define")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    let random_toml = dir.read("foobar-project/random.toml");
+    assert!(random_toml.contains("name = \"from-json\""));
+    assert!(random_toml.contains("value = \"from-define\""));
+    assert!(random_toml.contains("serde = true"));
+}
+
 #[test]
 fn it_accepts_individual_template_values_via_flag() {
     let template = tmp_dir()
@@ -0,0 +1,176 @@
+use indoc::indoc;
+
+use assert_cmd::assert::OutputAssertExt;
+use predicates::prelude::*;
+use predicates::str::PredicateStrExt;
+
+use crate::helpers::{project::binary, project_builder::tmp_dir};
+
+#[test]
+fn it_lays_the_template_over_the_base_it_extends() {
+    let templates = tmp_dir()
+        .file(
+            "base/cargo-generate.toml",
+            indoc! {r#"
+            [template]
+            ignore = ["NOTES.md"]
+
+            [placeholders]
+            license = { type = "string", prompt = "License?", default = "MIT" }
+            ci = { type = "bool", prompt = "CI?", default = true }
+            "#},
+        )
+        .file(
+            "base/Cargo.toml",
+            indoc! {r#"
+            [package]
+            name = "{{project-name}}"
+            license = "{{license}}"
+            "#},
+        )
+        .file("base/README.md", "base readme")
+        .file("base/NOTES.md", "base notes")
+        .file(
+            "child/cargo-generate.toml",
+            indoc! {r#"
+            [template]
+            extends = "../base"
+
+            [placeholders]
+            license = { type = "string", prompt = "License?", default = "Apache-2.0" }
+            "#},
+        )
+        .file("child/README.md", "child readme, ci: {{ci}}")
+        .build();
+
+    let dir = tmp_dir().build();
+
+    // the placeholders of the base are merged, the default of the child wins
+    binary()
+        .arg("generate")
+        .arg("--path")
+        .arg(templates.path().join("child"))
+        .arg("--list-variables")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(
+            predicates::str::contains(r#"default: "Apache-2.0""#)
+                .and(predicates::str::contains(r#"default: "MIT""#).not())
+                .and(predicates::str::contains("ci (bool)"))
+                .from_utf8(),
+        );
+
+    // silent mode takes no defaults, so the values are given
+    binary()
+        .arg("generate")
+        .arg("--path")
+        .arg(templates.path().join("child"))
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--define")
+        .arg("license=Apache-2.0")
+        .arg("--define")
+        .arg("ci=true")
+        .arg("--silent")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert!(dir
+        .read("foobar-project/Cargo.toml")
+        .contains(r#"license = "Apache-2.0""#));
+    assert_eq!(
+        dir.read("foobar-project/README.md"),
+        "child readme, ci: true"
+    );
+    assert!(!dir.exists("foobar-project/NOTES.md"));
+    assert!(!dir.exists("foobar-project/cargo-generate.toml"));
+}
+
+#[test]
+fn it_fails_on_cyclic_extends() {
+    let templates = tmp_dir()
+        .file(
+            "a/cargo-generate.toml",
+            indoc! {r#"
+            [template]
+            extends = "../b"
+            "#},
+        )
+        .file(
+            "b/cargo-generate.toml",
+            indoc! {r#"
+            [template]
+            extends = "../a"
+            "#},
+        )
+        .build();
+
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--path")
+        .arg(templates.path().join("a"))
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--silent")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Cyclic `extends` of templates").from_utf8());
+
+    assert!(!dir.exists("foobar-project"));
+}
+
+#[test]
+fn it_refuses_to_verify_the_exec_hooks_of_a_base_template() {
+    let templates = tmp_dir()
+        .file(
+            "base/cargo-generate.toml",
+            indoc! {r#"
+            [hooks]
+            pre = [{ type = "exec", command = ["sh", "-c", "echo changed by the base"] }]
+            "#},
+        )
+        .file(
+            "child/cargo-generate.toml",
+            indoc! {r#"
+            [template]
+            extends = "../base"
+            "#},
+        )
+        .file("child/README.md", "child readme")
+        .build();
+    // the manifest pins the child only, the base may change its hooks at will
+    let manifest = tmp_dir()
+        .file(
+            "template.sha256",
+            "0000000000000000000000000000000000000000000000000000000000000000  README.md\n",
+        )
+        .build();
+
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--path")
+        .arg(templates.path().join("child"))
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--allow-commands")
+        .arg("--verify-checksums")
+        .arg(manifest.path().join("template.sha256"))
+        .arg("--silent")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(
+            predicates::str::contains("Can't verify the commands of a template that extends")
+                .from_utf8(),
+        );
+
+    assert!(!dir.exists("foobar-project"));
+}
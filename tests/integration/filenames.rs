@@ -49,3 +49,207 @@ fn it_substitutes_filename() {
         "project should not contain foobar-project/src/foobar-project/lib.rs.liquid"
     );
 }
+
+#[test]
+fn it_renames_files_based_on_placeholders() {
+    let template = tmp_dir()
+        .file("{{module_name}}.rs.liquid", r#"pub mod {{module_name}};"#)
+        .file(
+            "cargo-generate.toml",
+            r#"[placeholders]
+module_name = { prompt = "module?" }
+"#,
+        )
+        .init_git()
+        .build();
+
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("-d")
+        .arg("module_name=my_module")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert!(dir.exists("foobar-project/my_module.rs"));
+    assert!(!dir.exists("foobar-project/{{module_name}}.rs"));
+    assert!(!dir.exists("foobar-project/{{module_name}}.rs.liquid"));
+}
+
+#[test]
+fn it_fails_when_rendered_filenames_collide() {
+    let template = tmp_dir()
+        .file("{{module_name}}.rs", "rendered")
+        .file("my_module.rs.liquid", "static")
+        .init_git()
+        .build();
+
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("-d")
+        .arg("module_name=my_module")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Filename collision").from_utf8());
+}
+
+#[test]
+fn it_fails_when_a_filename_collides_with_a_file_failing_to_render() {
+    let template = tmp_dir()
+        .file("{{module_name}}.rs", "rendered")
+        .file("my_module.rs", "{{ broken")
+        .init_git()
+        .build();
+
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("-d")
+        .arg("module_name=my_module")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Filename collision").from_utf8());
+}
+
+#[test]
+fn it_fails_when_a_filename_renders_empty() {
+    let template = tmp_dir()
+        .file("{{module_name}}", "content")
+        .init_git()
+        .build();
+
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("{{module_name}}").from_utf8());
+}
+
+#[test]
+fn it_can_match_exclude_against_rendered_paths() {
+    let template = tmp_dir()
+        .file(
+            "cargo-generate.toml",
+            r#"[template]
+exclude = ["src/lib.rs"]
+match_rendered_paths = true
+"#,
+        )
+        .file("src/lib.rs.liquid", "// {{project-name}}")
+        .file("src/main.rs.liquid", "// {{project-name}}")
+        .init_git()
+        .build();
+
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert_eq!(dir.read("foobar-project/src/lib.rs"), "// {{project-name}}");
+    assert_eq!(dir.read("foobar-project/src/main.rs"), "// foobar-project");
+}
+
+#[test]
+fn it_renames_files_declared_in_template_rename() {
+    let template = tmp_dir()
+        .file(
+            "cargo-generate.toml",
+            r#"[template.rename]
+"gitignore" = ".gitignore"
+"#,
+        )
+        .file("gitignore", "/target\n# {{project-name}}\n")
+        .init_git()
+        .build();
+
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert!(!dir.exists("foobar-project/gitignore"));
+    assert_eq!(
+        dir.read("foobar-project/.gitignore"),
+        "/target\n# foobar-project\n"
+    );
+}
+
+#[test]
+fn it_only_renders_files_with_the_render_extension() {
+    let template = tmp_dir()
+        .file(
+            "cargo-generate.toml",
+            r#"[template]
+render_extension = ".tmpl"
+"#,
+        )
+        .file("src/main.rs.tmpl", "// {{project-name}}")
+        .file("README.md", "# {{project-name}}")
+        .file("templates/page.html.liquid", "<h1>{{ title }}</h1>")
+        .init_git()
+        .build();
+
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert_eq!(dir.read("foobar-project/src/main.rs"), "// foobar-project");
+    assert!(!dir.exists("foobar-project/src/main.rs.tmpl"));
+    assert_eq!(dir.read("foobar-project/README.md"), "# {{project-name}}");
+    assert_eq!(
+        dir.read("foobar-project/templates/page.html.liquid"),
+        "<h1>{{ title }}</h1>"
+    );
+}
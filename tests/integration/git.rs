@@ -74,6 +74,65 @@ fn it_removes_git_history_also_on_local_templates() {
     assert_eq!(0, repo.references().unwrap().count());
 }
 
+#[test]
+fn it_keeps_git_history_if_asked_to() {
+    let template = tmp_dir().init_default_template().build();
+    let template_head = Repository::open(template.path())
+        .unwrap()
+        .head()
+        .unwrap()
+        .peel_to_commit()
+        .unwrap()
+        .id();
+
+    for source in ["--git", "--path"] {
+        let dir = tmp_dir().build();
+
+        binary()
+            .arg("generate")
+            .arg(source)
+            .arg(template.path())
+            .arg("--name")
+            .arg("foobar-project")
+            .arg("--keep-history")
+            .current_dir(dir.path())
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("Done!").from_utf8());
+
+        let repo = Repository::open(dir.path().join("foobar-project")).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(template_head, head.id());
+        assert!(dir
+            .read("foobar-project/Cargo.toml")
+            .contains("foobar-project"));
+    }
+}
+
+#[test]
+fn it_refuses_to_keep_git_history_of_a_subfolder() {
+    let template = tmp_dir()
+        .file("inner/Cargo.toml", "name = \"{{project-name}}\"")
+        .init_git()
+        .build();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("inner")
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--keep-history")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("subfolder of the template").from_utf8());
+
+    assert!(!dir.exists("foobar-project"));
+}
+
 #[test]
 fn it_should_init_an_empty_git_repo_even_when_starting_from_a_repo_when_forced() {
     let template = tmp_dir().init_default_template().build();
@@ -149,3 +208,271 @@ fn should_find_them_all() {
         }
     }
 }
+
+#[test]
+fn it_allows_a_git_revision_to_be_specified() {
+    let template = tmp_dir().file("version.txt", "first").init_git().build();
+    let first_rev = Repository::open(template.path())
+        .unwrap()
+        .head()
+        .unwrap()
+        .peel_to_commit()
+        .unwrap()
+        .id()
+        .to_string();
+
+    std::fs::write(template.path().join("version.txt"), "second").unwrap();
+    std::process::Command::new("git")
+        .args(["commit", "--all", "--message", "second commit"])
+        .current_dir(template.path())
+        .assert()
+        .success();
+
+    let dir = tmp_dir().build();
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--rev")
+        .arg(&first_rev)
+        .arg("--name")
+        .arg("foobar-project")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert_eq!(dir.read("foobar-project/version.txt"), "first");
+}
+
+#[test]
+fn it_fails_for_an_unknown_git_revision() {
+    let template = tmp_dir().init_default_template().build();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--rev")
+        .arg("0123456789abcdef0123456789abcdef01234567")
+        .arg("--name")
+        .arg("foobar-project")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("does not exist in the fetched history").from_utf8());
+}
+
+#[test]
+fn it_refuses_rev_together_with_branch() {
+    let template = tmp_dir().init_default_template().build();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--branch")
+        .arg("main")
+        .arg("--rev")
+        .arg("0123456789abcdef0123456789abcdef01234567")
+        .arg("--name")
+        .arg("foobar-project")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("cannot be used with").from_utf8());
+}
+
+#[test]
+fn it_allows_a_git_tag_to_be_specified() {
+    let template = tmp_dir().file("version.txt", "v1.0").init_git().build();
+    std::process::Command::new("git")
+        .args(["tag", "v1.0"])
+        .current_dir(template.path())
+        .assert()
+        .success();
+
+    std::fs::write(template.path().join("version.txt"), "unreleased").unwrap();
+    std::process::Command::new("git")
+        .args(["commit", "--all", "--message", "second commit"])
+        .current_dir(template.path())
+        .assert()
+        .success();
+
+    let dir = tmp_dir().build();
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--tag")
+        .arg("v1.0")
+        .arg("--name")
+        .arg("foobar-project")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert_eq!(dir.read("foobar-project/version.txt"), "v1.0");
+}
+
+#[test]
+fn it_exposes_the_template_branch_and_ref() {
+    let template = tmp_dir()
+        .file(
+            "ci.yml",
+            "branch: {{template.branch}}\nref: {{template.ref}}",
+        )
+        .init_git()
+        .build();
+    std::process::Command::new("git")
+        .args(["tag", "v1.0"])
+        .current_dir(template.path())
+        .assert()
+        .success();
+
+    let dir = tmp_dir().build();
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--branch")
+        .arg("main")
+        .arg("--name")
+        .arg("on-branch")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--tag")
+        .arg("v1.0")
+        .arg("--name")
+        .arg("on-tag")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(dir.read("on-branch/ci.yml"), "branch: main\nref: main");
+    assert_eq!(dir.read("on-tag/ci.yml"), "branch: main\nref: v1.0");
+}
+
+#[test]
+fn it_leaves_the_template_branch_empty_for_path_templates() {
+    let template = tmp_dir()
+        .file(
+            "ci.yml",
+            "{% if template.branch %}branch: {{template.branch}}{% else %}local{% endif %}",
+        )
+        .build();
+
+    let dir = tmp_dir().build();
+    binary()
+        .arg("generate")
+        .arg("--path")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(dir.read("foobar-project/ci.yml"), "local");
+}
+
+#[test]
+fn it_fails_for_an_unknown_git_tag() {
+    let template = tmp_dir().init_default_template().build();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--tag")
+        .arg("v0.0.0-missing")
+        .arg("--name")
+        .arg("foobar-project")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Tag `v0.0.0-missing` does not exist").from_utf8());
+}
+
+#[test]
+fn it_refuses_tag_together_with_branch() {
+    let template = tmp_dir().init_default_template().build();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--branch")
+        .arg("main")
+        .arg("--tag")
+        .arg("v1.0")
+        .arg("--name")
+        .arg("foobar-project")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("cannot be used with").from_utf8());
+}
+
+#[test]
+fn it_names_the_initial_branch_after_the_checked_out_tag() {
+    let template = tmp_dir().init_default_template().build();
+    std::process::Command::new("git")
+        .args(["tag", "v1.0"])
+        .current_dir(template.path())
+        .assert()
+        .success();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--tag")
+        .arg("v1.0")
+        .arg("--name")
+        .arg("foobar-project")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    let repo = Repository::open(dir.path().join("foobar-project")).unwrap();
+    let head = repo.find_reference("HEAD").unwrap();
+    assert_eq!(head.symbolic_target(), Some("refs/heads/v1.0"));
+}
+
+#[test]
+fn it_names_the_initial_branch_as_given() {
+    let template = tmp_dir().init_default_template().branch("bak").build();
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--branch")
+        .arg("bak")
+        .arg("--name")
+        .arg("foobar-project")
+        .arg("--initial-branch")
+        .arg("trunk")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    let repo = Repository::open(dir.path().join("foobar-project")).unwrap();
+    let head = repo.find_reference("HEAD").unwrap();
+    assert_eq!(head.symbolic_target(), Some("refs/heads/trunk"));
+}
@@ -1,9 +1,21 @@
 use indoc::indoc;
 
 use assert_cmd::assert::OutputAssertExt;
+use predicates::prelude::*;
 use predicates::str::PredicateStrExt;
 
-use crate::helpers::{project::binary, project_builder::tmp_dir};
+use crate::helpers::{
+    project::binary,
+    project_builder::{tmp_dir, ProjectBuilder},
+};
+
+/// A template running the rhai `script`, saved as `file`, as its only `stage` hook
+fn template_with_hook(stage: &str, file: &str, script: &str) -> ProjectBuilder {
+    tmp_dir().file(file, script).file(
+        "cargo-generate.toml",
+        format!("[hooks]\n{} = [\"{}\"]\n", stage, file),
+    )
+}
 
 #[test]
 fn it_runs_all_hook_types() {
@@ -81,18 +93,290 @@ fn it_runs_all_hook_types() {
 
 #[test]
 fn it_runs_system_commands() {
+    let template = template_with_hook(
+        "post",
+        "system-script.rhai",
+        indoc! {r#"
+            let output = system::command("touch", ["touched_file"]);
+        "#},
+    )
+    .init_git()
+    .build();
+
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("gen")
+        .arg("--git")
+        .arg(template.path())
+        .arg("-n")
+        .arg("script-project")
+        .arg("--allow-commands")
+        .current_dir(&dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert!(dir.exists("script-project/touched_file"));
+}
+
+#[test]
+fn it_fails_to_prompt_for_system_commands_in_silent_mode() {
+    let template = template_with_hook(
+        "post",
+        "system-script.rhai",
+        indoc! {r#"
+            let output = system::command("touch", ["touched_file"]);
+        "#},
+    )
+    .init_git()
+    .build();
+
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("gen")
+        .arg("--git")
+        .arg(template.path())
+        .arg("-n")
+        .arg("script-project")
+        .arg("--silent")
+        .current_dir(&dir.path())
+        .assert()
+        .failure()
+        // The error message should instruct the user on how to proceed with silent mode (i.e. by setting the allow flag).
+        .stderr(predicates::str::contains("--allow-commands").from_utf8());
+}
+
+#[test]
+fn it_fails_when_a_system_command_returns_non_zero_exit_code() {
+    let template = template_with_hook(
+        "post",
+        "system-script.rhai",
+        indoc! {r#"
+            let output = system::command("mkdir", ["invalid_/.dir_name"]);
+        "#},
+    )
+    .init_git()
+    .build();
+
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("gen")
+        .arg("--git")
+        .arg(template.path())
+        .arg("-n")
+        .arg("script-project")
+        .arg("--allow-commands")
+        .current_dir(&dir.path())
+        .assert()
+        .failure()
+        .stderr(
+            predicates::str::contains(
+                "System command `mkdir invalid_/.dir_name` returned non-zero status",
+            )
+            .from_utf8(),
+        );
+}
+
+#[test]
+fn it_fails_when_it_cant_execute_system_command() {
+    let template = template_with_hook(
+        "post",
+        "system-script.rhai",
+        indoc! {r#"
+            let output = system::command("dummy_command_that_doesn't_exist", ["dummy_arg"]);
+        "#},
+    )
+    .init_git()
+    .build();
+
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("gen")
+        .arg("--git")
+        .arg(template.path())
+        .arg("-n")
+        .arg("script-project")
+        .arg("--allow-commands")
+        .current_dir(&dir.path())
+        .assert()
+        .failure()
+        .stderr(
+            predicates::str::contains(
+                "System command `dummy_command_that_doesn't_exist dummy_arg` failed to execute",
+            )
+            .from_utf8(),
+        );
+}
+
+#[test]
+fn it_can_change_case() {
+    let template = template_with_hook(
+        "pre",
+        "pre-script.rhai",
+        indoc! {r#"
+        print(to_kebab_case("kebab case"));
+        print(to_lower_camel_case("lower camel case"));
+        print(to_pascal_case("pascal case"));
+        print(to_shouty_kebab_case("shouty kebab case"));
+        print(to_shouty_snake_case("shouty snake case"));
+        print(to_snake_case("snake case"));
+        print(to_title_case("title case"));
+        print(to_upper_camel_case("upper camel case"));
+    "#},
+    )
+    .init_git()
+    .build();
+
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("gen")
+        .arg("--git")
+        .arg(template.path())
+        .arg("-n")
+        .arg("script-project")
+        .current_dir(&dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("kebab-case"))
+        .stdout(predicates::str::contains("lowerCamelCase"))
+        .stdout(predicates::str::contains("PascalCase"))
+        .stdout(predicates::str::contains("SHOUTY-KEBAB-CASE"))
+        .stdout(predicates::str::contains("SHOUTY_SNAKE_CASE"))
+        .stdout(predicates::str::contains("snake_case"))
+        .stdout(predicates::str::contains("Title Case"))
+        .stdout(predicates::str::contains("UpperCamelCase"));
+}
+
+#[test]
+fn pre_hooks_set_variables_for_the_rendered_files() {
+    let template = template_with_hook(
+        "pre",
+        "pre-script.rhai",
+        indoc! {r#"
+        let name = variable::get("project-name");
+        variable::set("service_name", name + "-service");
+        variable::set("workers", 2 * 4);
+        variable::set("with_metrics", true);
+    "#},
+    )
+    .file(
+        "config.toml",
+        indoc! {r#"
+        name = "{{service_name}}"
+        workers = {{workers}}
+        metrics = {{with_metrics}}
+    "#},
+    )
+    .init_git()
+    .build();
+
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("gen")
+        .arg("--git")
+        .arg(template.path())
+        .arg("-n")
+        .arg("script-project")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert_eq!(
+        dir.read("script-project/config.toml"),
+        indoc! {r#"
+            name = "script-project-service"
+            workers = 8
+            metrics = true
+        "#}
+    );
+}
+
+#[test]
+fn it_aborts_with_a_clean_message() {
+    let template = template_with_hook(
+        "pre",
+        "pre-script.rhai",
+        indoc! {r#"
+            fn check() {
+                abort("Please install `wasm-pack` first");
+            }
+            check();
+        "#},
+    )
+    .init_git()
+    .build();
+
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("gen")
+        .arg("--git")
+        .arg(template.path())
+        .arg("-n")
+        .arg("script-project")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(
+            predicates::str::contains("Template aborted: Please install `wasm-pack` first")
+                .and(predicates::str::contains("Failed executing script").not())
+                .from_utf8(),
+        );
+
+    assert!(!dir.exists("script-project"));
+}
+
+#[test]
+fn it_sandboxes_system_commands_without_allow_commands() {
+    let template = template_with_hook(
+        "post",
+        "system-script.rhai",
+        indoc! {r#"
+            let output = system::command("touch", ["touched_file"]);
+        "#},
+    )
+    .init_git()
+    .build();
+
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("gen")
+        .arg("--git")
+        .arg(template.path())
+        .arg("-n")
+        .arg("script-project")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("--allow-commands").from_utf8());
+
+    assert!(!dir.exists("script-project/touched_file"));
+}
+
+#[test]
+fn it_skips_all_hooks_with_deny_hooks() {
     let template = tmp_dir()
         .file(
-            "system-script.rhai",
+            "malicious-script.rhai",
             indoc! {r#"
-                let output = system::command("touch", ["touched_file"]);
+                file::write("pwned", "malicious content");
+                abort("the hook should never run");
             "#},
         )
         .file(
             "cargo-generate.toml",
             indoc! {r#"
             [hooks]
-            post = ["system-script.rhai"]
+            pre = ["malicious-script.rhai"]
+            post = ["malicious-script.rhai"]
             "#},
         )
         .init_git()
@@ -106,29 +390,47 @@ fn it_runs_system_commands() {
         .arg(template.path())
         .arg("-n")
         .arg("script-project")
-        .arg("--allow-commands")
-        .current_dir(&dir.path())
+        .arg("--deny-hooks")
+        .current_dir(dir.path())
         .assert()
         .success()
-        .stdout(predicates::str::contains("Done!").from_utf8());
+        .stdout(
+            predicates::str::contains("Skipping post hook malicious-script.rhai")
+                .and(predicates::str::contains("Done!"))
+                .from_utf8(),
+        );
 
-    assert!(dir.exists("script-project/touched_file"));
+    assert!(!dir.exists("script-project/pwned"));
+    assert!(!dir.exists("script-project/malicious-script.rhai"));
 }
 
 #[test]
-fn it_fails_to_prompt_for_system_commands_in_silent_mode() {
+fn it_skips_only_the_named_hooks_with_skip_hook() {
     let template = tmp_dir()
         .file(
-            "system-script.rhai",
+            "hooks/install-toolchain.rhai",
             indoc! {r#"
-                let output = system::command("touch", ["touched_file"]);
+                abort("the hook should never run");
+            "#},
+        )
+        .file(
+            "hooks/pre.rhai",
+            indoc! {r#"
+                file::write("pre-ran", "yes");
+            "#},
+        )
+        .file(
+            "hooks/post.rhai",
+            indoc! {r#"
+                file::write("post-ran", "yes");
             "#},
         )
         .file(
             "cargo-generate.toml",
             indoc! {r#"
             [hooks]
-            post = ["system-script.rhai"]
+            pre = ["hooks/install-toolchain.rhai", "hooks/pre.rhai"]
+            post = ["hooks/post.rhai"]
             "#},
         )
         .init_git()
@@ -142,28 +444,36 @@ fn it_fails_to_prompt_for_system_commands_in_silent_mode() {
         .arg(template.path())
         .arg("-n")
         .arg("script-project")
-        .arg("--silent")
-        .current_dir(&dir.path())
+        .arg("--skip-hook")
+        .arg("install-toolchain")
+        .current_dir(dir.path())
         .assert()
-        .failure()
-        // The error message should instruct the user on how to proceed with silent mode (i.e. by setting the allow flag).
-        .stderr(predicates::str::contains("--allow-commands").from_utf8());
+        .success()
+        .stdout(
+            predicates::str::contains("Skipping pre hook hooks/install-toolchain.rhai")
+                .and(predicates::str::contains("Done!"))
+                .from_utf8(),
+        );
+
+    assert_eq!(dir.read("script-project/pre-ran"), "yes");
+    assert_eq!(dir.read("script-project/post-ran"), "yes");
 }
 
+#[cfg(unix)]
 #[test]
-fn it_fails_when_a_system_command_returns_non_zero_exit_code() {
+fn it_runs_exec_hooks_with_variables_as_env_vars() {
     let template = tmp_dir()
         .file(
-            "system-script.rhai",
+            "hooks/write-name.sh",
             indoc! {r#"
-                let output = system::command("mkdir", ["invalid_/.dir_name"]);
+                echo "$CARGO_GENERATE_VAR_PROJECT_NAME" > name.txt
             "#},
         )
         .file(
             "cargo-generate.toml",
             indoc! {r#"
             [hooks]
-            post = ["system-script.rhai"]
+            post = [{ type = "exec", command = ["sh", "hooks/write-name.sh"], file = "hooks/write-name.sh" }]
             "#},
         )
         .init_git()
@@ -178,31 +488,24 @@ fn it_fails_when_a_system_command_returns_non_zero_exit_code() {
         .arg("-n")
         .arg("script-project")
         .arg("--allow-commands")
-        .current_dir(&dir.path())
+        .current_dir(dir.path())
         .assert()
-        .failure()
-        .stderr(
-            predicates::str::contains(
-                "System command `mkdir invalid_/.dir_name` returned non-zero status",
-            )
-            .from_utf8(),
-        );
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert_eq!(dir.read("script-project/name.txt"), "script-project\n");
+    assert!(!dir.exists("script-project/hooks/write-name.sh"));
 }
 
+#[cfg(unix)]
 #[test]
-fn it_fails_when_it_cant_execute_system_command() {
+fn it_fails_on_exec_hooks_without_allow_commands_or_on_non_zero_exit() {
     let template = tmp_dir()
-        .file(
-            "system-script.rhai",
-            indoc! {r#"
-                let output = system::command("dummy_command_that_doesn't_exist", ["dummy_arg"]);
-            "#},
-        )
         .file(
             "cargo-generate.toml",
             indoc! {r#"
             [hooks]
-            post = ["system-script.rhai"]
+            pre = [{ type = "exec", command = ["sh", "-c", "exit 3"] }]
             "#},
         )
         .init_git()
@@ -215,40 +518,51 @@ fn it_fails_when_it_cant_execute_system_command() {
         .arg("--git")
         .arg(template.path())
         .arg("-n")
-        .arg("script-project")
+        .arg("denied-project")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("--allow-commands").from_utf8());
+
+    binary()
+        .arg("gen")
+        .arg("--git")
+        .arg(template.path())
+        .arg("-n")
+        .arg("failing-project")
         .arg("--allow-commands")
-        .current_dir(&dir.path())
+        .current_dir(dir.path())
         .assert()
         .failure()
-        .stderr(
-            predicates::str::contains(
-                "System command `dummy_command_that_doesn't_exist dummy_arg` failed to execute",
-            )
-            .from_utf8(),
-        );
+        .stderr(predicates::str::contains("returned non-zero status").from_utf8());
+
+    assert!(!dir.exists("failing-project"));
 }
 
 #[test]
-fn it_can_change_case() {
+fn post_hooks_receive_the_generated_files() {
     let template = tmp_dir()
         .file(
-            "pre-script.rhai",
+            "post-script.rhai",
             indoc! {r#"
-            print(to_kebab_case("kebab case"));
-            print(to_lower_camel_case("lower camel case"));
-            print(to_pascal_case("pascal case"));
-            print(to_shouty_kebab_case("shouty kebab case"));
-            print(to_shouty_snake_case("shouty snake case"));
-            print(to_snake_case("snake case"));
-            print(to_title_case("title case"));
-            print(to_upper_camel_case("upper camel case"));
-        "#},
+                let files = generated_files();
+                if files.len() != 2 || files[0] != "README.md" || files[1] != "src/main.rs" {
+                    abort(`unexpected files: ${files}`);
+                }
+                file::write("files.txt", files);
+            "#},
         )
+        .file("README.md", "{{project-name}}")
+        .file("src/main.rs", "fn main() {}")
+        .file("ignored.txt", "not generated")
         .file(
             "cargo-generate.toml",
             indoc! {r#"
-            [hooks]
-            pre = ["pre-script.rhai"]
+                [template]
+                ignore = ["ignored.txt"]
+
+                [hooks]
+                post = ["post-script.rhai"]
             "#},
         )
         .init_git()
@@ -262,15 +576,41 @@ fn it_can_change_case() {
         .arg(template.path())
         .arg("-n")
         .arg("script-project")
-        .current_dir(&dir.path())
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert_eq!(
+        dir.read("script-project/files.txt"),
+        "README.md\nsrc/main.rs\n"
+    );
+}
+
+#[test]
+fn it_lists_but_does_not_run_hooks_on_dry_run() {
+    let template = template_with_hook(
+        "post",
+        "post-script.rhai",
+        indoc! {r#"
+        file::write("marker", "the hook ran");
+    "#},
+    )
+    .init_git()
+    .build();
+
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("gen")
+        .arg("--git")
+        .arg(template.path())
+        .arg("-n")
+        .arg("script-project")
+        .arg("--dry-run")
+        .current_dir(dir.path())
         .assert()
         .success()
-        .stdout(predicates::str::contains("kebab-case"))
-        .stdout(predicates::str::contains("lowerCamelCase"))
-        .stdout(predicates::str::contains("PascalCase"))
-        .stdout(predicates::str::contains("SHOUTY-KEBAB-CASE"))
-        .stdout(predicates::str::contains("SHOUTY_SNAKE_CASE"))
-        .stdout(predicates::str::contains("snake_case"))
-        .stdout(predicates::str::contains("Title Case"))
-        .stdout(predicates::str::contains("UpperCamelCase"));
+        .stdout(predicates::str::contains("Dry run, would run post hook").from_utf8());
+
+    assert!(!dir.exists("script-project"));
 }
@@ -1,5 +1,8 @@
 use crate::helpers::project_builder::tmp_dir;
-use cargo_generate::{generate, GenerateArgs, TemplatePath, Vcs};
+use cargo_generate::{
+    generate, generate_with_result, GenerateArgs, LogFormat, OutputFormat, Overwrite, TemplatePath,
+    Vcs,
+};
 
 #[test]
 fn it_allows_generate_call_with_public_args() {
@@ -20,28 +23,67 @@ version = "0.1.0"
     let args_exposed: GenerateArgs = GenerateArgs {
         template_path: TemplatePath {
             auto_path: None,
-            git: Some(format!("{}", template.path().display())),
+            git: vec![format!("{}", template.path().display())],
+            hg: None,
             branch: Some(String::from("main")),
+            tag: None,
+            rev: None,
             path: None,
+            archive: None,
+            github_release: None,
+            asset: None,
             favorite: None,
             subfolder: None,
+            subfolders: vec![],
         },
         name: Some(String::from("foobar_project")),
+        directory_name: None,
         force: true,
-        vcs: Vcs::Git,
+        vcs: Some(Vcs::Git),
+        profile: None,
+        recurse_submodules: false,
+        keep_history: false,
+        jobs: None,
+        no_rename: false,
+        log_format: LogFormat::Human,
+        ignore_rust_version: false,
+        respect_gitignore: true,
+        select_template: None,
+        command: None,
+        output_format: OutputFormat::Human,
         verbose: true,
+        quiet: false,
         template_values_file: None,
         silent: false,
         list_favorites: false,
+        list_variables: false,
+        print_template_dir: false,
         config: None,
         bin: true,
         lib: false,
         ssh_identity: None,
         define: vec![],
+        define_from_json: None,
         init: false,
         destination: None,
         force_git_init: false,
+        initial_branch: None,
         allow_commands: false,
+        deny_hooks: false,
+        skip_hooks: vec![],
+        ignore_required_commands: false,
+        verify_checksums: None,
+        dry_run: false,
+        fmt: false,
+        allow_symlinks: false,
+        to_stdout_tar: false,
+        offline: false,
+        clone_retries: 0,
+        no_cache: false,
+        no_progress: false,
+        overwrite: Overwrite::Never,
+        update: false,
+        keep_project_on_failure: false,
     };
     // need to cd to the dir as we aren't running in the cargo shell.
     assert!(std::env::set_current_dir(&dir.root).is_ok());
@@ -51,3 +93,97 @@ version = "0.1.0"
         .read("foobar_project/Cargo.toml")
         .contains("foobar_project"));
 }
+
+#[test]
+fn it_returns_what_has_been_generated() {
+    let template = tmp_dir()
+        .file(
+            "Cargo.toml",
+            r#"[package]
+name = "{{project-name}}"
+description = "A wonderful project"
+version = "0.1.0"
+"#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .init_git()
+        .build();
+
+    let dir = tmp_dir().build();
+
+    let args_exposed: GenerateArgs = GenerateArgs {
+        template_path: TemplatePath {
+            auto_path: None,
+            git: vec![format!("{}", template.path().display())],
+            hg: None,
+            branch: Some(String::from("main")),
+            tag: None,
+            rev: None,
+            path: None,
+            archive: None,
+            github_release: None,
+            asset: None,
+            favorite: None,
+            subfolder: None,
+            subfolders: vec![],
+        },
+        name: Some(String::from("foobar-project")),
+        directory_name: None,
+        force: false,
+        vcs: Some(Vcs::None),
+        profile: None,
+        recurse_submodules: false,
+        keep_history: false,
+        jobs: None,
+        no_rename: false,
+        log_format: LogFormat::Human,
+        ignore_rust_version: false,
+        respect_gitignore: true,
+        select_template: None,
+        command: None,
+        output_format: OutputFormat::Human,
+        verbose: false,
+        quiet: false,
+        template_values_file: None,
+        silent: true,
+        list_favorites: false,
+        list_variables: false,
+        print_template_dir: false,
+        config: None,
+        bin: true,
+        lib: false,
+        ssh_identity: None,
+        define: vec![],
+        define_from_json: None,
+        init: false,
+        destination: Some(dir.path().to_path_buf()),
+        force_git_init: false,
+        initial_branch: None,
+        allow_commands: false,
+        deny_hooks: false,
+        skip_hooks: vec![],
+        ignore_required_commands: false,
+        verify_checksums: None,
+        dry_run: false,
+        fmt: false,
+        allow_symlinks: false,
+        to_stdout_tar: false,
+        offline: false,
+        clone_retries: 0,
+        no_cache: false,
+        no_progress: false,
+        overwrite: Overwrite::Never,
+        update: false,
+        keep_project_on_failure: false,
+    };
+    let result = generate_with_result(args_exposed).unwrap();
+
+    assert_eq!(result.project_name, "foobar-project");
+    assert_eq!(result.project_dir, dir.path().join("foobar-project"));
+    assert!(result
+        .files
+        .contains(&dir.path().join("foobar-project/Cargo.toml")));
+    assert!(result
+        .files
+        .contains(&dir.path().join("foobar-project/src/main.rs")));
+}
@@ -3,8 +3,10 @@ mod helpers;
 // test modules go here
 mod basics;
 mod config_file;
+mod extends;
 mod filenames;
 mod git;
 mod hooks;
 mod library;
 mod online;
+mod placeholders;
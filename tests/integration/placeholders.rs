@@ -0,0 +1,511 @@
+use predicates::prelude::*;
+
+use std::process::Command;
+
+use crate::helpers::project::{binary, Project};
+use crate::helpers::project_builder::tmp_dir;
+
+use assert_cmd::prelude::*;
+use indoc::indoc;
+use sha2::{Digest, Sha256};
+
+/// Generates the `template` as `foobar-project` into `dir`, the placeholder values are up to the test
+fn generate(template: &Project, dir: &Project) -> Command {
+    let mut command = binary();
+    command
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--name")
+        .arg("foobar-project")
+        .current_dir(dir.path());
+    command
+}
+
+#[test]
+fn it_renders_number_placeholders_as_numbers() {
+    let template = tmp_dir()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders]
+                port = { type = "number", prompt = "Port?", min = 1024, max = 65535 }
+            "#},
+        )
+        .file("config.toml", "next_port = {{ port | plus: 1 }}")
+        .init_git()
+        .build();
+
+    let dir = tmp_dir().build();
+
+    generate(&template, &dir)
+        .arg("--silent")
+        .arg("-d")
+        .arg("port=8080")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert_eq!(dir.read("foobar-project/config.toml"), "next_port = 8081");
+}
+
+#[test]
+fn it_fails_for_number_placeholders_out_of_range() {
+    let template = tmp_dir()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders]
+                port = { type = "number", prompt = "Port?", min = 1024, max = 65535 }
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tmp_dir().build();
+
+    generate(&template, &dir)
+        .arg("--silent")
+        .arg("-d")
+        .arg("port=80")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("out of the allowed range `1024..=65535`").from_utf8());
+}
+
+#[test]
+fn it_fails_for_conditional_placeholders_out_of_range() {
+    let template = tmp_dir()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders]
+                server = { type = "bool", prompt = "Server?" }
+
+                [conditional.'server'.placeholders]
+                port = { type = "number", prompt = "Port?", min = 1024, max = 65535 }
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tmp_dir().build();
+
+    generate(&template, &dir)
+        .arg("--silent")
+        .arg("-d")
+        .arg("server=true")
+        .arg("-d")
+        .arg("port=80")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("out of the allowed range `1024..=65535`").from_utf8());
+}
+
+#[test]
+fn it_renders_multiselect_placeholders_as_arrays() {
+    let template = tmp_dir()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders]
+                features = { type = "multiselect", prompt = "Features?", choices = ["serde", "tokio", "clap"] }
+            "#},
+        )
+        .file(
+            "features.txt",
+            "{% for f in features %}{{ f }};{% endfor %}",
+        )
+        .init_git()
+        .build();
+
+    let dir = tmp_dir().build();
+
+    generate(&template, &dir)
+        .arg("--silent")
+        .arg("-d")
+        .arg("features=serde,clap")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert_eq!(dir.read("foobar-project/features.txt"), "serde;clap;");
+}
+
+#[test]
+fn it_fails_for_multiselect_values_not_in_choices() {
+    let template = tmp_dir()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders]
+                features = { type = "multiselect", prompt = "Features?", choices = ["serde", "tokio", "clap"] }
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tmp_dir().build();
+
+    generate(&template, &dir)
+        .arg("--silent")
+        .arg("-d")
+        .arg("features=serde,log")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("`log` is not a valid value").from_utf8());
+}
+
+#[test]
+fn it_parses_defined_values_as_toml_scalars() {
+    let template = tmp_dir()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders]
+                use_serde = { type = "bool", prompt = "Serde?" }
+            "#},
+        )
+        .file(
+            "lib.rs",
+            "{% if use_serde %}serde{% endif %}{% if count > 2 %} many{% endif %}",
+        )
+        .init_git()
+        .build();
+
+    let dir = tmp_dir().build();
+
+    generate(&template, &dir)
+        .arg("--silent")
+        .arg("-D")
+        .arg("use_serde=true")
+        .arg("--define")
+        .arg("count=3")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert_eq!(dir.read("foobar-project/lib.rs"), "serde many");
+}
+
+#[test]
+fn it_keeps_the_defined_text_for_string_placeholders() {
+    let template = tmp_dir()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders]
+                ver = { type = "string", prompt = "Version?" }
+                price = { type = "string", prompt = "Price?", choices = ["2.50", "3.00"] }
+            "#},
+        )
+        .file(
+            "Cargo.toml",
+            r#"ver = "{{ver}}", price = "{{price}}", x = {{x}}"#,
+        )
+        .init_git()
+        .build();
+
+    let dir = tmp_dir().build();
+
+    generate(&template, &dir)
+        .arg("--silent")
+        .arg("-d")
+        .arg("ver=0.10")
+        .arg("-d")
+        .arg("price=2.50")
+        .arg("-d")
+        .arg("x=0.10")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    assert_eq!(
+        dir.read("foobar-project/Cargo.toml"),
+        r#"ver = "0.10", price = "2.50", x = 0.1"#
+    );
+}
+
+#[test]
+fn it_fails_for_invalid_defined_values_in_silent_mode() {
+    let template = tmp_dir()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders]
+                phone_number = { type = "string", prompt = "Phone?", regex = "^[0-9]+$" }
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tmp_dir().build();
+
+    generate(&template, &dir)
+        .arg("--silent")
+        .arg("-d")
+        .arg("phone_number=call me")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("does not match configured regex").from_utf8());
+}
+
+#[test]
+fn it_shows_the_regex_hint_for_invalid_defined_values_in_silent_mode() {
+    let template = tmp_dir()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders]
+                phone_number = { type = "string", prompt = "Phone?", regex = "^[0-9]+$", regex_hint = "digits only" }
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tmp_dir().build();
+
+    generate(&template, &dir)
+        .arg("--silent")
+        .arg("-d")
+        .arg("phone_number=call me")
+        .assert()
+        .failure()
+        .stderr(
+            predicates::str::contains(
+                "`call me` is not a valid value for `phone_number`: digits only",
+            )
+            .from_utf8(),
+        );
+}
+
+#[test]
+fn it_lists_all_placeholders_without_generating() {
+    let template = tmp_dir()
+        .file(
+            "cargo-generate.toml",
+            r#"
+[placeholders]
+serde = { type = "bool", prompt = "Use serde?", default = false }
+
+[conditional.'serde'.placeholders]
+format = { type = "string", prompt = "Which format?", choices = ["json", "toml"], default = "json" }
+"#,
+        )
+        .init_git()
+        .build();
+
+    let dir = tmp_dir().build();
+
+    binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--list-variables")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(
+            predicates::str::contains("serde (bool): Use serde?")
+                .and(predicates::str::contains("format (string): Which format?"))
+                .and(predicates::str::contains("choices: json, toml"))
+                .and(predicates::str::contains("only if: serde"))
+                .from_utf8(),
+        );
+
+    assert!(std::fs::read_dir(dir.path()).unwrap().next().is_none());
+}
+
+#[test]
+fn it_lists_all_placeholders_as_json() {
+    let template = tmp_dir()
+        .file(
+            "cargo-generate.toml",
+            r#"
+[template]
+name = "Serde Service"
+description = "A service with optional serde support"
+
+[placeholders]
+serde = { type = "bool", prompt = "Use serde?", default = false }
+
+[conditional.'serde'.placeholders]
+format = { type = "string", prompt = "Which format?", choices = ["json", "toml"], default = "json" }
+"#,
+        )
+        .init_git()
+        .build();
+
+    let output = binary()
+        .arg("generate")
+        .arg("--git")
+        .arg(template.path())
+        .arg("--list-variables")
+        .arg("--output-format")
+        .arg("json")
+        .current_dir(template.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let listing: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(listing["name"], "Serde Service");
+    assert_eq!(
+        listing["description"],
+        "A service with optional serde support"
+    );
+    let variables = &listing["placeholders"];
+    assert_eq!(variables[0]["name"], "serde");
+    assert_eq!(variables[0]["type"], "bool");
+    assert_eq!(variables[0]["default"], false);
+    assert_eq!(variables[0]["condition"], serde_json::Value::Null);
+    assert_eq!(variables[1]["name"], "format");
+    assert_eq!(variables[1]["choices"], serde_json::json!(["json", "toml"]));
+    assert_eq!(variables[1]["condition"], "serde");
+}
+
+#[test]
+fn it_only_prompts_placeholders_whose_prompt_if_is_true() {
+    let template = tmp_dir()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+            [placeholders]
+            use_db = { type = "bool", prompt = "Use a database?" }
+            db_url = { type = "string", prompt = "Database url?", default = "sqlite://", prompt_if = "use_db" }
+            db_name = { type = "string", prompt = "Database name?", prompt_if = "use_db" }
+            "#},
+        )
+        .file(
+            "README.md",
+            indoc! {r#"
+            url: {{ db_url }}
+            {% if db_name %}name: {{ db_name }}{% endif %}
+            "#},
+        )
+        .init_git()
+        .build();
+
+    let dir = tmp_dir().build();
+
+    // `db_name` is neither prompted nor missing in silent mode
+    generate(&template, &dir)
+        .arg("--silent")
+        .arg("-d")
+        .arg("use_db=false")
+        .assert()
+        .success();
+
+    let readme = dir.read("foobar-project/README.md");
+    assert!(readme.contains("url: sqlite://"));
+    assert!(!readme.contains("name:"));
+}
+
+#[test]
+fn it_lets_the_authors_be_overridden() {
+    let template = tmp_dir()
+        .file(
+            "Cargo.toml",
+            r#"[package]
+name = "{{project-name}}"
+authors = ["{{authors}}"]
+description = "by {{username}}"
+"#,
+        )
+        .init_git()
+        .build();
+    let dir = tmp_dir().build();
+
+    generate(&template, &dir)
+        .arg("--define")
+        .arg("authors=Jane Doe <jane@example.com>")
+        .arg("--define")
+        .arg("username=jane")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Done!").from_utf8());
+
+    let cargo_toml = dir.read("foobar-project/Cargo.toml");
+    assert!(cargo_toml.contains(r#"authors = ["Jane Doe <jane@example.com>"]"#));
+    assert!(cargo_toml.contains(r#"description = "by jane""#));
+}
+
+#[test]
+fn it_prints_no_group_header_without_prompts() {
+    let template = tmp_dir()
+        .file(
+            "cargo-generate.toml",
+            indoc! {r#"
+                [placeholders]
+                db = { type = "string", prompt = "Database?", group = "Database options" }
+            "#},
+        )
+        .file("db.txt", "{{db}}")
+        .init_git()
+        .build();
+
+    let dir = tmp_dir().build();
+
+    generate(&template, &dir)
+        .arg("--silent")
+        .arg("--quiet")
+        .arg("-d")
+        .arg("db=pg")
+        .assert()
+        .success()
+        .stdout(
+            predicates::str::contains("Database options")
+                .not()
+                .from_utf8(),
+        )
+        .stderr(
+            predicates::str::contains("Database options")
+                .not()
+                .from_utf8(),
+        );
+
+    assert_eq!(dir.read("foobar-project/db.txt"), "pg");
+}
+
+#[cfg(unix)]
+#[test]
+fn it_verifies_the_checksum_of_the_config_before_running_a_choices_command() {
+    let placeholder = |command: &str| {
+        format!(
+            "[placeholders]\nversion = {{ type = \"string\", prompt = \"Version?\", choices = [\"1.0\"], choices_command = {} }}\n",
+            command
+        )
+    };
+    let dir = tmp_dir().build();
+    let reviewed = placeholder(r#"["git", "tag", "--list"]"#);
+    let tampered = placeholder(&format!(
+        r#"["touch", "{}"]"#,
+        dir.path().join("pwned").display()
+    ));
+    let template = tmp_dir()
+        .file("cargo-generate.toml", &tampered)
+        .init_git()
+        .build();
+    let manifest = tmp_dir()
+        .file(
+            "template.sha256",
+            format!(
+                "{:x}  cargo-generate.toml\n",
+                Sha256::digest(reviewed.as_bytes())
+            ),
+        )
+        .build();
+
+    generate(&template, &dir)
+        .arg("--silent")
+        .arg("--allow-commands")
+        .arg("-d")
+        .arg("version=1.0")
+        .arg("--verify-checksums")
+        .arg(manifest.path().join("template.sha256"))
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Checksum mismatch").from_utf8());
+
+    assert!(!dir.exists("pwned"));
+}